@@ -6,15 +6,16 @@ use data::tokens::Span;
 use syntax::async_util::UnparsedType;
 use syntax::errors::{ErrorSource, ParsingError, ParsingMessage};
 use syntax::program::code::{
-    EffectType, Effects, ExpressionType, FinalizedEffectType, FinalizedEffects, FinalizedExpression,
+    EffectType, Effects, Expression, ExpressionType, FinalizedEffectType, FinalizedEffects, FinalizedExpression,
 };
 use syntax::program::function::{CodeBody, FinalizedCodeBody};
+use syntax::program::r#struct::BOOL;
 use syntax::program::syntax::Syntax;
 use syntax::program::types::FinalizedTypes;
 use syntax::SimpleVariableManager;
 
 use crate::check_impl_call::check_impl_call;
-use crate::check_method_call::check_method_call;
+use crate::check_method_call::{check_method_call, record_call};
 use crate::check_operator::check_operator;
 use crate::degeneric::degeneric_type_fields;
 use crate::{get_return, CodeVerifier};
@@ -35,10 +36,13 @@ pub async fn verify_code(
             _ => {}
         }
 
-        body.push(FinalizedExpression::new(
-            line.expression_type.clone(),
-            verify_effect(code_verifier, variables, line.effect).await?,
-        ));
+        match check_select(code_verifier, variables, &line).await? {
+            Some(selected) => body.push(selected),
+            None => body.push(FinalizedExpression::new(
+                line.expression_type.clone(),
+                verify_effect(code_verifier, variables, line.effect).await?,
+            )),
+        }
 
         if check_return_type(line.expression_type, code_verifier, &mut body, variables, &code_verifier.syntax).await? {
             return Ok(FinalizedCodeBody::new(body.clone(), code.label.clone(), true));
@@ -52,6 +56,87 @@ pub async fn verify_code(
     return Ok(FinalizedCodeBody::new(body, code.label.clone(), false));
 }
 
+/// Turns an if/else that only returns one of two values of the same type, like if a > b { return a; } else { return b; },
+/// into a return of core's select, so it compiles to a conditional move instead of a branch.
+/// Select always evaluates both values, so only variables and literals are turned into one.
+async fn check_select(
+    code_verifier: &mut CodeVerifier<'_>,
+    variables: &mut SimpleVariableManager,
+    line: &Expression,
+) -> Result<Option<FinalizedExpression>, ParsingError> {
+    let (ExpressionType::Return(_), EffectType::CodeBody(code)) = (&line.expression_type, &line.effect.types) else {
+        return Ok(None);
+    };
+    let Some((condition, first, second)) = select_arms(code) else {
+        return Ok(None);
+    };
+
+    // The values are only variables and literals, so their types are found without checking them.
+    // Only the select or the branch is checked, so each value is checked once.
+    let first_type = arm_type(first, code_verifier, variables).await;
+    let second_type = arm_type(second, code_verifier, variables).await;
+    // Select takes both values as the same generic, so values of different types keep the branch
+    if first_type.is_none() || first_type != second_type {
+        return Ok(None);
+    }
+
+    let select = Effects::new(
+        line.effect.span.clone(),
+        EffectType::MethodCall(
+            None,
+            "math::select".to_string(),
+            vec![condition.clone(), first.clone(), second.clone()],
+            None,
+        ),
+    );
+    return Ok(Some(FinalizedExpression::new(
+        line.expression_type.clone(),
+        verify_effect(code_verifier, variables, select).await?,
+    )));
+}
+
+/// Gets the type of a variable or literal returned by an arm of an if/else, without checking it
+async fn arm_type(
+    arm: &Effects,
+    code_verifier: &CodeVerifier<'_>,
+    variables: &SimpleVariableManager,
+) -> Option<FinalizedTypes> {
+    let finalized = finalize_basic(arm).await?;
+    return get_return(&finalized.types, variables, &code_verifier.syntax).await;
+}
+
+/// Gets the condition and both returned values of a desugared if/else whose bodies only return a variable or literal
+fn select_arms(code: &CodeBody) -> Option<(&Effects, &Effects, &Effects)> {
+    let [condition, first, second] = code.expressions.as_slice() else {
+        return None;
+    };
+    let EffectType::CompareJump(condition, _, _) = &condition.effect.types else {
+        return None;
+    };
+    return Some((condition, returned_value(&first.effect)?, returned_value(&second.effect)?));
+}
+
+/// Gets the value returned by a body that only returns a variable or literal, followed by the jumps out of the if
+fn returned_value(effect: &Effects) -> Option<&Effects> {
+    let EffectType::CodeBody(body) = &effect.types else {
+        return None;
+    };
+    let (returning, jumps) = body.expressions.split_first()?;
+    if !matches!(returning.expression_type, ExpressionType::Return(_))
+        || !jumps.iter().all(|jump| matches!(jump.effect.types, EffectType::Jump(_)))
+    {
+        return None;
+    }
+    return match &returning.effect.types {
+        EffectType::LoadVariable(_)
+        | EffectType::Int(_, _)
+        | EffectType::Float(_)
+        | EffectType::Bool(_)
+        | EffectType::Char(_) => Some(&returning.effect),
+        _ => None,
+    };
+}
+
 /// Checks to make sure the return type matches in the code block.
 async fn check_return_type(
     line: ExpressionType,
@@ -99,10 +184,25 @@ async fn check_return_type(
     };
 }
 
+/// Verifies a single effect, erroring instead of overflowing the stack if effects are nested too deeply
+pub async fn verify_effect(
+    code_verifier: &mut CodeVerifier<'_>,
+    variables: &mut SimpleVariableManager,
+    effect: Effects,
+) -> Result<FinalizedEffects, ParsingError> {
+    if code_verifier.depth >= code_verifier.recursion_limit {
+        return Err(effect.span.make_error(ParsingMessage::ExpressionTooDeep(code_verifier.recursion_limit)));
+    }
+    code_verifier.depth += 1;
+    let output = verify_inner_effect(code_verifier, variables, effect).await;
+    code_verifier.depth -= 1;
+    return output;
+}
+
 /// Verifies a single effect
 #[async_recursion]
 // skipcq: RS-R1000 Match statements have complexity calculated incorrectly
-pub async fn verify_effect(
+async fn verify_inner_effect(
     code_verifier: &mut CodeVerifier<'_>,
     variables: &mut SimpleVariableManager,
     effect: Effects,
@@ -118,32 +218,39 @@ pub async fn verify_effect(
             effect.span.clone(),
             FinalizedEffectType::CodeBody(verify_code(code_verifier, &mut variables.clone(), body, false).await?),
         ),
-        EffectType::Set(first, second) => FinalizedEffects::new(
-            effect.span.clone(),
-            FinalizedEffectType::Set(
-                Box::new(verify_effect(code_verifier, variables, *first).await?),
-                Box::new(verify_effect(code_verifier, variables, *second).await?),
-            ),
-        ),
+        EffectType::Set(first, second) => {
+            check_assignable(&first, variables, &code_verifier.syntax)?;
+            FinalizedEffects::new(
+                effect.span.clone(),
+                FinalizedEffectType::Set(
+                    Box::new(verify_effect(code_verifier, variables, *first).await?),
+                    Box::new(verify_effect(code_verifier, variables, *second).await?),
+                ),
+            )
+        }
         EffectType::Operation(_, _) => check_operator(code_verifier, variables, effect).await?,
         EffectType::ImplementationCall(_, _, _, _, _) => check_impl_call(code_verifier, variables, effect).await?,
-        EffectType::MethodCall(_, _, _, _) => check_method_call(code_verifier, variables, effect).await?,
-        EffectType::CompareJump(effect, first, second) => FinalizedEffects::new(
-            effect.span.clone(),
-            FinalizedEffectType::CompareJump(
-                Box::new(verify_effect(code_verifier, variables, *effect).await?),
-                first,
-                second,
-            ),
-        ),
-        EffectType::CreateStruct(target, effects) => verify_create_struct(code_verifier, target, effects, variables).await?,
+        EffectType::MethodCall(_, _, _, _) => {
+            let call = check_method_call(code_verifier, variables, effect).await?;
+            record_call(&code_verifier.syntax, &call);
+            call
+        }
+        EffectType::CompareJump(effect, first, second) => {
+            let span = effect.span.clone();
+            let condition = verify_effect(code_verifier, variables, *effect).await?;
+            check_condition(&condition, code_verifier, variables).await?;
+            FinalizedEffects::new(span, FinalizedEffectType::CompareJump(Box::new(condition), first, second))
+        }
+        EffectType::CreateStruct(target, effects, base) => {
+            verify_create_struct(code_verifier, target, effects, base, variables).await?
+        }
         EffectType::Load(inner_effect, target) => {
             let output = verify_effect(code_verifier, variables, *inner_effect).await?;
             let types = get_return(&output.types, variables, &code_verifier.syntax).await.unwrap();
 
             FinalizedEffects::new(effect.span.clone(), FinalizedEffectType::Load(Box::new(output), target.clone(), types))
         }
-        EffectType::CreateVariable(name, inner_effect) => {
+        EffectType::CreateVariable(name, inner_effect, mutable) => {
             let effect = verify_effect(code_verifier, variables, *inner_effect).await?;
             let found;
             if let Some(temp_found) = get_return(&effect.types, variables, &code_verifier.syntax).await {
@@ -153,6 +260,12 @@ pub async fn verify_effect(
             };
 
             variables.variables.insert(name.clone(), found.clone());
+            // Shadowing a mutable variable with an immutable one makes it immutable
+            if mutable {
+                variables.mutable.insert(name.clone());
+            } else {
+                variables.mutable.remove(&name);
+            }
             FinalizedEffects::new(
                 effect.span.clone(),
                 FinalizedEffectType::CreateVariable(name.clone(), Box::new(effect), found),
@@ -179,6 +292,33 @@ pub async fn verify_effect(
     return Ok(output);
 }
 
+/// Checks the effect can be assigned to, either with = or a compound assignment like +=.
+/// It has to be a variable or a field of one, and since the 2025 edition the variable has to be mutable.
+pub(crate) fn check_assignable(
+    effect: &Effects,
+    variables: &SimpleVariableManager,
+    syntax: &Arc<Mutex<Syntax>>,
+) -> Result<(), ParsingError> {
+    return match place_variable(effect) {
+        Some(variable) if !variables.mutable.contains(variable) && syntax.lock().edition_of(effect.span.file) >= 2025 => {
+            Err(effect.span.make_error(ParsingMessage::AssignToImmutable(variable.clone())))
+        }
+        Some(_) => Ok(()),
+        None => Err(effect.span.make_error(ParsingMessage::AssignToTemporary())),
+    };
+}
+
+/// Gets the variable an assigned effect is in, or None if it can't be assigned to.
+/// Only variables or a chain of fields loaded from a variable can be assigned to.
+fn place_variable(effect: &Effects) -> Option<&String> {
+    return match &effect.types {
+        EffectType::LoadVariable(variable) => Some(variable),
+        EffectType::Load(inner, _) => place_variable(inner),
+        EffectType::Paren(inner) => place_variable(inner),
+        _ => None,
+    };
+}
+
 /// Separately handles a few basic effects to declutter the main function
 async fn finalize_basic(effects: &Effects) -> Option<FinalizedEffects> {
     return Some(FinalizedEffects::new(
@@ -198,11 +338,28 @@ async fn finalize_basic(effects: &Effects) -> Option<FinalizedEffects> {
     ));
 }
 
+/// Checks that the condition of an if, while or for is a bool, since there are no implicit conversions to bool
+async fn check_condition(
+    condition: &FinalizedEffects,
+    code_verifier: &CodeVerifier<'_>,
+    variables: &SimpleVariableManager,
+) -> Result<(), ParsingError> {
+    let found = match get_return(&condition.types, variables, &code_verifier.syntax).await {
+        Some(found) => found,
+        None => return Err(condition.span.make_error(ParsingMessage::UnexpectedVoid())),
+    };
+    if !found.of_type(&FinalizedTypes::Struct(BOOL.clone()), code_verifier.syntax.clone()).await {
+        return Err(condition.span.make_error(ParsingMessage::ExpectedBool(found)));
+    }
+    return Ok(());
+}
+
 /// Verifies a CreateStruct call
 async fn verify_create_struct(
     code_verifier: &mut CodeVerifier<'_>,
     target: UnparsedType,
     effects: Vec<(String, Effects)>,
+    base: Option<Box<Effects>>,
     variables: &mut SimpleVariableManager,
 ) -> Result<FinalizedEffects, ParsingError> {
     let mut target = Syntax::parse_type(
@@ -242,7 +399,64 @@ async fn verify_create_struct(
         final_effects.push((i, final_effect));
     }
 
+    let mut base_type = None;
+    if let Some(base) = base {
+        let error = base.span.clone();
+        let base = verify_effect(code_verifier, variables, *base).await?;
+        let found = get_return(&base.types, variables, &code_verifier.syntax).await.unwrap();
+        // The base is always evaluated once, into a variable no other update in scope uses
+        let mut id = variables.variables.len();
+        while variables.variables.contains_key(&format!("$update{}", id)) {
+            id += 1;
+        }
+        let variable = format!("$update{}", id);
+        variables.variables.insert(variable.clone(), found.clone());
+
+        let copying = (0..fields.len()).filter(|i| !final_effects.iter().any(|(set, _)| set == i)).collect::<Vec<_>>();
+        for i in &copying {
+            if !found.get_fields().iter().any(|field| field.field.name == fields[*i].field.name) {
+                return Err(error.make_error(ParsingMessage::UnknownField(fields[*i].field.name.clone())));
+            }
+        }
+
+        // The base is stored in the variable first. If every field is set, the field loaded from it is overwritten
+        // by the field's value after, so the base is still evaluated.
+        let stored = FinalizedEffectType::CreateVariable(variable.clone(), Box::new(base), found.clone());
+        let mut loads = vec![(copying.first().copied().unwrap_or(0), Some(stored))];
+        loads.extend(copying.iter().skip(1).map(|i| (*i, None)));
+        let mut copied = Vec::default();
+        for (i, stored) in loads {
+            let loading = stored.unwrap_or_else(|| FinalizedEffectType::LoadVariable(variable.clone()));
+            let load = FinalizedEffects::new(
+                error.clone(),
+                FinalizedEffectType::Load(
+                    Box::new(FinalizedEffects::new(error.clone(), loading)),
+                    fields[i].field.name.clone(),
+                    found.clone(),
+                ),
+            );
+            if copying.contains(&i) {
+                get_return(&load.types, variables, &code_verifier.syntax)
+                    .await
+                    .unwrap()
+                    .resolve_generic(&fields[i].field.field_type, &code_verifier.syntax, &mut generics, error.clone())
+                    .await?;
+            }
+            copied.push((i, load));
+        }
+        // The first load stores the base, so it goes before the fields that are set
+        final_effects.insert(0, copied.remove(0));
+        final_effects.extend(copied);
+        base_type = Some((found, error));
+    }
+
     degeneric_type_fields(&mut target, &mut generics, &code_verifier.syntax).await;
+    // Fields are copied by name, so the base has to be the same type as the struct being created
+    if let Some((found, error)) = base_type {
+        if !found.of_type(&target, code_verifier.syntax.clone()).await {
+            return Err(error.make_error(ParsingMessage::MismatchedTypes(found, target)));
+        }
+    }
     return Ok(FinalizedEffects::new(
         Span::default(),
         FinalizedEffectType::CreateStruct(
@@ -101,6 +101,9 @@ pub async fn verify_function_code(
         resolver,
         return_type: codeless.return_type.clone(),
         syntax: syntax.clone(),
+        depth: 0,
+        recursion_limit: syntax.lock().recursion_limit,
+        unsafe_code: is_modifier(codeless.data.modifiers, Modifier::Unsafe),
     };
 
     let mut code = verify_code(&mut code_verifier, &mut variable_manager, code, true).await?;
@@ -2,13 +2,13 @@ use std::mem;
 
 use data::tokens::Span;
 use syntax::async_util::{AsyncDataGetter, UnparsedType};
-use syntax::errors::{ErrorSource, ParsingError, ParsingMessage};
+use syntax::errors::{fix_type, ErrorSource, ParsingError, ParsingMessage};
 use syntax::program::code::{EffectType, Effects, FinalizedEffectType, FinalizedEffects};
 use syntax::program::r#struct::VOID;
 use syntax::program::syntax::Syntax;
 use syntax::program::types::FinalizedTypes;
 use syntax::top_element_manager::ImplWaiter;
-use syntax::SimpleVariableManager;
+use syntax::{FinishedTraitImplementor, SimpleVariableManager};
 
 use crate::check_code::verify_effect;
 use crate::check_method_call::check_method;
@@ -87,7 +87,11 @@ pub async fn check_impl_call(
         if output.is_none() {
             output = try_get_impl(&impl_checker, &effect.span).await?;
             if output.is_none() {
-                return Err(calling.span.make_error(ParsingMessage::NoTraitImpl(calling_type, trait_type)));
+                let reasons = explain_impls(&impl_checker, &effect.span).await?;
+                if reasons.is_empty() {
+                    return Err(calling.span.make_error(ParsingMessage::NoTraitImpl(calling_type, trait_type)));
+                }
+                return Err(calling.span.make_error(ParsingMessage::NoMatchingImpl(calling_type, trait_type, reasons)));
             }
         }
 
@@ -207,34 +211,23 @@ async fn check_virtual_type(data: &mut ImplCheckerData<'_>, token: &Span) -> Res
 
 /// Tries to get an implementation matching the types passed in
 async fn try_get_impl(data: &ImplCheckerData<'_>, span: &Span) -> Result<Option<FinalizedEffects>, ParsingError> {
-    let result = ImplWaiter {
+    // The waiter only errors once every implementation is parsed and none are for the type, which explain_impls reports
+    let result = match (ImplWaiter {
         syntax: data.code_verifier.syntax.clone(),
         base_type: data.calling_type.clone(),
         trait_type: data.trait_type.clone(),
         error: span.make_error(ParsingMessage::NoTraitImpl(data.calling_type.clone(), data.trait_type.clone())),
-    }
-    .await?;
+    })
+    .await
+    {
+        Ok(result) => result,
+        Err(_) => return Ok(None),
+    };
 
     for temp in result.iter().flat_map(|(_, inner)| inner) {
         if temp.name.split("::").last().unwrap() == data.method || data.method.is_empty() {
             let method = AsyncDataGetter::new(data.code_verifier.syntax.clone(), temp.clone()).await;
-
-            let returning = match &data.explicit_generics {
-                Some(inner) => Some((
-                    Syntax::parse_type(
-                        data.code_verifier.syntax.clone(),
-                        span.clone(),
-                        data.code_verifier.resolver.boxed_clone(),
-                        inner.clone(),
-                        vec![],
-                    )
-                    .await?
-                    .finalize(data.code_verifier.syntax.clone())
-                    .await,
-                    span.clone(),
-                )),
-                None => None,
-            };
+            let returning = explicit_returning(data, span).await?;
 
             match check_method(
                 method.clone(),
@@ -253,3 +246,111 @@ async fn try_get_impl(data: &ImplCheckerData<'_>, span: &Span) -> Result<Option<
     }
     return Ok(None);
 }
+
+/// Parses the explicit generic return type of the call, if it has one
+async fn explicit_returning(
+    data: &ImplCheckerData<'_>,
+    span: &Span,
+) -> Result<Option<(FinalizedTypes, Span)>, ParsingError> {
+    return Ok(match data.explicit_generics {
+        Some(inner) => Some((
+            Syntax::parse_type(
+                data.code_verifier.syntax.clone(),
+                span.clone(),
+                data.code_verifier.resolver.boxed_clone(),
+                inner.clone(),
+                vec![],
+            )
+            .await?
+            .finalize(data.code_verifier.syntax.clone())
+            .await,
+            span.clone(),
+        )),
+        None => None,
+    });
+}
+
+/// Explains why each implementation of the trait doesn't fit the call, for the error when none do.
+/// Each reason is the implementation and the first thing about it that didn't match, which is either the type it's for
+/// or one of its generic bounds, the method's name, or the type of an argument.
+async fn explain_impls(data: &ImplCheckerData<'_>, span: &Span) -> Result<Vec<String>, ParsingError> {
+    let returning = explicit_returning(data, span).await?;
+    let implementations = data.code_verifier.syntax.lock().trait_implementations(data.trait_type);
+    let mut reasons = Vec::default();
+    for implementation in implementations {
+        if let Some(reason) = explain_impl(data, &implementation, returning.clone(), span).await {
+            reasons.push(format!(
+                "impl {} for {}: {}",
+                fix_type(&implementation.target),
+                fix_type(&implementation.base),
+                reason
+            ));
+        }
+    }
+    return Ok(reasons);
+}
+
+/// Explains why a single implementation doesn't fit the call, or None if it does
+async fn explain_impl(
+    data: &ImplCheckerData<'_>,
+    implementation: &FinishedTraitImplementor,
+    returning: Option<(FinalizedTypes, Span)>,
+    span: &Span,
+) -> Option<String> {
+    let syntax = &data.code_verifier.syntax;
+    let calling_type = data.calling_type;
+    let method = data.method;
+    if !calling_type.of_type(&implementation.base, syntax.clone()).await {
+        // Blanket implementations like impl<T: Bound> Trait for T can only fail because of their bounds
+        if let FinalizedTypes::Generic(name, bounds) = &implementation.base {
+            for bound in bounds {
+                if !calling_type.of_type(bound, syntax.clone()).await {
+                    return Some(format!("{} doesn't meet the bound {}: {}", fix_type(calling_type), name, fix_type(bound)));
+                }
+            }
+        }
+        return Some(format!("it's for {}, not {}", fix_type(&implementation.base), fix_type(calling_type)));
+    }
+
+    let Some(found) = implementation
+        .functions
+        .iter()
+        .find(|function| method.is_empty() || function.name.split("::").last().unwrap() == method)
+    else {
+        let methods =
+            implementation.functions.iter().map(|function| function.name.split("::").last().unwrap()).collect::<Vec<_>>();
+        return Some(format!("it has no method {}, only {}", method, methods.join(", ")));
+    };
+
+    let function = AsyncDataGetter::new(syntax.clone(), found.clone()).await;
+    let effects = &*data.finalized_effects;
+    if function.arguments.len() != effects.len() {
+        return Some(format!("{} takes {} arguments, but was given {}", method, function.arguments.len(), effects.len()));
+    }
+    // Arguments are counted from 1, including self
+    for (position, (argument, effect)) in function.arguments.iter().zip(effects).enumerate() {
+        let expected = &argument.field.field_type;
+        let found = match get_return(&effect.types, data.variables, syntax).await {
+            Some(found) => found,
+            None => {
+                return Some(format!("argument {} ({}) of {} was given void", position + 1, argument.field.name, method))
+            }
+        };
+        if !found.of_type(expected, syntax.clone()).await {
+            return Some(format!(
+                "argument {} ({}) of {} is a {}, but was given {}",
+                position + 1,
+                argument.field.name,
+                method,
+                fix_type(expected),
+                fix_type(&found)
+            ));
+        }
+    }
+
+    // Each argument fits on its own, so it's the rest of the method check that fails, like generics or the return type
+    return match check_method(function, effects.clone(), syntax, data.variables, returning, span).await {
+        Ok(_) => None,
+        Err(error) => Some(error.message.to_string()),
+    };
+}
@@ -1,10 +1,11 @@
 use parking_lot::Mutex;
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use data::tokens::Span;
 use syntax::async_util::AsyncDataGetter;
 use syntax::errors::{ErrorSource, ParsingError, ParsingMessage};
-use syntax::program::code::{EffectType, Effects, FinalizedEffectType, FinalizedEffects};
+use syntax::program::code::{EffectType, Effects, FinalizedEffectType, FinalizedEffects, InlineCode};
 use syntax::program::function::{CodelessFinalizedFunction, FunctionData};
 use syntax::program::syntax::Syntax;
 use syntax::program::types::FinalizedTypes;
@@ -52,7 +53,13 @@ pub async fn check_method_call(
         None => None,
     };
 
+    // llvm! and asm! aren't functions, their code is spliced straight into this one
+    if calling.is_none() && (method == "llvm!" || method == "asm!") {
+        return check_inline_code(code_verifier, method == "asm!", finalized_effects, returning, &effect.span);
+    }
+
     // Finds methods based off the calling type.
+    let called_on_value = calling.is_some();
     let method = if let Some(found) = calling {
         let calling = verify_effect(code_verifier, variables, *found).await?;
         let return_type: FinalizedTypes = get_return(&calling.types, variables, &code_verifier.syntax).await.unwrap();
@@ -90,7 +97,8 @@ pub async fn check_method_call(
         }
 
         // If it's a trait, handle virtual method calls.
-        if is_modifier(return_type.inner_struct().data.modifiers, Modifier::Trait) {
+        // Arrays have no structure, their methods all come from trait impls like impl<T> Array<T> for [T]
+        if return_type.inner_struct_safe().is_some_and(|inner| is_modifier(inner.data.modifiers, Modifier::Trait)) {
             finalized_effects.insert(0, calling);
 
             let method = Syntax::get_function(
@@ -103,6 +111,7 @@ pub async fn check_method_call(
             .await?;
             let method = AsyncDataGetter::new(code_verifier.syntax.clone(), method).await;
 
+            check_receiver(&method, true, finalized_effects.len(), &effect.span)?;
             check_args(&method, &mut finalized_effects, &code_verifier.syntax, variables, &effect.span).await?;
 
             let index = return_type.inner_struct().data.functions.iter().position(|found| *found == method.data).unwrap();
@@ -124,11 +133,14 @@ pub async fn check_method_call(
         .await
         {
             value
+        } else if let Some(value) = find_impl_method(&code_verifier.syntax, &return_type, &method).await {
+            value
         } else {
             let checker = async |implementor: Arc<FinishedTraitImplementor>,
                                  method: Arc<FunctionData>|
                    -> Result<FinalizedEffects, ParsingError> {
                 let method = AsyncDataGetter::new(code_verifier.syntax.clone(), method).await;
+                check_receiver(&method, true, finalized_effects.len(), &effect.span)?;
                 let mut process_manager = code_verifier.process_manager.clone();
                 implementor
                     .base
@@ -178,6 +190,8 @@ pub async fn check_method_call(
                     for function in &implementor.functions {
                         if function.name.split("::").last().unwrap() == possible[possible.len() - 1] {
                             let method = AsyncDataGetter::new(code_verifier.syntax.clone(), function.clone()).await;
+                            // Calling an instance method without a value is a mistake, not another candidate
+                            check_receiver(&method, false, finalized_effects.len(), &effect.span)?;
                             match check_method(
                                 method,
                                 finalized_effects.clone(),
@@ -189,7 +203,8 @@ pub async fn check_method_call(
                             .await
                             {
                                 Ok(result) => return Ok(result),
-                                Err(error) => println!("Error: {}", error.message),
+                                // Other candidates are tried next, so a failed candidate isn't an error yet
+                                Err(_) => {}
                             }
                         }
                     }
@@ -208,9 +223,111 @@ pub async fn check_method_call(
     };
 
     let method = AsyncDataGetter::new(code_verifier.syntax.clone(), method).await;
+    check_receiver(&method, called_on_value, finalized_effects.len(), &effect.span)?;
     return check_method(method, finalized_effects, &code_verifier.syntax, variables, returning, &effect.span).await;
 }
 
+/// Records the function a call was resolved to at the call's span, if the syntax is recording call sites
+pub fn record_call(syntax: &Arc<Mutex<Syntax>>, call: &FinalizedEffects) {
+    let function = match &call.types {
+        FinalizedEffectType::MethodCall(_, function, _, _)
+        | FinalizedEffectType::GenericMethodCall(function, _, _)
+        | FinalizedEffectType::VirtualCall(_, function, _, _) => &function.data,
+        FinalizedEffectType::GenericVirtualCall(_, function, _, _, _) => function,
+        _ => return,
+    };
+    if let Some(call_sites) = &mut syntax.lock().call_sites {
+        call_sites.entry(function.name.clone()).or_default().push(call.span.clone());
+    }
+}
+
+/// Checks a call to llvm! or asm!, which can only be used in unsafe functions and starts with its code as string literals.
+/// The rest of the arguments are its inputs, and the generic type, like llvm!<u64>(...), is its output.
+fn check_inline_code(
+    code_verifier: &CodeVerifier<'_>,
+    asm: bool,
+    mut arguments: Vec<FinalizedEffects>,
+    returning: Option<(FinalizedTypes, Span)>,
+    span: &Span,
+) -> Result<FinalizedEffects, ParsingError> {
+    let (name, example, strings) = if asm {
+        ("asm", "asm!<u64>(\"mov $1, $0\", \"=r,r\", value)", 2)
+    } else {
+        ("llvm", "llvm!<u64>(\"ret i64 %0\", value)", 1)
+    };
+    if !code_verifier.unsafe_code {
+        return Err(span.make_error(ParsingMessage::UnsafeInlineCode(name)));
+    }
+    if arguments.len() < strings {
+        return Err(span.make_error(ParsingMessage::InvalidInlineCode(name, example)));
+    }
+    let code = arguments
+        .drain(..strings)
+        .map(|argument| string_literal(&argument))
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| span.make_error(ParsingMessage::InvalidInlineCode(name, example)))?;
+    let code = if asm { InlineCode::Asm(code[0].clone(), code[1].clone()) } else { InlineCode::Llvm(code[0].clone()) };
+
+    let output = returning.map(|(output, _)| output);
+    let inline = FinalizedEffectType::InlineCode(code, arguments, output.clone());
+    // The output is a value like a literal, so it's stored on the heap the same way
+    return Ok(FinalizedEffects::new(
+        span.clone(),
+        match output {
+            Some(_) => FinalizedEffectType::HeapStore(Box::new(FinalizedEffects::new(span.clone(), inline))),
+            None => inline,
+        },
+    ));
+}
+
+/// Gets the string an argument was given as a literal, which is stored on the heap like every literal
+fn string_literal(argument: &FinalizedEffects) -> Option<String> {
+    return match &argument.types {
+        FinalizedEffectType::String(string) => Some(string.clone()),
+        FinalizedEffectType::HeapStore(inner) => string_literal(inner),
+        _ => None,
+    };
+}
+
+/// Finds a method by name in the impl blocks of a type without a trait, like impl MyStruct { ... }
+/// Primitives are internal structures, so this also finds methods from impls like impl str { ... }
+async fn find_impl_method(syntax: &Arc<Mutex<Syntax>>, target: &FinalizedTypes, method: &str) -> Option<Arc<FunctionData>> {
+    // Impls are stored by the structure itself, not a reference to it
+    let target = FinalizedTypes::Struct(target.inner_struct_safe()?.clone());
+    for implementor in Syntax::get_struct_impl(syntax.clone(), target).await {
+        for function in &implementor.functions {
+            if function.name.split("::").last().unwrap() == method {
+                return Some(function.clone());
+            }
+        }
+    }
+    return None;
+}
+
+/// Checks that a method is called the way it's declared: instance methods taking self are called on a value,
+/// and associated functions without self are called on the type.
+/// Instance methods can still be called on the type if the receiver is passed explicitly, like Type::method(value).
+fn check_receiver(
+    method: &CodelessFinalizedFunction,
+    called_on_value: bool,
+    arguments: usize,
+    span: &Span,
+) -> Result<(), ParsingError> {
+    // Free functions have no receiver either way, so they can't be called the wrong way
+    if method.parent.is_none() {
+        return Ok(());
+    }
+
+    let name = method.data.name.split("::").last().unwrap().to_string();
+    if called_on_value && !method.has_receiver() {
+        return Err(span.make_error(ParsingMessage::NotAMethod(name)));
+    }
+    if !called_on_value && method.has_receiver() && arguments + 1 == method.arguments.len() {
+        return Err(span.make_error(ParsingMessage::MissingReceiver(name)));
+    }
+    return Ok(());
+}
+
 /// Checks if a method call is valid
 /// The CheckerVariableManager here is used for the effects calling the method
 pub async fn check_method(
@@ -264,6 +381,10 @@ pub async fn check_args(
         return Err(span.make_error(ParsingMessage::MissingArgument()));
     }
 
+    // Since the 2025 edition, arguments sharing a generic have to be the same type as the first argument declared with it.
+    // Before that, later arguments silently replaced the type, so count(1, true) compiles in 2024 but not in 2025
+    let mut generics: HashMap<String, FinalizedTypes> = HashMap::default();
+    let matching_generics = syntax.lock().edition_of(span.file) >= 2025;
     for i in 0..function.arguments.len() {
         let mut arg_return_type = get_return(&args[i].types, variables, syntax).await;
         if !arg_return_type.is_some() {
@@ -275,6 +396,21 @@ pub async fn check_args(
         if !arg_return_type.of_type(base_field_type, syntax.clone()).await {
             return Err(span.make_error(ParsingMessage::MismatchedTypes(arg_return_type.clone(), base_field_type.clone())));
         }
+
+        if let (true, FinalizedTypes::Generic(name, _)) = (matching_generics, base_field_type) {
+            match generics.get(name) {
+                Some(previous) => {
+                    if !arg_return_type.of_type(previous, syntax.clone()).await {
+                        return Err(args[i]
+                            .span
+                            .make_error(ParsingMessage::MismatchedTypes(arg_return_type.clone(), previous.clone())));
+                    }
+                }
+                None => {
+                    generics.insert(name.clone(), arg_return_type.clone());
+                }
+            }
+        }
     }
 
     return Ok(());
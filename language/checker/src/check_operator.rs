@@ -2,15 +2,26 @@ use data::tokens::Span;
 use std::mem;
 use std::sync::Arc;
 
+use syntax::desugar::Desugar;
 use syntax::errors::{ErrorSource, ParsingError, ParsingMessage};
-use syntax::operation_util::OperationGetter;
+use syntax::operation_util::{operation_order, OperationGetter};
 use syntax::program::code::{EffectType, Effects, FinalizedEffects};
 use syntax::program::r#struct::StructData;
 use syntax::{Attribute, SimpleVariableManager};
 
-use crate::check_code::verify_effect;
+use crate::check_code::{check_assignable, verify_effect};
 use crate::CodeVerifier;
 
+/// The comparison operators' traits, with the trait a type has to implement to be compared with them
+static COMPARISONS: [(&str, &str); 6] = [
+    ("math::Equal", "math::Equal"),
+    ("math::NotEqual", "math::Equal"),
+    ("math::LessThan", "math::Compare"),
+    ("math::GreaterThan", "math::Compare"),
+    ("math::LessOrEqual", "math::Compare"),
+    ("math::GreaterOrEqual", "math::Compare"),
+];
+
 /// Checks if an operator call is valid
 pub async fn check_operator(
     code_verifier: &mut CodeVerifier<'_>,
@@ -46,6 +57,13 @@ pub async fn check_operator(
         }
     }
 
+    // Compound assignments like += change their left value, so it has to be assignable like with =
+    if is_compound_assignment(&operation.name, &operation.attributes) {
+        if let Some(target) = values.first() {
+            check_assignable(target, variables, &code_verifier.syntax)?;
+        }
+    }
+
     let calling;
     if values.len() > 0 {
         calling = Box::new(values.remove(0));
@@ -53,15 +71,41 @@ pub async fn check_operator(
         calling = Box::new(Effects::new(Span::default(), EffectType::NOP));
     }
 
-    return verify_effect(
+    // Operators are desugared into a call to the trait's function
+    let desugar = Desugar::new(&effect.span, "operator");
+    let output = verify_effect(
         code_verifier,
         variables,
-        Effects::new(
-            effect.span.clone(),
-            EffectType::ImplementationCall(calling, operation.name.clone(), String::default(), values, None),
-        ),
+        desugar.effect(EffectType::ImplementationCall(calling, operation.name.clone(), String::default(), values, None)),
     )
     .await;
+    return output.map_err(|error| comparison_error(&operation.name, error));
+}
+
+/// Whether the operation assigns to its left value, like {}+={}, which is any binary operation ending in = that
+/// isn't a comparison like {}<={}
+fn is_compound_assignment(name: &str, attributes: &Vec<Attribute>) -> bool {
+    let operation = Attribute::find_attribute("operation", attributes).unwrap().as_string_attribute().unwrap();
+    return operation.starts_with("{}")
+        && operation.ends_with("={}")
+        && operation != "{}={}"
+        && !COMPARISONS.iter().any(|(found, _)| *found == name);
+}
+
+/// Points comparisons of types that can't be compared at the trait they need, like Compare for <
+fn comparison_error(operation: &str, error: ParsingError) -> ParsingError {
+    let required = match COMPARISONS.iter().find(|(found, _)| *found == operation) {
+        Some((_, required)) => *required,
+        None => return error,
+    };
+    return match &error.message {
+        ParsingMessage::NoTraitImpl(found, traits) | ParsingMessage::NoMatchingImpl(found, traits, _)
+            if traits.inner_struct_safe().is_some_and(|found| found.data.name == operation) =>
+        {
+            error.span.make_error(ParsingMessage::NotComparable(found.clone(), required))
+        }
+        _ => error,
+    };
 }
 
 /// Checks if two operations can be combined
@@ -208,15 +252,8 @@ pub fn operator_pratt_parsing(
     token: Span,
     inner_token: Span,
 ) -> Option<Arc<StructData>> {
-    let op_priority = Attribute::find_attribute("priority", &found.attributes)
-        .map(|inner| inner.as_int_attribute().unwrap_or(0))
-        .unwrap_or(0);
-    let op_parse_left = Attribute::find_attribute("parse_left", &found.attributes)
-        .map(|inner| inner.as_bool_attribute().unwrap_or(false))
-        .unwrap_or(false);
-    let lhs_priority = Attribute::find_attribute("priority", &inner_data.attributes)
-        .map(|inner| inner.as_int_attribute().unwrap_or(0))
-        .unwrap_or(0);
+    let (op_priority, op_parse_left) = operation_order(&found.attributes);
+    let (lhs_priority, _) = operation_order(&inner_data.attributes);
 
     return if lhs_priority < op_priority || (!op_parse_left && lhs_priority == op_priority) {
         if inner_array {
@@ -8,6 +8,7 @@ use std::sync::Arc;
 use std::task::{Context, Poll};
 
 use async_recursion::async_recursion;
+use data::stats;
 use data::tokens::Span;
 use syntax::async_util::AsyncDataGetter;
 use syntax::errors::{ErrorSource, ParsingError, ParsingMessage};
@@ -79,6 +80,7 @@ pub async fn degeneric_effect(
                 syntax,
                 variables,
                 return_type.clone(),
+                span,
             )
             .await?;
 
@@ -107,7 +109,8 @@ pub async fn degeneric_effect(
             arguments.insert(0, calling.clone());
             let function = AsyncDataGetter::new(syntax.clone(), function.clone()).await;
             let function =
-                degeneric_function(function.clone(), process_manager.cloned(), &arguments, syntax, variables, None).await?;
+                degeneric_function(function.clone(), process_manager.cloned(), &arguments, syntax, variables, None, span)
+                    .await?;
             for argument in &mut *arguments {
                 degeneric_effect(&mut argument.types, syntax, process_manager, variables, span).await?;
             }
@@ -156,6 +159,7 @@ pub async fn degeneric_effect(
                 syntax,
                 variables,
                 returning.clone(),
+                span,
             )
             .await?;
             for effect in &mut *arguments {
@@ -201,7 +205,8 @@ pub async fn degeneric_effect(
 
             for function in &impl_functions[0].1 {
                 let function = AsyncDataGetter::new(syntax.clone(), function.clone()).await;
-                let function = degeneric_function(function, manager.cloned(), &vec![], syntax, variables, None).await?;
+                let function =
+                    degeneric_function(function, manager.cloned(), &vec![], syntax, variables, None, span).await?;
                 functions.push(function)
             }
 
@@ -218,6 +223,14 @@ pub async fn degeneric_effect(
         FinalizedEffectType::StackStore(storing) => {
             degeneric_effect(&mut storing.types, syntax, process_manager, variables, span).await?
         }
+        FinalizedEffectType::InlineCode(_, inputs, output) => {
+            if let Some(found) = output {
+                degeneric_type(found, process_manager.generics(), syntax).await;
+            }
+            for input in inputs {
+                degeneric_effect(&mut input.types, syntax, process_manager, variables, span).await?;
+            }
+        }
         _ => {}
     }
     return Ok(());
@@ -259,6 +272,7 @@ pub async fn degeneric_arguments(
 /// This can't always figure out return types, so an optional return type variable is passed as well
 /// for function calls that include them (see EffectType::MethodCall)
 /// The VariableManager here is for the arguments to the function, and not for the function itself.
+/// The call site is the span of the code calling the function, recorded for --dump-instantiations.
 pub async fn degeneric_function(
     method: Arc<CodelessFinalizedFunction>,
     mut manager: Box<dyn ProcessManager>,
@@ -266,6 +280,7 @@ pub async fn degeneric_function(
     syntax: &Arc<Mutex<Syntax>>,
     variables: &SimpleVariableManager,
     returning: Option<(FinalizedTypes, Span)>,
+    call_site: &Span,
 ) -> Result<Arc<CodelessFinalizedFunction>, ParsingError> {
     /*
     TODO properly merge generics? needs more research
@@ -310,12 +325,28 @@ pub async fn degeneric_function(
         )
     };
 
+    // Every call is recorded, including ones reusing an earlier instantiation
+    if !manager.generics().is_empty() {
+        if let Some(instantiations) = &mut syntax.lock().instantiations {
+            instantiations.entry(name.clone()).or_default().push(call_site.clone());
+        }
+    }
+
     // If this function has already been degenericed, use the previous one.
     if syntax.lock().compiling.contains_key(&name) {
         let data = syntax.lock().functions.types.get(&name).unwrap().clone();
         return Ok(AsyncDataGetter::new(syntax.clone(), data).await);
     }
 
+    // A function whose generics grow every time it's instantiated, like foo<T> calling foo<Wrapper<T>>, never finishes.
+    if let Some(start) = find_growing_instantiation(manager.instantiations(), &name) {
+        let mut chain =
+            manager.instantiations()[start..].iter().map(|found| display_instantiation(found)).collect::<Vec<_>>();
+        chain.push(display_instantiation(&name));
+        return Err(method.data.get_span().make_error(ParsingMessage::InfiniteInstantiation(chain)));
+    }
+    manager.mut_instantiations().push(name.clone());
+
     // Copy the method and degeneric every type inside of it.
     let mut new_method = CodelessFinalizedFunction::clone(&method);
     // Delete the generics because now they are all solidified.
@@ -345,6 +376,7 @@ pub async fn degeneric_function(
     }*/
     locked.functions.add_type(new_method.data.clone());
     locked.functions.add_data(new_method.data.clone(), new_method.clone());
+    stats::FUNCTION_INSTANTIATIONS.add();
 
     // Spawn a thread to asynchronously degeneric the code inside the function.
     let handle = manager.handle().clone();
@@ -353,6 +385,38 @@ pub async fn degeneric_function(
     return Ok(new_method);
 }
 
+/// Finds where the instantiation chain starts growing without end, if it does.
+/// That's when the function was already instantiated twice in the chain, with each one's generics nested in the next,
+/// because instantiating a function with its own generics wrapped in another type can only repeat.
+fn find_growing_instantiation(chain: &Vec<String>, name: &str) -> Option<usize> {
+    let Some((base, mut generics)) = name.split_once('$') else {
+        return None;
+    };
+
+    let mut growing = 0;
+    for (i, found) in chain.iter().enumerate().rev() {
+        let Some((found_base, found_generics)) = found.split_once('$') else {
+            continue;
+        };
+        if found_base == base && found_generics != generics && generics.contains(found_generics) {
+            growing += 1;
+            generics = found_generics;
+            if growing == 2 {
+                return Some(i);
+            }
+        }
+    }
+    return None;
+}
+
+/// Displays an instantiated function's name with its generics, like foo<u64> instead of foo$u64
+fn display_instantiation(name: &str) -> String {
+    return match name.split_once('$') {
+        Some((base, generics)) => format!("{}<{}>", base, generics),
+        None => name.to_string(),
+    };
+}
+
 /// Degenerics the code body of the method.
 async fn degeneric_code(
     syntax: Arc<Mutex<Syntax>>,
@@ -447,6 +511,13 @@ pub async fn degeneric_type(
                 return;
             }
             let name = format!("{}<{}>", base.data.name, display_parenless(&bounds, ", "));
+            // Self-referential generics, like a field of Node<T> being a Node<Box<T>>, would flatten forever
+            let limit = syntax.lock().recursion_limit;
+            if generic_depth(&name) > limit {
+                syntax.lock().errors.push(base.data.get_span().make_error(ParsingMessage::TypeTooDeep(name, limit)));
+                *types = FinalizedTypes::Struct(base.clone());
+                return;
+            }
             // If this type has already been flattened with these args, return that.
             if syntax.lock().structures.types.contains_key(&name) {
                 let data;
@@ -485,6 +556,7 @@ pub async fn degeneric_type(
                 // Add the flattened type to the syntax
                 let mut locked = syntax.lock();
                 locked.structures.add_data(arc_other, data.clone());
+                stats::STRUCT_INSTANTIATIONS.add();
                 *types = FinalizedTypes::Struct(data.clone());
             }
         }
@@ -500,6 +572,23 @@ pub async fn degeneric_type(
     };
 }
 
+/// Gets how deeply the generics in a type's name are nested, like 2 for List<Box<u64>>
+fn generic_depth(name: &str) -> u32 {
+    let mut depth = 0;
+    let mut deepest = 0;
+    for character in name.chars() {
+        match character {
+            '<' => {
+                depth += 1;
+                deepest = deepest.max(depth);
+            }
+            '>' => depth -= 1,
+            _ => {}
+        }
+    }
+    return deepest;
+}
+
 /// Degenerics the type by replacing all generics with their solidified value.
 /// Ignores generic types
 #[async_recursion(Sync)]
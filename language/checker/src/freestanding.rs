@@ -0,0 +1,144 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use data::tokens::Span;
+use parking_lot::Mutex;
+use syntax::errors::{ErrorSource, ParsingError, ParsingMessage};
+use syntax::program::code::{FinalizedEffectType, FinalizedEffects};
+use syntax::program::function::{CodelessFinalizedFunction, FinalizedFunction};
+use syntax::program::syntax::Syntax;
+use syntax::{is_modifier, Attribute, Modifier};
+
+/// The functions freestanding programs provide, with their attribute, how many arguments they take,
+/// whether they return a value, and an example declaration
+static HOOKS: [(&str, usize, bool, &str); 2] = [
+    ("allocator", 1, true, "extern fn alloc(size: u64) -> u64;"),
+    ("panic_handler", 1, false, "extern fn on_panic(message: str);"),
+];
+
+/// Checks a --no-std program after it's been checked, finding its allocator and panic handler and
+/// making sure the target can't reach any function that needs an operating system.
+/// The hooks are saved to the syntax so the compiler calls them instead of libc and the runtime.
+pub fn check_freestanding(syntax: &Arc<Mutex<Syntax>>) -> Vec<ParsingError> {
+    let mut locked = syntax.lock();
+    let mut errors = Vec::default();
+    let target = locked.compiling.get(&locked.async_manager.target).map(|found| found.value().clone());
+    // Missing hooks are reported on the target since they aren't anywhere in the code
+    let span = target.as_ref().map(|found| found.data.span.clone()).unwrap_or_default();
+
+    let mut hooks = Vec::default();
+    for (attribute, arguments, returns, example) in HOOKS {
+        let mut found = locked
+            .compiling
+            .iter()
+            .map(|pair| pair.value().clone())
+            .filter(|function| Attribute::find_attribute(attribute, &function.data.attributes).is_some())
+            .collect::<Vec<_>>();
+        found.sort_by(|first, second| first.data.name.cmp(&second.data.name));
+        for function in found.iter().skip(1) {
+            errors.push(function.data.span.make_error(ParsingMessage::DuplicateHook(attribute)));
+        }
+        hooks.push(match found.first() {
+            Some(function) => {
+                if !is_modifier(function.data.modifiers, Modifier::Extern)
+                    || function.fields.len() != arguments
+                    || function.return_type.is_some() != returns
+                {
+                    errors.push(function.data.span.make_error(ParsingMessage::InvalidHook(attribute, example)));
+                }
+                Some(function.data.name.clone())
+            }
+            None => {
+                errors.push(span.make_error(ParsingMessage::MissingHook(attribute, example)));
+                None
+            }
+        });
+    }
+    locked.allocator = hooks[0].clone();
+    locked.panic_handler = hooks[1].clone();
+
+    if let Some(target) = target {
+        check_reachable(&locked, target, &mut errors);
+    }
+    return errors;
+}
+
+/// Walks every function reachable from the target, erroring at each call to a #[hosted] function
+fn check_reachable(syntax: &Syntax, target: Arc<FinalizedFunction>, errors: &mut Vec<ParsingError>) {
+    let mut visited = HashSet::new();
+    visited.insert(target.data.name.clone());
+    let mut waiting = vec![target];
+    while let Some(function) = waiting.pop() {
+        let mut calls = Vec::default();
+        for expression in &function.code.expressions {
+            find_calls(&expression.effect, &mut calls);
+        }
+
+        for (called, span) in calls {
+            // Functions that need an operating system or the host's runtime, like most LLVM intrinsics, are marked
+            // #[hosted] where they're declared. malloc and panic aren't, since they call the program's hooks instead.
+            if Attribute::find_attribute("hosted", &called.data.attributes).is_some() {
+                errors.push(span.make_error(ParsingMessage::HostedFunction(called.data.name.clone())));
+            }
+            if visited.insert(called.data.name.clone()) {
+                if let Some(found) = syntax.compiling.get(&called.data.name) {
+                    waiting.push(found.value().clone());
+                }
+            }
+        }
+    }
+}
+
+/// Finds every function the effect calls or downcasts into a trait, with the span of the effect calling it
+fn find_calls(effect: &FinalizedEffects, output: &mut Vec<(Arc<CodelessFinalizedFunction>, Span)>) {
+    match &effect.types {
+        FinalizedEffectType::MethodCall(calling, function, arguments, _) => {
+            if let Some(calling) = calling {
+                find_calls(calling, output);
+            }
+            output.push((function.clone(), effect.span.clone()));
+            arguments.iter().for_each(|argument| find_calls(argument, output));
+        }
+        FinalizedEffectType::GenericMethodCall(function, _, arguments)
+        | FinalizedEffectType::VirtualCall(_, function, arguments, _)
+        | FinalizedEffectType::GenericVirtualCall(_, _, function, arguments, _) => {
+            output.push((function.clone(), effect.span.clone()));
+            arguments.iter().for_each(|argument| find_calls(argument, output));
+        }
+        FinalizedEffectType::Downcast(inner, _, functions) => {
+            find_calls(inner, output);
+            output.extend(functions.iter().map(|function| (function.clone(), effect.span.clone())));
+        }
+        FinalizedEffectType::CodeBody(body) => {
+            body.expressions.iter().for_each(|expression| find_calls(&expression.effect, output));
+        }
+        FinalizedEffectType::CreateVariable(_, inner, _)
+        | FinalizedEffectType::CompareJump(inner, _, _)
+        | FinalizedEffectType::Load(inner, _, _)
+        | FinalizedEffectType::HeapStore(inner)
+        | FinalizedEffectType::ReferenceLoad(inner)
+        | FinalizedEffectType::StackStore(inner) => find_calls(inner, output),
+        FinalizedEffectType::Set(first, second) => {
+            find_calls(first, output);
+            find_calls(second, output);
+        }
+        FinalizedEffectType::CreateStruct(target, _, fields) => {
+            if let Some(target) = target {
+                find_calls(target, output);
+            }
+            fields.iter().for_each(|(_, field)| find_calls(field, output));
+        }
+        FinalizedEffectType::CreateArray(_, values) | FinalizedEffectType::InlineCode(_, values, _) => {
+            values.iter().for_each(|value| find_calls(value, output))
+        }
+        FinalizedEffectType::NOP
+        | FinalizedEffectType::Jump(_)
+        | FinalizedEffectType::LoadVariable(_)
+        | FinalizedEffectType::Float(_)
+        | FinalizedEffectType::UInt(_)
+        | FinalizedEffectType::Bool(_)
+        | FinalizedEffectType::String(_)
+        | FinalizedEffectType::Char(_)
+        | FinalizedEffectType::HeapAllocate(_) => {}
+    }
+}
@@ -34,6 +34,8 @@ pub mod check_operator;
 pub mod check_struct;
 /// Degenerics types
 pub mod degeneric;
+/// Checks programs compiled without the std
+pub mod freestanding;
 /// Used to send data to be checked by the checker and then send the result to the compiler
 pub mod output;
 
@@ -59,6 +61,12 @@ pub struct CodeVerifier<'a> {
     resolver: Box<dyn NameResolver>,
     return_type: Option<FinalizedTypes>,
     syntax: Arc<Mutex<Syntax>>,
+    /// How many effects are currently being verified inside each other
+    depth: u32,
+    /// The most effects that can be verified inside each other, see Syntax::recursion_limit
+    recursion_limit: u32,
+    /// Whether the function is unsafe, which allows llvm! and asm!
+    unsafe_code: bool,
 }
 
 /// Gets the return type of the effect, requiring a variable manager to get
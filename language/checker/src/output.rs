@@ -15,7 +15,7 @@ use syntax::program::function::{
 use syntax::program::r#struct::{FinalizedStruct, StructData, UnfinalizedStruct};
 use syntax::program::syntax::Syntax;
 use syntax::program::types::FinalizedTypes;
-use syntax::{ProcessManager, SimpleVariableManager};
+use syntax::{ProcessManager, SimpleVariableManager, TopElement};
 
 /// Wrapper around data used by checkers
 #[derive(Clone)]
@@ -26,12 +26,14 @@ pub struct TypesChecker {
     pub generics: HashMap<String, FinalizedTypes>,
     /// Whether to include references
     include_refs: bool,
+    /// The generic instantiations that led to the current one, see ProcessManager::instantiations
+    instantiations: Vec<String>,
 }
 
 impl TypesChecker {
     /// Makes a new TypesChecker
     pub fn new(runtime: Arc<Mutex<HandleWrapper>>, include_refs: bool) -> Self {
-        return Self { runtime, generics: HashMap::default(), include_refs };
+        return Self { runtime, generics: HashMap::default(), include_refs, instantiations: Vec::default() };
     }
 }
 
@@ -82,7 +84,8 @@ impl ProcessManager for TypesChecker {
 
     async fn degeneric_code(&self, function: Arc<CodelessFinalizedFunction>, syntax: &Arc<Mutex<Syntax>>) {
         let variables = SimpleVariableManager::for_function(&function);
-        let _ = match degeneric_function(function, Box::new(self.clone()), &vec![], syntax, &variables, None).await {
+        let span = function.data.get_span().clone();
+        let _ = match degeneric_function(function, Box::new(self.clone()), &vec![], syntax, &variables, None, &span).await {
             Ok(result) => result,
             Err(error) => panic!("{:?}", error),
         };
@@ -115,6 +118,14 @@ impl ProcessManager for TypesChecker {
         return &mut self.generics;
     }
 
+    fn instantiations(&self) -> &Vec<String> {
+        return &self.instantiations;
+    }
+
+    fn mut_instantiations(&mut self) -> &mut Vec<String> {
+        return &mut self.instantiations;
+    }
+
     fn cloned(&self) -> Box<dyn ProcessManager> {
         return Box::new(self.clone());
     }
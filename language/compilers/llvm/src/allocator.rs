@@ -0,0 +1,128 @@
+use parking_lot::Mutex;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::RefCell;
+use std::sync::Arc;
+
+use crate::heap_profiler;
+
+/// Every allocation is aligned to this, which fits any Raven type
+const ALIGNMENT: usize = 8;
+
+/// The allocator compiled programs get their heap memory from.
+/// Every heap allocation made by Raven code, like storing a struct or an array, calls alloc,
+/// and memory is given back to the allocator with dealloc, so it must be freed by the same allocator that allocated it.
+pub trait Allocator: Send + Sync {
+    /// Allocates the amount of bytes aligned to 8 bytes, returning null if there isn't enough memory
+    fn alloc(&self, size: u64) -> *mut u8;
+
+    /// Frees memory from alloc or realloc, given the size it was allocated with
+    fn dealloc(&self, pointer: *mut u8, size: u64);
+
+    /// Resizes memory from alloc or realloc, keeping its contents up to the smaller size,
+    /// and returning the new pointer or null if there isn't enough memory
+    fn realloc(&self, pointer: *mut u8, size: u64, new_size: u64) -> *mut u8;
+}
+
+/// The default allocator, which uses the system's allocator
+pub struct SystemAllocator;
+
+impl SystemAllocator {
+    /// Gets the layout of an allocation. Empty allocations still get a byte so every allocation has its own address
+    fn layout(size: u64) -> Layout {
+        return Layout::from_size_align((size as usize).max(1), ALIGNMENT).unwrap();
+    }
+}
+
+impl Allocator for SystemAllocator {
+    fn alloc(&self, size: u64) -> *mut u8 {
+        return unsafe { System.alloc(Self::layout(size)) };
+    }
+
+    fn dealloc(&self, pointer: *mut u8, size: u64) {
+        unsafe { System.dealloc(pointer, Self::layout(size)) };
+    }
+
+    fn realloc(&self, pointer: *mut u8, size: u64, new_size: u64) -> *mut u8 {
+        return unsafe { System.realloc(pointer, Self::layout(size), (new_size as usize).max(1)) };
+    }
+}
+
+/// The installed allocator, or None to use the SystemAllocator
+static ALLOCATOR: Mutex<Option<Arc<dyn Allocator>>> = Mutex::new(None);
+
+/// Installs the allocator compiled programs allocate from, replacing any previous allocator.
+/// Memory has to be freed by the allocator that allocated it, so this must be called before running a program.
+pub fn set_allocator(allocator: Arc<dyn Allocator>) {
+    *ALLOCATOR.lock() = Some(allocator);
+}
+
+/// Removes the installed allocator, returning it. Programs run afterward use the SystemAllocator
+pub fn take_allocator() -> Option<Arc<dyn Allocator>> {
+    return ALLOCATOR.lock().take();
+}
+
+thread_local! {
+    /// The allocators entered on this thread, like arenas, with the last one used instead of the installed allocator
+    static ENTERED: RefCell<Vec<Arc<dyn Allocator>>> = RefCell::new(Vec::default());
+}
+
+/// Makes allocations on this thread come from the allocator until it's exited, like std's Arena::enter.
+/// Allocators can be entered inside each other, and the last one entered is used.
+pub fn enter_allocator(allocator: Arc<dyn Allocator>) {
+    ENTERED.with(|entered| entered.borrow_mut().push(allocator));
+}
+
+/// Stops allocating from the allocator on this thread, going back to the one entered before it.
+/// Does nothing and returns false if the allocator wasn't entered.
+pub fn exit_allocator(allocator: &Arc<dyn Allocator>) -> bool {
+    return ENTERED.with(|entered| {
+        let mut entered = entered.borrow_mut();
+        // Compares the data pointers, since the same allocator can have different vtable pointers
+        let same = |found: &Arc<dyn Allocator>| Arc::as_ptr(found) as *const u8 == Arc::as_ptr(allocator) as *const u8;
+        return match entered.iter().rposition(same) {
+            Some(position) => {
+                entered.remove(position);
+                true
+            }
+            None => false,
+        };
+    });
+}
+
+/// Calls the allocator entered last on this thread, or the installed allocator if none are.
+/// It's cloned out so allocators can call Raven code that allocates
+fn with_allocator<T>(call: impl FnOnce(&dyn Allocator) -> T) -> T {
+    let entered = ENTERED.with(|entered| entered.borrow().last().cloned());
+    return match &entered {
+        Some(allocator) => call(allocator.as_ref()),
+        None => with_installed_allocator(call),
+    };
+}
+
+/// Calls the installed allocator, or the SystemAllocator if there isn't one, ignoring any entered allocators.
+/// Entered allocators use this for memory they didn't allocate.
+pub(crate) fn with_installed_allocator<T>(call: impl FnOnce(&dyn Allocator) -> T) -> T {
+    let installed = ALLOCATOR.lock().clone();
+    return match &installed {
+        Some(allocator) => call(allocator.as_ref()),
+        None => call(&SystemAllocator),
+    };
+}
+
+/// Called by the compiled program for every heap allocation, through core's malloc function
+pub extern "C" fn raven_alloc(size: u64) -> *mut u8 {
+    return with_allocator(|allocator| allocator.alloc(size));
+}
+
+/// Called by the compiled program to free memory, through core's dealloc function
+pub extern "C" fn raven_dealloc(pointer: u64, size: u64) {
+    heap_profiler::raven_record_free(pointer);
+    with_allocator(|allocator| allocator.dealloc(pointer as *mut u8, size));
+}
+
+/// Called by the compiled program to resize memory, through core's realloc function, returning the new pointer
+pub extern "C" fn raven_realloc(pointer: u64, size: u64, new_size: u64) -> u64 {
+    let resized = with_allocator(|allocator| allocator.realloc(pointer as *mut u8, size, new_size)) as u64;
+    heap_profiler::record_move(pointer, resized);
+    return resized;
+}
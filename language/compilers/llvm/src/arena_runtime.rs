@@ -0,0 +1,162 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+
+use crate::allocator::{enter_allocator, exit_allocator, with_installed_allocator, Allocator};
+
+/// Allocations are bump allocated from chunks of at least this many bytes
+const CHUNK_SIZE: usize = 4096;
+
+/// A region of memory that's allocated from quickly and freed all at once
+#[derive(Default)]
+struct Arena {
+    /// The chunks allocations are handed out from. Chunks are never resized, so allocations never move
+    chunks: Vec<Box<[u8]>>,
+    /// How many bytes of the last chunk are used
+    used: usize,
+    /// The total bytes handed out since the arena was last freed
+    allocated: u64,
+    /// How many times the arena has been freed, so allocations from before a free can be found
+    generation: u64,
+}
+
+impl Arena {
+    /// Allocates zeroed bytes aligned to 8 bytes, with allocations bigger than a chunk getting their own chunk
+    fn alloc(&mut self, length: usize) -> *mut u8 {
+        let start = (self.used + 7) & !7;
+        self.allocated += length as u64;
+        if self.chunks.is_empty() || start + length > self.chunks.last().unwrap().len() {
+            self.chunks.push(vec![0; length.max(CHUNK_SIZE)].into_boxed_slice());
+            self.used = length;
+            return self.chunks.last_mut().unwrap().as_mut_ptr();
+        }
+        self.used = start + length;
+        return unsafe { self.chunks.last_mut().unwrap().as_mut_ptr().add(start) };
+    }
+
+    /// Checks if the pointer is in one of the arena's chunks
+    fn owns(&self, pointer: *mut u8) -> bool {
+        return self.chunks.iter().any(|chunk| chunk.as_ptr_range().contains(&(pointer as *const u8)));
+    }
+}
+
+/// An arena that Raven code can allocate everything from, like structs, by entering it.
+/// Freeing its memory does nothing, since it's all freed with the arena, and memory that was allocated
+/// before entering it is given back to the installed allocator.
+#[derive(Default)]
+pub struct ArenaAllocator {
+    /// The bytes allocated by std's arena module, which are freed by free
+    arena: Mutex<Arena>,
+    /// The memory allocated while the arena is entered, like structs. Raven can't check if those are used after
+    /// they're freed, so they're kept until the arena is dropped instead of being freed by free
+    entered: Mutex<Arena>,
+    /// How many times the arena is currently entered, on every thread
+    depth: AtomicU64,
+}
+
+impl Allocator for ArenaAllocator {
+    fn alloc(&self, size: u64) -> *mut u8 {
+        return self.entered.lock().alloc(size as usize);
+    }
+
+    fn dealloc(&self, pointer: *mut u8, size: u64) {
+        if !self.entered.lock().owns(pointer) {
+            with_installed_allocator(|allocator| allocator.dealloc(pointer, size));
+        }
+    }
+
+    fn realloc(&self, pointer: *mut u8, size: u64, new_size: u64) -> *mut u8 {
+        let mut arena = self.entered.lock();
+        let owned = arena.owns(pointer);
+        if owned && new_size <= size {
+            return pointer;
+        }
+        let resized = arena.alloc(new_size as usize);
+        unsafe { std::ptr::copy_nonoverlapping(pointer, resized, size.min(new_size) as usize) };
+        drop(arena);
+        if !owned {
+            with_installed_allocator(|allocator| allocator.dealloc(pointer, size));
+        }
+        return resized;
+    }
+}
+
+/// The generation of a dropped arena, which bytes never have, so using bytes from a dropped arena panics
+const DROPPED: u64 = u64::MAX;
+
+/// Every arena made by raven_arena_new, with each handle being its index plus one.
+/// Dropping an arena empties its slot instead of reusing it, so bytes from a dropped arena never look like
+/// they're from a newer one, and its handle can still be checked afterward.
+static ARENAS: Mutex<Vec<Option<Arc<ArenaAllocator>>>> = Mutex::new(Vec::new());
+
+/// Gets the arena behind a handle from std's arena module, or None if it was dropped
+fn arena(handle: u64) -> Option<Arc<ArenaAllocator>> {
+    return ARENAS.lock().get((handle as usize).wrapping_sub(1)).cloned().flatten();
+}
+
+/// Called by std's arena module to make an empty arena, returning its handle
+pub extern "C" fn raven_arena_new() -> u64 {
+    let mut arenas = ARENAS.lock();
+    arenas.push(Some(Arc::new(ArenaAllocator::default())));
+    return arenas.len() as u64;
+}
+
+/// Called by std's arena module to allocate zeroed bytes, returning a pointer to them, or null if it was dropped
+pub extern "C" fn raven_arena_alloc(handle: u64, length: u64) -> u64 {
+    return arena(handle).map_or(0, |arena| arena.arena.lock().alloc(length as usize) as u64);
+}
+
+/// Called by std's arena module to make every allocation on this thread come from the arena until it's exited
+pub extern "C" fn raven_arena_enter(handle: u64) {
+    if let Some(arena) = arena(handle) {
+        arena.depth.fetch_add(1, Ordering::SeqCst);
+        enter_allocator(arena);
+    }
+}
+
+/// Called by std's arena module to stop allocating from the arena on this thread
+pub extern "C" fn raven_arena_exit(handle: u64) {
+    if let Some(arena) = arena(handle) {
+        let allocator: Arc<dyn Allocator> = arena.clone();
+        if exit_allocator(&allocator) {
+            arena.depth.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+}
+
+/// Called by std's arena module to get how many times the arena is entered, so it isn't freed while it's in use
+pub extern "C" fn raven_arena_entered(handle: u64) -> u64 {
+    return arena(handle).map_or(0, |arena| arena.depth.load(Ordering::SeqCst));
+}
+
+/// Called by std's arena module to get how many times the arena has been freed, or DROPPED if it was dropped
+pub extern "C" fn raven_arena_generation(handle: u64) -> u64 {
+    return arena(handle).map_or(DROPPED, |arena| arena.arena.lock().generation);
+}
+
+/// Called by std's arena module to get the total bytes the arena holds, which are the bytes allocated since it was
+/// last freed and everything allocated while it was entered
+pub extern "C" fn raven_arena_allocated(handle: u64) -> u64 {
+    return arena(handle).map_or(0, |arena| arena.arena.lock().allocated + arena.entered.lock().allocated);
+}
+
+/// Called by std's arena module to free every byte allocated from it at once. The arena can be allocated from again
+/// afterward. Memory allocated while it was entered is kept until it's dropped.
+pub extern "C" fn raven_arena_free(handle: u64) {
+    if let Some(arena) = arena(handle) {
+        let mut arena = arena.arena.lock();
+        arena.chunks.clear();
+        arena.used = 0;
+        arena.allocated = 0;
+        arena.generation += 1;
+    }
+}
+
+/// Called by std's arena module to free the arena and everything in it, including memory allocated while it was
+/// entered. The memory is freed once no thread has it entered, and the handle can't be used to allocate again.
+pub extern "C" fn raven_arena_drop(handle: u64) {
+    if let Some(slot) = ARENAS.lock().get_mut((handle as usize).wrapping_sub(1)) {
+        *slot = None;
+    }
+}
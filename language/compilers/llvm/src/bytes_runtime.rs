@@ -0,0 +1,117 @@
+use std::ffi::{c_char, CStr, CString};
+use std::fs;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+
+/// Gets the buffer behind a handle from std's bytes module.
+/// Handles are leaked boxes made by raven_bytes_new, which Raven never frees.
+fn buffer<'a>(handle: u64) -> &'a mut Vec<u8> {
+    return unsafe { &mut *(handle as *mut Vec<u8>) };
+}
+
+/// Gets the stream behind a handle from std's net module, made by raven_tcp_connect
+fn stream<'a>(handle: u64) -> &'a mut TcpStream {
+    return unsafe { &mut *(handle as *mut TcpStream) };
+}
+
+/// Reads a string passed from Raven
+fn read(text: *const c_char) -> String {
+    return unsafe { CStr::from_ptr(text) }.to_string_lossy().to_string();
+}
+
+/// Called by std's bytes module to make an empty buffer, returning its handle
+pub extern "C" fn raven_bytes_new() -> u64 {
+    return Box::into_raw(Box::new(Vec::<u8>::default())) as u64;
+}
+
+/// Called by std's bytes module to get the length of a buffer
+pub extern "C" fn raven_bytes_length(handle: u64) -> u64 {
+    return buffer(handle).len() as u64;
+}
+
+/// Called by std's bytes module to get a byte. The index is checked by Raven, so it can panic with Raven's hook.
+pub extern "C" fn raven_bytes_get(handle: u64, index: u64) -> u64 {
+    return buffer(handle)[index as usize] as u64;
+}
+
+/// Called by std's bytes module to replace a byte, keeping only the value's lowest 8 bits
+pub extern "C" fn raven_bytes_set(handle: u64, index: u64, value: u64) {
+    buffer(handle)[index as usize] = value as u8;
+}
+
+/// Called by std's bytes module to add a byte to the end, keeping only the value's lowest 8 bits
+pub extern "C" fn raven_bytes_push(handle: u64, value: u64) {
+    buffer(handle).push(value as u8);
+}
+
+/// Called by std's bytes module to make a buffer of the string's UTF-8 bytes, returning its handle
+pub extern "C" fn raven_bytes_from_str(text: *const c_char) -> u64 {
+    return Box::into_raw(Box::new(unsafe { CStr::from_ptr(text) }.to_bytes().to_vec())) as u64;
+}
+
+/// Called by std's bytes module to make a buffer from a copy of the memory at the pointer, returning its handle.
+/// The memory is checked by Raven, like an arena checking its bytes weren't freed.
+pub extern "C" fn raven_bytes_copy(pointer: u64, length: u64) -> u64 {
+    let bytes = unsafe { std::slice::from_raw_parts(pointer as *const u8, length as usize) };
+    return Box::into_raw(Box::new(bytes.to_vec())) as u64;
+}
+
+/// Called by std's bytes module to read a buffer as UTF-8 text.
+/// Invalid UTF-8 is replaced and the text stops at the first zero byte. Raven strings are never freed, so it's leaked.
+pub extern "C" fn raven_bytes_to_str(handle: u64) -> *const c_char {
+    let text = String::from_utf8_lossy(buffer(handle)).to_string();
+    let text = text.split('\0').next().unwrap_or_default().to_string();
+    return CString::new(text).unwrap_or_default().into_raw();
+}
+
+/// Called by std's bytes module to add a file's contents to the end of a buffer, returning 1 if it could be read or 0 if not
+pub extern "C" fn raven_bytes_read_file(handle: u64, path: *const c_char) -> u64 {
+    return match fs::read(read(path)) {
+        Ok(contents) => {
+            buffer(handle).extend(contents);
+            1
+        }
+        Err(_) => 0,
+    };
+}
+
+/// Called by std's bytes module to replace a file's contents with a buffer, returning 1 if it could be written or 0 if not
+pub extern "C" fn raven_bytes_write_file(handle: u64, path: *const c_char) -> u64 {
+    return fs::write(read(path), buffer(handle)).is_ok() as u64;
+}
+
+/// Called by std's net module to connect to an address like "localhost:8080", returning the stream's handle or 0 if
+/// it couldn't connect
+pub extern "C" fn raven_tcp_connect(address: *const c_char) -> u64 {
+    return match TcpStream::connect(read(address)) {
+        Ok(stream) => Box::into_raw(Box::new(stream)) as u64,
+        Err(_) => 0,
+    };
+}
+
+/// Called by std's net module to send all of a buffer, returning 1 if it was sent or 0 if not
+pub extern "C" fn raven_tcp_send(handle: u64, bytes: u64) -> u64 {
+    return stream(handle).write_all(buffer(bytes)).is_ok() as u64;
+}
+
+/// The most bytes received at once, so a huge maximum doesn't allocate all of it before anything is received
+const RECEIVE_LIMIT: u64 = 64 * 1024;
+
+/// Called by std's net module to wait for up to the maximum amount of bytes, capped at RECEIVE_LIMIT,
+/// and add them to the end of a buffer.
+/// Returns 1 if it worked, even if nothing was received because the other side closed the connection, or 0 if it failed.
+pub extern "C" fn raven_tcp_receive(handle: u64, bytes: u64, maximum: u64) -> u64 {
+    let mut received = vec![0; maximum.min(RECEIVE_LIMIT) as usize];
+    return match stream(handle).read(&mut received) {
+        Ok(length) => {
+            buffer(bytes).extend_from_slice(&received[..length]);
+            1
+        }
+        Err(_) => 0,
+    };
+}
+
+/// Called by std's net module to close a stream. Its handle can't be used after this.
+pub extern "C" fn raven_tcp_close(handle: u64) {
+    drop(unsafe { Box::from_raw(handle as *mut TcpStream) });
+}
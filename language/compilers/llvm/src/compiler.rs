@@ -3,20 +3,25 @@ use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use dashmap::DashMap;
+use inkwell::attributes::{Attribute, AttributeLoc};
 use inkwell::builder::Builder;
 use inkwell::context::Context;
 use inkwell::execution_engine::ExecutionEngine;
 use inkwell::module::Module;
+use inkwell::passes::{PassManager, PassManagerBuilder};
 use inkwell::OptimizationLevel;
 use tokio::time;
 
+use data::progress::Progress;
 use data::tokens::Span;
 use data::CompilerArguments;
 use syntax::async_util::EmptyNameResolver;
 use syntax::program::function::{CodelessFinalizedFunction, FinalizedFunction};
+use syntax::program::printer::SourceLocator;
 use syntax::program::r#struct::FinalizedStruct;
 use syntax::program::syntax::Syntax;
 
+use crate::debug_info::{set_debug_location, DebugInfo};
 use crate::function_compiler::{compile_block, instance_function};
 use crate::main_future::MainFuture;
 use crate::type_getter::CompilerTypeGetter;
@@ -32,14 +37,42 @@ pub struct CompilerImpl<'ctx> {
     pub builder: Builder<'ctx>,
     /// LLVM execution engine
     pub execution_engine: ExecutionEngine<'ctx>,
+    /// The arguments being compiled with
+    pub arguments: CompilerArguments,
+    /// The module's debug info, only emitted for --debug-info
+    pub debug_info: Option<DebugInfo<'ctx>>,
 }
 
 impl<'ctx> CompilerImpl<'ctx> {
-    /// Creates a new CompilerImpl from the context
-    pub fn new(context: &'ctx Context) -> Self {
+    /// Creates a new CompilerImpl from the context, with the locator finding the lines of the debug info if it's emitted
+    pub fn new(context: &'ctx Context, arguments: &CompilerArguments, locator: Option<Arc<SourceLocator>>) -> Self {
         let module = context.create_module("main");
-        let execution_engine = module.create_jit_execution_engine(OptimizationLevel::None).unwrap();
-        return Self { module, context, builder: context.create_builder(), execution_engine };
+        let execution_engine = module.create_jit_execution_engine(Self::optimization_level(arguments)).unwrap();
+        let debug_info = locator
+            .filter(|_| arguments.debug_info)
+            .map(|locator| DebugInfo::new(context, &module, locator, arguments.opt_level > 0 || arguments.lto));
+        return Self {
+            module,
+            context,
+            builder: context.create_builder(),
+            execution_engine,
+            arguments: arguments.clone(),
+            debug_info,
+        };
+    }
+
+    /// Gets the LLVM optimization level from the arguments
+    fn optimization_level(arguments: &CompilerArguments) -> OptimizationLevel {
+        // Optimizing for size builds on the default optimizations, like -Oz does
+        if arguments.opt_size && arguments.opt_level < 2 {
+            return OptimizationLevel::Default;
+        }
+        return match arguments.opt_level {
+            0 => OptimizationLevel::None,
+            1 => OptimizationLevel::Less,
+            2 => OptimizationLevel::Default,
+            _ => OptimizationLevel::Aggressive,
+        };
     }
 
     /// Finds the main function
@@ -75,9 +108,11 @@ impl<'ctx> CompilerImpl<'ctx> {
     pub fn compile(
         main: Arc<CodelessFinalizedFunction>,
         type_getter: &mut CompilerTypeGetter<'ctx>,
+        arguments: &CompilerArguments,
         functions: &Arc<DashMap<String, Arc<FinalizedFunction>>>,
         _structures: &Arc<DashMap<String, Arc<FinalizedStruct>>>,
     ) {
+        let mut progress = Progress::new("Codegen", "functions", None, arguments);
         instance_function(main, type_getter);
 
         let start = Instant::now();
@@ -110,22 +145,91 @@ impl<'ctx> CompilerImpl<'ctx> {
                 continue;
             }
 
+            if let Some(debug_info) = &type_getter.compiler.debug_info {
+                debug_info.add_function(function_type, &finalized_function.data.name, &finalized_function.data.span);
+                set_debug_location(&type_getter.compiler, function_type, &finalized_function.data.span);
+            }
             compile_block(
                 &finalized_function.code,
                 function_type,
                 &mut type_getter.for_function(&finalized_function, function_type),
                 &mut 0,
             );
+            progress.advance();
         }
+        progress.finish();
 
-        //let pass_manager = PassManager::create(&self.compiler.module);
+        if let Some(debug_info) = &type_getter.compiler.debug_info {
+            type_getter.compiler.builder.unset_current_debug_location();
+            debug_info.finalize();
+        }
+
+        if arguments.verify_ir {
+            if let Err(error) = type_getter.compiler.module.verify() {
+                panic!("Compiled invalid LLVM IR:\n{}", error.to_string());
+            }
+        }
 
-        /*unsafe {
-            LLVMWriteBitcodeToFile(type_getter.compiler.module.as_mut_ptr(),
-                                   CString::new(arguments.temp_folder.join("output.bc")
-                                       .to_str().unwrap()).unwrap().as_ptr());
-        }*/
+        if arguments.opt_size {
+            let panic = type_getter.syntax.lock().panic_handler.clone().unwrap_or_else(|| "panic".to_string());
+            Self::mark_for_size(type_getter.compiler.context, &type_getter.compiler.module, &panic);
+        }
+
+        if arguments.lto {
+            let progress = Progress::new("Optimizing", "", None, arguments);
+            Self::link_time_optimize(&type_getter.compiler.module, arguments);
+            progress.finish();
+        } else if arguments.opt_level > 0 || arguments.opt_size {
+            let pass_manager = PassManager::create(());
+            let builder = Self::pass_manager_builder(Self::optimization_level(arguments), arguments);
+            builder.populate_module_pass_manager(&pass_manager);
+            if arguments.opt_size {
+                pass_manager.add_merge_functions_pass();
+            }
+            pass_manager.run_on(&type_getter.compiler.module);
+        }
 
         //print_formatted(type_getter.compiler.module.to_string());
     }
+
+    /// Runs LLVM's LTO pass pipeline over the module. The std and user code are compiled into this one module,
+    /// so there's nothing to link, but the pipeline's whole-program passes still inline std functions into user code.
+    fn link_time_optimize(module: &Module<'ctx>, arguments: &CompilerArguments) {
+        let pass_manager = PassManager::create(());
+        let builder = Self::pass_manager_builder(OptimizationLevel::Aggressive, arguments);
+        // Nothing can be internalized because the target function is looked up by name after this.
+        builder.populate_lto_pass_manager(&pass_manager, false, true);
+        if arguments.opt_size {
+            pass_manager.add_merge_functions_pass();
+        }
+        pass_manager.run_on(module);
+    }
+
+    /// Creates the pass manager builder for the optimization level.
+    /// Optimizing for size (-Oz) makes the inliner only inline functions that shrink the code.
+    /// Functions with identical bodies are merged separately, with the merge functions pass, which shares
+    /// generic functions instantiated with types that compile the same.
+    fn pass_manager_builder(level: OptimizationLevel, arguments: &CompilerArguments) -> PassManagerBuilder {
+        let builder = PassManagerBuilder::create();
+        builder.set_optimization_level(level);
+        if arguments.opt_size {
+            builder.set_size_level(2);
+        }
+        return builder;
+    }
+
+    /// Marks every compiled function to be optimized for size, and panics as cold so the code calling them
+    /// is moved out of the way of the code that normally runs instead of being inlined or duplicated.
+    /// The panic function is the runtime's, or the panic handler of a --no-std program.
+    fn mark_for_size(context: &'ctx Context, module: &Module<'ctx>, panic: &str) {
+        let attribute = |name: &str| context.create_enum_attribute(Attribute::get_named_enum_kind_id(name), 0);
+        for function in module.get_functions() {
+            if function.get_name().to_bytes() == panic.as_bytes() {
+                function.add_attribute(AttributeLoc::Function, attribute("cold"));
+            } else if function.count_basic_blocks() > 0 {
+                function.add_attribute(AttributeLoc::Function, attribute("optsize"));
+                function.add_attribute(AttributeLoc::Function, attribute("minsize"));
+            }
+        }
+    }
 }
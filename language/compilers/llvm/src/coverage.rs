@@ -0,0 +1,63 @@
+use parking_lot::Mutex;
+
+use inkwell::values::BasicMetadataValueEnum;
+
+use data::tokens::Span;
+
+use crate::heap_profiler::get_runtime_function;
+use crate::type_getter::CompilerTypeGetter;
+
+/// The name of the runtime function called every time an instrumented line runs
+static RECORD_FUNCTION: &str = "raven_record_coverage";
+
+/// Every instrumented line in the program, indexed by the site id passed to the record function
+static SITES: Mutex<Vec<CoverageSite>> = Mutex::new(Vec::new());
+
+/// A single line of code counted for coverage, and how many times it ran
+#[derive(Clone, Debug)]
+pub struct CoverageSite {
+    /// The span of the line's effect
+    pub span: Span,
+    /// The amount of times the line ran
+    pub count: u64,
+}
+
+/// Called by the compiled program every time an instrumented line runs
+extern "C" fn raven_record_coverage(site: u64) {
+    SITES.lock()[site as usize].count += 1;
+}
+
+/// Clears every coverage site, called before compiling a new program
+pub fn reset() {
+    SITES.lock().clear();
+}
+
+/// Gets every instrumented line of the last program, including the ones that never ran
+pub fn coverage_sites() -> Vec<CoverageSite> {
+    return SITES.lock().clone();
+}
+
+/// Counts each time the code at the span runs if coverage is enabled.
+/// Only compiled functions are instrumented, so functions that are never called don't have any sites.
+pub fn record_coverage<'ctx>(type_getter: &CompilerTypeGetter<'ctx>, span: &Span, id: &mut u64) {
+    if !type_getter.compiler.arguments.coverage {
+        return;
+    }
+
+    let site = {
+        let mut sites = SITES.lock();
+        sites.push(CoverageSite { span: span.clone(), count: 0 });
+        sites.len() as u64 - 1
+    };
+
+    type_getter
+        .compiler
+        .builder
+        .build_call(
+            get_runtime_function(type_getter, RECORD_FUNCTION, 1, raven_record_coverage as usize),
+            &[BasicMetadataValueEnum::IntValue(type_getter.compiler.context.i64_type().const_int(site, false))],
+            &id.to_string(),
+        )
+        .unwrap();
+    *id += 1;
+}
@@ -0,0 +1,119 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use inkwell::context::Context;
+use inkwell::debug_info::{
+    AsDIScope, DICompileUnit, DIFile, DIFlags, DIFlagsConstants, DWARFEmissionKind, DWARFSourceLanguage, DebugInfoBuilder,
+};
+use inkwell::module::{FlagBehavior, Module};
+use inkwell::values::FunctionValue;
+
+use data::tokens::Span;
+use syntax::program::printer::SourceLocator;
+
+use crate::compiler::CompilerImpl;
+
+/// The version of the debug info metadata LLVM 13 reads, which the module has to be flagged with
+const DEBUG_INFO_VERSION: u64 = 3;
+
+/// Emits the DWARF debug info of the compiled functions, so debuggers and profilers can show the Raven function
+/// and line each instruction came from
+pub struct DebugInfo<'ctx> {
+    /// LLVM's debug info builder
+    builder: DebugInfoBuilder<'ctx>,
+    /// The compile unit every function is in, since the whole program is compiled into one module
+    compile_unit: DICompileUnit<'ctx>,
+    /// Finds the file and line of spans
+    locator: Arc<SourceLocator>,
+    /// The debug info file of every source file used so far, by its path
+    files: RefCell<HashMap<String, DIFile<'ctx>>>,
+    /// Whether the module is optimized, which debuggers use to warn that variables and lines may be missing
+    optimized: bool,
+}
+
+impl<'ctx> DebugInfo<'ctx> {
+    /// Creates the debug info of the module
+    pub fn new(context: &'ctx Context, module: &Module<'ctx>, locator: Arc<SourceLocator>, optimized: bool) -> Self {
+        module.add_basic_value_flag(
+            "Debug Info Version",
+            FlagBehavior::Warning,
+            context.i32_type().const_int(DEBUG_INFO_VERSION, false),
+        );
+        let (builder, compile_unit) = module.create_debug_info_builder(
+            true,
+            // DWARF has no language code for Raven, and C's is the one every debugger understands
+            DWARFSourceLanguage::C,
+            "main",
+            ".",
+            "magpie",
+            optimized,
+            "",
+            0,
+            "",
+            DWARFEmissionKind::Full,
+            0,
+            false,
+            false,
+            "",
+            "",
+        );
+        return Self { builder, compile_unit, locator, files: RefCell::new(HashMap::default()), optimized };
+    }
+
+    /// Gets the debug info file and line of the span, or the compile unit's file and line 0 (no line)
+    /// if the span isn't in a known file
+    fn file(&self, span: &Span) -> (DIFile<'ctx>, u32) {
+        let Some((path, line)) = self.locator.line(span) else {
+            return (self.compile_unit.get_file(), 0);
+        };
+        let file = *self.files.borrow_mut().entry(path.clone()).or_insert_with(|| {
+            let path = Path::new(path);
+            self.builder.create_file(
+                &path.file_name().unwrap_or_default().to_string_lossy(),
+                &path.parent().map(|parent| parent.to_string_lossy().to_string()).unwrap_or_default(),
+            )
+        });
+        return (file, line);
+    }
+
+    /// Attaches the debug info of a Raven function to its compiled function, which has to be done before compiling its body
+    pub fn add_function(&self, function: FunctionValue<'ctx>, name: &str, span: &Span) {
+        let (file, line) = self.file(span);
+        // Only the lines are mapped, so the function's parameter and return types are left out
+        let linkage_name = function.get_name().to_string_lossy();
+        let subroutine = self.builder.create_subroutine_type(file, None, &[], DIFlags::ZERO);
+        let subprogram = self.builder.create_function(
+            self.compile_unit.as_debug_info_scope(),
+            name,
+            Some(linkage_name.as_ref()),
+            file,
+            line,
+            subroutine,
+            true,
+            true,
+            line,
+            DIFlags::ZERO,
+            self.optimized,
+        );
+        function.set_subprogram(subprogram);
+    }
+
+    /// Finishes the debug info, which has to be done before the module is verified or optimized
+    pub fn finalize(&self) {
+        self.builder.finalize();
+    }
+}
+
+/// Sets the line of the instructions compiled after this to the span's line, if debug info is enabled.
+/// LLVM requires every call in a function with debug info to have a line, so this is set before compiling any of it.
+pub fn set_debug_location<'ctx>(compiler: &CompilerImpl<'ctx>, function: FunctionValue<'ctx>, span: &Span) {
+    let (Some(debug_info), Some(subprogram)) = (&compiler.debug_info, function.get_subprogram()) else {
+        return;
+    };
+    let (_, line) = debug_info.file(span);
+    let location =
+        debug_info.builder.create_debug_location(compiler.context, line, 0, subprogram.as_debug_info_scope(), None);
+    compiler.builder.set_current_debug_location(location);
+}
@@ -0,0 +1,50 @@
+use std::ffi::{c_char, CStr, CString};
+
+/// Called by core's fmt module to write a float with the fewest digits that parse back to the same float,
+/// like 0.1 instead of 0.1000000000000000055511151231257827.
+/// Very large and small floats use an exponent like 1e300, and whole floats keep a ".0" so they still read as floats.
+/// Raven strings are never freed, so the result is leaked.
+pub extern "C" fn raven_f64_to_string(value: f64) -> *const c_char {
+    // Rust's Debug formatting is shortest round-trip, switching to an exponent past 1e16 and below 1e-5
+    return CString::new(format!("{:?}", value)).unwrap_or_default().into_raw();
+}
+
+/// Called by core's fmt module to write a float rounded to the given number of digits after the period,
+/// like 3.14 for pi with 2 digits, which is how format strings like {value:.2} write floats.
+pub extern "C" fn raven_f64_to_string_precision(value: f64, digits: u64) -> *const c_char {
+    return CString::new(format!("{:.*}", digits as usize, value)).unwrap_or_default().into_raw();
+}
+
+/// Called by core's fmt module to parse a float, returning 0 if it isn't valid (see raven_f64_is_valid).
+/// The result is correctly rounded to the closest float.
+pub extern "C" fn raven_f64_parse(text: *const c_char) -> f64 {
+    let text = unsafe { CStr::from_ptr(text) }.to_string_lossy();
+    if !is_valid(&text) {
+        return 0.0;
+    }
+    return text.parse().unwrap_or(0.0);
+}
+
+/// Called by core's fmt module to check if text is a valid float, returning 1 if it is or 0 if not
+pub extern "C" fn raven_f64_is_valid(text: *const c_char) -> u64 {
+    return is_valid(&unsafe { CStr::from_ptr(text) }.to_string_lossy()) as u64;
+}
+
+/// Checks if the text is a float in the strict format -123.456e-7: an optional minus sign, digits,
+/// optionally a period followed by digits, and optionally an exponent.
+/// Whitespace, plus signs, missing digits around the period, and words like "inf" or "NaN" aren't allowed.
+fn is_valid(text: &str) -> bool {
+    let text = text.strip_prefix('-').unwrap_or(text);
+    let (number, exponent) = match text.find(['e', 'E']) {
+        Some(index) => (&text[..index], Some(&text[index + 1..])),
+        None => (text, None),
+    };
+    let (whole, fraction) = match number.split_once('.') {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (number, None),
+    };
+    let digits = |part: &str| !part.is_empty() && part.bytes().all(|byte| byte.is_ascii_digit());
+    return digits(whole)
+        && fraction.map_or(true, digits)
+        && exponent.map_or(true, |exponent| digits(exponent.strip_prefix(['-', '+']).unwrap_or(exponent)));
+}
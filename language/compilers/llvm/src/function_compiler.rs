@@ -4,15 +4,21 @@ use std::sync::Arc;
 
 use inkwell::basic_block::BasicBlock;
 use inkwell::module::Linkage;
-use inkwell::types::{BasicType, BasicTypeEnum};
-use inkwell::values::{BasicMetadataValueEnum, BasicValue, BasicValueEnum, CallableValue, FunctionValue};
+use inkwell::types::{AnyType, BasicType, BasicTypeEnum};
+use inkwell::values::{BasicMetadataValueEnum, BasicValue, BasicValueEnum, CallableValue, FunctionValue, PointerValue};
 use inkwell::AddressSpace;
 
+use data::stats;
 use syntax::program::code::{ExpressionType, FinalizedEffectType, FinalizedEffects};
 use syntax::program::function::{CodelessFinalizedFunction, FinalizedCodeBody};
 use syntax::program::types::FinalizedTypes;
 use syntax::{is_modifier, Attribute, Modifier};
 
+use crate::coverage::record_coverage;
+use crate::debug_info::set_debug_location;
+use crate::heap_profiler::record_allocation;
+use crate::host_functions::{compile_host_call, host_function_id};
+use crate::inline_code::compile_inline_code;
 use crate::internal::instructions::{compile_internal, malloc_type};
 use crate::internal::intrinsics::compile_llvm_intrinsics;
 use crate::type_getter::CompilerTypeGetter;
@@ -23,6 +29,10 @@ pub fn instance_function<'a, 'ctx>(
     function: Arc<CodelessFinalizedFunction>,
     type_getter: &mut CompilerTypeGetter<'ctx>,
 ) -> FunctionValue<'ctx> {
+    // Internal and host functions are compiled in the middle of the function calling them, and have no debug info
+    // of their own, so they can't use the calling function's lines
+    let location = type_getter.compiler.builder.get_current_debug_location();
+    type_getter.compiler.builder.unset_current_debug_location();
     let value;
     if function.data.attributes.iter().any(|attribute| {
         if let Attribute::Basic(inner) = attribute {
@@ -36,11 +46,17 @@ pub fn instance_function<'a, 'ctx>(
         value = create_function_value(&function, type_getter, None);
         compile_internal(&type_getter, &type_getter.compiler, &function.data.name, value);
     } else if is_modifier(function.data.modifiers, Modifier::Extern) {
-        value = create_function_value(&function, type_getter, Some(Linkage::External))
+        value = create_function_value(&function, type_getter, Some(Linkage::External));
+        if let Some(id) = host_function_id(&function.data.name) {
+            compile_host_call(type_getter, value, id, &function.data.span);
+        }
     } else {
         value = create_function_value(&function, type_getter, None);
         type_getter.compiling.borrow_mut().push((value, function));
     }
+    if let Some(location) = location {
+        type_getter.compiler.builder.set_current_debug_location(location);
+    }
     return value;
 }
 
@@ -94,6 +110,16 @@ pub fn compile_block<'ctx>(
     type_getter.compiler.builder.position_at_end(block);
     let mut broke = false;
     for line in &code.expressions {
+        // Code bodies and jumps are control flow the compiler added, so they aren't lines of their own
+        if !broke
+            && !matches!(
+                &line.effect.types,
+                FinalizedEffectType::CodeBody(_) | FinalizedEffectType::Jump(_) | FinalizedEffectType::NOP
+            )
+        {
+            set_debug_location(&type_getter.compiler, function, &line.effect.span);
+            record_coverage(type_getter, &line.effect.span, id);
+        }
         match line.expression_type {
             ExpressionType::Return(_) => {
                 if let FinalizedEffectType::CodeBody(body) = &line.effect.types {
@@ -207,6 +233,7 @@ pub fn compile_effect<'ctx>(
         FinalizedEffectType::CodeBody(body) => compile_block(body, function, type_getter, id),
         //Calling function, function arguments
         FinalizedEffectType::MethodCall(pointer, calling_function, arguments, _) => {
+            stats::STATIC_CALLS.add();
             let mut final_arguments = Vec::default();
 
             let calling = type_getter.get_function(calling_function);
@@ -260,9 +287,10 @@ pub fn compile_effect<'ctx>(
         }
         //Sets pointer to value
         FinalizedEffectType::Set(setting, value) => {
-            let output = compile_effect(type_getter, function, setting, id).unwrap();
+            let output = compile_place(type_getter, function, setting, id);
             let mut storing = compile_effect(type_getter, function, value, id).unwrap();
-            if storing.is_pointer_value() {
+            // Fields holding a reference store the pointer itself, everything else stores the value it points to
+            if storing.is_pointer_value() && storing.get_type().as_any_type_enum() != output.get_type().get_element_type() {
                 storing = type_getter.compiler.builder.build_load(storing.into_pointer_value(), &id.to_string()).unwrap();
                 *id += 1;
             }
@@ -279,25 +307,16 @@ pub fn compile_effect<'ctx>(
         }
         //Loads variable/field pointer from program, or self if program is None
         FinalizedEffectType::Load(loading_from, field, _) => {
-            let from = compile_effect(type_getter, function, loading_from, id).unwrap();
-            //Compensate for type id
-            let mut offset = 1;
-            for struct_field in &loading_from.types.get_nongeneric_return(type_getter).unwrap().inner_struct().fields {
-                if &struct_field.field.name != field {
-                    offset += 1;
-                } else {
-                    break;
-                }
-            }
-
-            let gep =
-                type_getter.compiler.builder.build_struct_gep(from.into_pointer_value(), offset, &id.to_string()).unwrap();
-            *id += 2;
+            let gep = compile_field_pointer(type_getter, function, loading_from, field, id);
+            *id += 1;
             Some(type_getter.compiler.builder.build_load(gep, &(*id - 1).to_string()).unwrap())
         }
         //Struct to create and a tuple of the index of the argument and the argument
         FinalizedEffectType::CreateStruct(effect, structure, arguments) => {
-            let mut out_arguments = vec![MaybeUninit::uninit(); arguments.len()];
+            // A field can be set more than once, like a struct update's base loading a field that's set after it,
+            // and the last value is kept
+            let fields = arguments.iter().map(|(index, _)| index + 1).max().unwrap_or(0);
+            let mut out_arguments = vec![MaybeUninit::uninit(); fields];
 
             for (index, effect) in arguments {
                 let returned = compile_effect(type_getter, function, effect, id).unwrap();
@@ -393,6 +412,7 @@ pub fn compile_effect<'ctx>(
                 .unwrap_left()
                 .into_pointer_value();
             *id += 1;
+            record_allocation(type_getter, function, &effect.span, pointer_type.const_zero(), malloc, id);
 
             let malloc = type_getter.compiler.builder.build_pointer_cast(malloc, pointer_type, &id.to_string()).unwrap();
             *id += 1;
@@ -404,6 +424,21 @@ pub fn compile_effect<'ctx>(
             type_getter.compiler.builder.build_store(malloc, output).unwrap();
             Some(malloc.as_basic_value_enum())
         }
+        FinalizedEffectType::InlineCode(code, inputs, output) => {
+            let mut values = Vec::default();
+            for input in inputs {
+                let mut value = compile_effect(type_getter, function, input, id).unwrap();
+                // Inputs are passed by value, so stored values are loaded first
+                let types = type_getter.get_type(&input.types.get_nongeneric_return(type_getter).unwrap());
+                if value.is_pointer_value() && value.get_type() != types {
+                    value = type_getter.compiler.builder.build_load(value.into_pointer_value(), &id.to_string()).unwrap();
+                    *id += 1;
+                }
+                values.push(value);
+            }
+            let output = output.as_ref().map(|output| type_getter.get_type(output));
+            compile_inline_code(type_getter, function, code, &values, output, id)
+        }
         FinalizedEffectType::StackStore(inner) => {
             let output = compile_effect(type_getter, function, inner, id).unwrap();
             if !output.is_pointer_value() {
@@ -468,6 +503,7 @@ pub fn compile_effect<'ctx>(
                 .unwrap_left()
                 .into_pointer_value();
             *id += 1;
+            record_allocation(type_getter, function, &effect.span, pointer_type.const_zero(), malloc, id);
 
             let malloc = type_getter.compiler.builder.build_pointer_cast(malloc, pointer_type, &id.to_string()).unwrap();
             *id += 1;
@@ -500,6 +536,7 @@ pub fn compile_effect<'ctx>(
                     type_getter.compiler.context.struct_type(&[], false).ptr_type(AddressSpace::default()).const_zero()
                 });
             let malloc = malloc_type(type_getter, ptr_type, id);
+            record_allocation(type_getter, function, &effect.span, ptr_type, malloc, id);
 
             type_getter
                 .compiler
@@ -531,6 +568,7 @@ pub fn compile_effect<'ctx>(
             Some(malloc.as_basic_value_enum())
         }
         FinalizedEffectType::VirtualCall(func_offset, method, args, _) => {
+            stats::VIRTUAL_CALLS.add();
             let table = compile_effect(type_getter, function, &args[0], id).unwrap();
 
             let mut compiled_args = Vec::default();
@@ -643,7 +681,9 @@ pub fn compile_effect<'ctx>(
                     )
                     .ptr_type(AddressSpace::default());
 
-                let malloc = malloc_type(type_getter, structure.ptr_type(AddressSpace::default()).const_zero(), id);
+                let pointer_type = structure.ptr_type(AddressSpace::default()).const_zero();
+                let malloc = malloc_type(type_getter, pointer_type, id);
+                record_allocation(type_getter, function, &effect.span, pointer_type, malloc, id);
                 let struct_field = type_getter
                     .compiler
                     .builder
@@ -674,6 +714,46 @@ pub fn compile_effect<'ctx>(
     };
 }
 
+/// Gets a pointer to the field of the struct
+fn compile_field_pointer<'ctx>(
+    type_getter: &mut CompilerTypeGetter<'ctx>,
+    function: FunctionValue<'ctx>,
+    loading_from: &FinalizedEffects,
+    field: &String,
+    id: &mut u64,
+) -> PointerValue<'ctx> {
+    let from = compile_effect(type_getter, function, loading_from, id).unwrap();
+    //Compensate for type id
+    let mut offset = 1;
+    for struct_field in &loading_from.types.get_nongeneric_return(type_getter).unwrap().inner_struct().fields {
+        if &struct_field.field.name != field {
+            offset += 1;
+        } else {
+            break;
+        }
+    }
+
+    let gep = type_getter.compiler.builder.build_struct_gep(from.into_pointer_value(), offset, &id.to_string()).unwrap();
+    *id += 1;
+    return gep;
+}
+
+/// Compiles the place being set to a pointer, which the checker makes sure is a variable or a field.
+/// Fields are pointers into their struct instead of their loaded value, so setting one changes the struct.
+fn compile_place<'ctx>(
+    type_getter: &mut CompilerTypeGetter<'ctx>,
+    function: FunctionValue<'ctx>,
+    place: &FinalizedEffects,
+    id: &mut u64,
+) -> PointerValue<'ctx> {
+    return match &place.types {
+        FinalizedEffectType::Load(loading_from, field, _) => {
+            compile_field_pointer(type_getter, function, loading_from, field, id)
+        }
+        _ => compile_effect(type_getter, function, place, id).unwrap().into_pointer_value(),
+    };
+}
+
 /// Stores a value and then loads it
 fn store_and_load<'ctx, T: BasicType<'ctx>>(
     type_getter: &mut CompilerTypeGetter<'ctx>,
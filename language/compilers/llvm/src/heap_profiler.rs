@@ -0,0 +1,183 @@
+use parking_lot::Mutex;
+use std::collections::BTreeMap;
+
+use inkwell::module::Linkage;
+use inkwell::types::BasicMetadataTypeEnum;
+use inkwell::values::{BasicMetadataValueEnum, FunctionValue, PointerValue};
+
+use data::tokens::Span;
+use data::CompilerArguments;
+use syntax::mangling::demangle;
+
+use crate::type_getter::CompilerTypeGetter;
+
+/// The name of the runtime function called on every tracked allocation
+static RECORD_FUNCTION: &str = "raven_record_allocation";
+/// The name of the runtime function called on every tracked free
+static FREE_FUNCTION: &str = "raven_record_free";
+
+/// Every allocation site in the program, indexed by the site id passed to the record function
+static SITES: Mutex<Vec<AllocationSite>> = Mutex::new(Vec::new());
+/// Every allocation that hasn't been freed yet, from the pointer to the site id
+static LIVE: Mutex<BTreeMap<u64, u64>> = Mutex::new(BTreeMap::new());
+
+/// A single place in the code that allocates on the heap, and the allocations made there while running
+#[derive(Clone, Debug)]
+pub struct AllocationSite {
+    /// The demangled name of the function the allocation is in
+    pub function: String,
+    /// The span of the allocating effect
+    pub span: Span,
+    /// The amount of allocations made
+    pub count: u64,
+    /// The total bytes allocated
+    pub bytes: u64,
+    /// The amount of allocations that haven't been freed yet
+    pub live: u64,
+}
+
+/// Called by the compiled program on every allocation when allocations are tracked
+extern "C" fn raven_record_allocation(site: u64, bytes: u64, pointer: u64) {
+    let mut sites = SITES.lock();
+    let found = &mut sites[site as usize];
+    found.count += 1;
+    found.bytes += bytes;
+    found.live += 1;
+    LIVE.lock().insert(pointer, site);
+}
+
+/// Called by the compiled program on every free when allocations are tracked, and by the allocator on every dealloc
+pub(crate) extern "C" fn raven_record_free(pointer: u64) {
+    let site = LIVE.lock().remove(&pointer);
+    if let Some(site) = site {
+        SITES.lock()[site as usize].live -= 1;
+    }
+}
+
+/// Called by the allocator when memory is moved by a realloc, so it's still counted for the site that allocated it
+pub(crate) fn record_move(pointer: u64, moved: u64) {
+    let mut live = LIVE.lock();
+    if let Some(site) = live.remove(&pointer) {
+        live.insert(moved, site);
+    }
+}
+
+/// Clears every allocation site, called before compiling a new program
+pub fn reset() {
+    SITES.lock().clear();
+    LIVE.lock().clear();
+}
+
+/// Whether allocations should be tracked for the arguments
+pub fn is_tracking(arguments: &CompilerArguments) -> bool {
+    return arguments.heap_profile || arguments.leak_check;
+}
+
+/// Gets every site that allocated while running, sorted by the amount of allocations
+pub fn allocation_sites() -> Vec<AllocationSite> {
+    let mut sites = SITES.lock().iter().filter(|site| site.count > 0).cloned().collect::<Vec<_>>();
+    sites.sort_by(|first, second| second.count.cmp(&first.count).then(second.bytes.cmp(&first.bytes)));
+    return sites;
+}
+
+/// Gets every site with allocations that were never freed, sorted by the amount of unfreed allocations
+pub fn leaked_sites() -> Vec<AllocationSite> {
+    let mut sites = SITES.lock().iter().filter(|site| site.live > 0).cloned().collect::<Vec<_>>();
+    sites.sort_by(|first, second| second.live.cmp(&first.live));
+    return sites;
+}
+
+/// Records an allocation at the span if allocations are tracked.
+/// The pointer type is a null pointer of the allocated type, the same one passed to malloc_type,
+/// and the allocated pointer is the value returned by malloc.
+pub fn record_allocation<'ctx>(
+    type_getter: &CompilerTypeGetter<'ctx>,
+    function: FunctionValue<'ctx>,
+    span: &Span,
+    pointer_type: PointerValue<'ctx>,
+    allocated: PointerValue<'ctx>,
+    id: &mut u64,
+) {
+    if !is_tracking(&type_getter.compiler.arguments) {
+        return;
+    }
+
+    let site = {
+        let mut sites = SITES.lock();
+        let name = function.get_name().to_str().unwrap();
+        sites.push(AllocationSite {
+            function: demangle(name).unwrap_or(name.to_string()),
+            span: span.clone(),
+            count: 0,
+            bytes: 0,
+            live: 0,
+        });
+        sites.len() as u64 - 1
+    };
+
+    let i64_type = type_getter.compiler.context.i64_type();
+    let size = unsafe {
+        type_getter.compiler.builder.build_gep(pointer_type, &[i64_type.const_int(1, false)], &id.to_string()).unwrap()
+    };
+    *id += 1;
+    let size = type_getter.compiler.builder.build_ptr_to_int(size, i64_type, &id.to_string()).unwrap();
+    *id += 1;
+    let allocated = type_getter.compiler.builder.build_ptr_to_int(allocated, i64_type, &id.to_string()).unwrap();
+    *id += 1;
+
+    type_getter
+        .compiler
+        .builder
+        .build_call(
+            get_runtime_function(type_getter, RECORD_FUNCTION, 3, raven_record_allocation as usize),
+            &[
+                BasicMetadataValueEnum::IntValue(i64_type.const_int(site, false)),
+                BasicMetadataValueEnum::IntValue(size),
+                BasicMetadataValueEnum::IntValue(allocated),
+            ],
+            &id.to_string(),
+        )
+        .unwrap();
+    *id += 1;
+}
+
+/// Records a free of the pointer if allocations are tracked, for when memory is deallocated
+pub fn record_free<'ctx>(type_getter: &CompilerTypeGetter<'ctx>, pointer: PointerValue<'ctx>, id: &mut u64) {
+    if !is_tracking(&type_getter.compiler.arguments) {
+        return;
+    }
+
+    let i64_type = type_getter.compiler.context.i64_type();
+    let pointer = type_getter.compiler.builder.build_ptr_to_int(pointer, i64_type, &id.to_string()).unwrap();
+    *id += 1;
+    type_getter
+        .compiler
+        .builder
+        .build_call(
+            get_runtime_function(type_getter, FREE_FUNCTION, 1, raven_record_free as usize),
+            &[BasicMetadataValueEnum::IntValue(pointer)],
+            &id.to_string(),
+        )
+        .unwrap();
+    *id += 1;
+}
+
+/// Gets a void runtime function taking only u64s, declaring it and mapping it to the Rust function if it doesn't exist yet
+pub(crate) fn get_runtime_function<'ctx>(
+    type_getter: &CompilerTypeGetter<'ctx>,
+    name: &str,
+    arguments: usize,
+    address: usize,
+) -> FunctionValue<'ctx> {
+    if let Some(found) = type_getter.compiler.module.get_function(name) {
+        return found;
+    }
+    let i64_type = BasicMetadataTypeEnum::from(type_getter.compiler.context.i64_type());
+    let function = type_getter.compiler.module.add_function(
+        name,
+        type_getter.compiler.context.void_type().fn_type(&vec![i64_type; arguments], false),
+        Some(Linkage::External),
+    );
+    type_getter.compiler.execution_engine.add_global_mapping(&function, address);
+    return function;
+}
@@ -0,0 +1,396 @@
+use parking_lot::Mutex;
+use std::ffi::{c_char, CStr, CString};
+use std::fmt::{Display, Formatter};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+
+use inkwell::module::Linkage;
+use inkwell::types::{AnyType, BasicMetadataTypeEnum, BasicTypeEnum};
+use inkwell::values::{BasicMetadataValueEnum, BasicValueEnum, FunctionValue};
+use inkwell::AddressSpace;
+
+use data::tokens::Span;
+
+use crate::panic_hook::raven_panic;
+use crate::type_getter::CompilerTypeGetter;
+
+/// The name of the runtime function every host function call goes through
+static CALL_FUNCTION: &str = "raven_call_host";
+
+/// A registered host function, taking and returning marshalled values
+type RawHostFunction = Arc<dyn Fn(&[u64]) -> u64 + Send + Sync>;
+
+/// A host function registered for an extern function
+struct RegisteredHost {
+    /// The full name of the extern function, like "main::log"
+    name: String,
+    /// The function, taking and returning marshalled values
+    function: RawHostFunction,
+    /// The type of each argument the function takes
+    arguments: Vec<HostType>,
+    /// The type the function returns
+    returning: HostType,
+}
+
+/// Every registered host function, indexed by the id passed to raven_call_host
+static HOST_FUNCTIONS: Mutex<Vec<RegisteredHost>> = Mutex::new(Vec::new());
+
+/// Every extern function whose declaration didn't match its host function's signature while compiling,
+/// with the reason why. Programs with any of these aren't run.
+static SIGNATURE_ERRORS: Mutex<Vec<(Span, String, String)>> = Mutex::new(Vec::new());
+
+/// The LLVM type a HostValue is passed as, which the extern function's declaration has to match
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HostType {
+    /// An integer with the amount of bits, like bool's 1 or u64's 64
+    Int(u32),
+    /// A 64-bit float
+    Float,
+    /// A pointer, like a str
+    Pointer,
+    /// Nothing, only for return values
+    Void,
+}
+
+impl Display for HostType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            HostType::Int(1) => write!(f, "bool"),
+            HostType::Int(bits) => write!(f, "a {}-bit integer", bits),
+            HostType::Float => write!(f, "f64"),
+            HostType::Pointer => write!(f, "str"),
+            HostType::Void => write!(f, "nothing"),
+        };
+    }
+}
+
+/// A value that can be passed between Raven and a host function.
+/// Every value is marshalled as a u64, with pointers (like strings) passed as their address.
+pub trait HostValue: Sized {
+    /// The type the value is declared as in Raven
+    const TYPE: HostType;
+
+    /// Reads the value from Raven
+    fn from_raw(raw: u64) -> Self;
+
+    /// Converts the value to pass it to Raven
+    fn into_raw(self) -> u64;
+}
+
+impl HostValue for u64 {
+    const TYPE: HostType = HostType::Int(64);
+
+    fn from_raw(raw: u64) -> Self {
+        return raw;
+    }
+
+    fn into_raw(self) -> u64 {
+        return self;
+    }
+}
+
+impl HostValue for i64 {
+    const TYPE: HostType = HostType::Int(64);
+
+    fn from_raw(raw: u64) -> Self {
+        return raw as i64;
+    }
+
+    fn into_raw(self) -> u64 {
+        return self as u64;
+    }
+}
+
+impl HostValue for f64 {
+    const TYPE: HostType = HostType::Float;
+
+    fn from_raw(raw: u64) -> Self {
+        return f64::from_bits(raw);
+    }
+
+    fn into_raw(self) -> u64 {
+        return self.to_bits();
+    }
+}
+
+impl HostValue for bool {
+    const TYPE: HostType = HostType::Int(1);
+
+    fn from_raw(raw: u64) -> Self {
+        return raw & 1 != 0;
+    }
+
+    fn into_raw(self) -> u64 {
+        return self as u64;
+    }
+}
+
+impl HostValue for u8 {
+    const TYPE: HostType = HostType::Int(8);
+
+    fn from_raw(raw: u64) -> Self {
+        return raw as u8;
+    }
+
+    fn into_raw(self) -> u64 {
+        return self as u64;
+    }
+}
+
+impl HostValue for String {
+    const TYPE: HostType = HostType::Pointer;
+
+    fn from_raw(raw: u64) -> Self {
+        return unsafe { CStr::from_ptr(raw as *const c_char) }.to_string_lossy().to_string();
+    }
+
+    fn into_raw(self) -> u64 {
+        // Raven has no way to free memory yet, so returned strings are leaked like any other Raven allocation
+        return CString::new(self).unwrap().into_raw() as u64;
+    }
+}
+
+impl HostValue for () {
+    const TYPE: HostType = HostType::Void;
+
+    fn from_raw(_raw: u64) -> Self {
+        return ();
+    }
+
+    fn into_raw(self) -> u64 {
+        return 0;
+    }
+}
+
+/// A Rust closure that can be called from Raven, implemented for closures taking and returning HostValues
+pub trait HostFunction<Arguments>: Send + Sync + 'static {
+    /// The types of the arguments the function takes and the type it returns
+    fn signature() -> (Vec<HostType>, HostType);
+
+    /// Calls the function with the marshalled arguments, returning the marshalled result.
+    /// The arguments always match the signature, since declarations that don't match it aren't compiled.
+    fn call(&self, arguments: &[u64]) -> u64;
+}
+
+/// Implements HostFunction for closures with the given argument types
+macro_rules! host_function {
+    ($($argument:ident),*) => {
+        impl<Function, Returning, $($argument),*> HostFunction<($($argument,)*)> for Function
+        where
+            Function: Fn($($argument),*) -> Returning + Send + Sync + 'static,
+            Returning: HostValue,
+            $($argument: HostValue),*
+        {
+            fn signature() -> (Vec<HostType>, HostType) {
+                return (vec![$(<$argument as HostValue>::TYPE),*], Returning::TYPE);
+            }
+
+            #[allow(unused_variables, unused_mut)]
+            fn call(&self, arguments: &[u64]) -> u64 {
+                let mut arguments = arguments.iter();
+                return self($(<$argument as HostValue>::from_raw(*arguments.next().unwrap())),*).into_raw();
+            }
+        }
+    };
+}
+
+host_function!();
+host_function!(A);
+host_function!(A, B);
+host_function!(A, B, C);
+host_function!(A, B, C, D);
+
+/// Registers a Rust closure as the body of the extern function with the given full name, replacing any previous one.
+/// The name is the function's path, so registering "main::log" implements extern fn log(message: str) in main.rv,
+/// and an extern fn log in any other file is still linked normally. Must be called before compiling the program.
+/// For example, register_host_function("main::log", |message: String| println!("{}", message))
+pub fn register_host_function<Arguments, Function: HostFunction<Arguments>>(name: &str, function: Function) {
+    let (arguments, returning) = Function::signature();
+    let registered = RegisteredHost {
+        name: name.to_string(),
+        function: Arc::new(move |arguments| function.call(arguments)),
+        arguments,
+        returning,
+    };
+    let mut functions = HOST_FUNCTIONS.lock();
+    match functions.iter_mut().find(|found| found.name == name) {
+        Some(found) => *found = registered,
+        None => functions.push(registered),
+    }
+}
+
+/// Removes every registered host function
+pub fn clear_host_functions() {
+    HOST_FUNCTIONS.lock().clear();
+}
+
+/// Gets the id of the host function for the Raven function's full name, if there is one
+pub fn host_function_id(name: &str) -> Option<u64> {
+    return HOST_FUNCTIONS.lock().iter().position(|found| found.name == name).map(|id| id as u64);
+}
+
+/// Clears the signature errors, called before compiling a new program
+pub fn reset() {
+    SIGNATURE_ERRORS.lock().clear();
+}
+
+/// Gets the span, name, and reason of every extern function that didn't match its host function while compiling
+pub fn signature_errors() -> Vec<(Span, String, String)> {
+    return SIGNATURE_ERRORS.lock().clone();
+}
+
+/// Called by the compiled program to call a host function.
+/// Only calls with the host function's arguments are compiled, so the arguments always match.
+/// Panics can't unwind through Raven code, so a host function that panics is reported to the panic hook instead.
+extern "C" fn raven_call_host(id: u64, arguments: *const u64, length: u64) -> u64 {
+    // The lock is released before calling, so host functions can register other functions
+    let (name, function) = {
+        let functions = HOST_FUNCTIONS.lock();
+        (functions[id as usize].name.clone(), functions[id as usize].function.clone())
+    };
+    let arguments = unsafe { std::slice::from_raw_parts(arguments, length as usize) };
+    return match panic::catch_unwind(AssertUnwindSafe(|| function(arguments))) {
+        Ok(returned) => returned,
+        Err(error) => {
+            let reason = match error.downcast_ref::<&str>() {
+                Some(reason) => reason.to_string(),
+                None => error.downcast_ref::<String>().cloned().unwrap_or_default(),
+            };
+            let message = CString::new(format!("Host function {} panicked: {}", name, reason)).unwrap_or_default();
+            raven_panic(message.as_ptr());
+            unreachable!()
+        }
+    };
+}
+
+/// Compiles the body of an extern function to call the host function with the given id,
+/// marshalling each argument to a u64 and the u64 result back to the return type.
+/// If the declaration doesn't match the host function's signature, the error is recorded with the function's span
+/// instead, so the mismatched values are never passed to the host function.
+pub fn compile_host_call<'ctx>(type_getter: &CompilerTypeGetter<'ctx>, value: FunctionValue<'ctx>, id: u64, span: &Span) {
+    let compiler = &type_getter.compiler;
+    let i64_type = compiler.context.i64_type();
+    // The function is given a body, so it no longer has to be linked externally
+    value.set_linkage(Linkage::Private);
+    let block = compiler.context.append_basic_block(value, "0");
+    compiler.builder.position_at_end(block);
+
+    let (name, expected, expected_return) = {
+        let functions = HOST_FUNCTIONS.lock();
+        let host = &functions[id as usize];
+        (host.name.clone(), host.arguments.clone(), host.returning)
+    };
+    if let Err(reason) = check_signature(value, &expected, expected_return) {
+        SIGNATURE_ERRORS.lock().push((span.clone(), name, reason));
+        compiler.builder.build_unreachable().unwrap();
+        return;
+    }
+
+    let params = value.get_params();
+    let arguments =
+        compiler.builder.build_array_alloca(i64_type, i64_type.const_int(params.len().max(1) as u64, false), "1").unwrap();
+    for (i, param) in params.iter().enumerate() {
+        let raw = match param {
+            BasicValueEnum::IntValue(int) => compiler.builder.build_int_z_extend_or_bit_cast(*int, i64_type, "2").unwrap(),
+            BasicValueEnum::FloatValue(float) => {
+                let float = compiler.builder.build_float_cast(*float, compiler.context.f64_type(), "2").unwrap();
+                compiler.builder.build_bitcast(float, i64_type, "3").unwrap().into_int_value()
+            }
+            BasicValueEnum::PointerValue(pointer) => compiler.builder.build_ptr_to_int(*pointer, i64_type, "2").unwrap(),
+            _ => panic!("Host function arguments must be numbers, bools, chars, or strings!"),
+        };
+        let offset = unsafe { compiler.builder.build_gep(arguments, &[i64_type.const_int(i as u64, false)], "4").unwrap() };
+        compiler.builder.build_store(offset, raw).unwrap();
+    }
+
+    let returned = compiler
+        .builder
+        .build_call(
+            get_call_function(type_getter),
+            &[
+                BasicMetadataValueEnum::IntValue(i64_type.const_int(id, false)),
+                BasicMetadataValueEnum::PointerValue(arguments),
+                BasicMetadataValueEnum::IntValue(i64_type.const_int(params.len() as u64, false)),
+            ],
+            "5",
+        )
+        .unwrap()
+        .try_as_basic_value()
+        .unwrap_left()
+        .into_int_value();
+
+    match value.get_type().get_return_type() {
+        Some(BasicTypeEnum::IntType(int)) => {
+            let returning = compiler.builder.build_int_truncate_or_bit_cast(returned, int, "6").unwrap();
+            compiler.builder.build_return(Some(&returning)).unwrap();
+        }
+        Some(BasicTypeEnum::FloatType(float)) => {
+            let returning = compiler.builder.build_bitcast(returned, compiler.context.f64_type(), "6").unwrap();
+            let returning = compiler.builder.build_float_cast(returning.into_float_value(), float, "7").unwrap();
+            compiler.builder.build_return(Some(&returning)).unwrap();
+        }
+        Some(BasicTypeEnum::PointerType(pointer)) => {
+            let returning = compiler.builder.build_int_to_ptr(returned, pointer, "6").unwrap();
+            compiler.builder.build_return(Some(&returning)).unwrap();
+        }
+        Some(_) => panic!("Host functions must return numbers, bools, chars, or strings!"),
+        None => {
+            compiler.builder.build_return(None).unwrap();
+        }
+    }
+}
+
+/// Checks the extern function's declaration matches the host function's argument and return types
+fn check_signature(value: FunctionValue, arguments: &Vec<HostType>, returning: HostType) -> Result<(), String> {
+    let params = value.get_params();
+    if params.len() != arguments.len() {
+        return Err(format!("it takes {} arguments but is declared with {}", arguments.len(), params.len()));
+    }
+    for (i, (param, expected)) in params.iter().zip(arguments).enumerate() {
+        if host_type(Some(param.get_type())) != Some(*expected) {
+            return Err(format!("argument {} should be {}", i + 1, expected));
+        }
+    }
+    if host_type(value.get_type().get_return_type()) != Some(returning) {
+        return Err(format!("it returns {}", returning));
+    }
+    return Ok(());
+}
+
+/// Gets the host type an LLVM type is passed as, or None if it can't be passed to a host function
+fn host_type(types: Option<BasicTypeEnum>) -> Option<HostType> {
+    return match types {
+        None => Some(HostType::Void),
+        Some(BasicTypeEnum::IntType(int)) => Some(HostType::Int(int.get_bit_width())),
+        Some(BasicTypeEnum::FloatType(float)) if float == float.get_context().f64_type() => Some(HostType::Float),
+        // Only Raven strs are passed as pointers, other pointers like structs would be read as strings
+        Some(BasicTypeEnum::PointerType(pointer))
+            if pointer.get_element_type() == pointer.get_context().i8_type().as_any_type_enum() =>
+        {
+            Some(HostType::Pointer)
+        }
+        Some(_) => None,
+    };
+}
+
+/// Gets raven_call_host, declaring it and mapping it to the Rust function if it doesn't exist yet
+fn get_call_function<'ctx>(type_getter: &CompilerTypeGetter<'ctx>) -> FunctionValue<'ctx> {
+    if let Some(found) = type_getter.compiler.module.get_function(CALL_FUNCTION) {
+        return found;
+    }
+    let i64_type = type_getter.compiler.context.i64_type();
+    let function = type_getter.compiler.module.add_function(
+        CALL_FUNCTION,
+        i64_type.fn_type(
+            &[
+                BasicMetadataTypeEnum::from(i64_type),
+                BasicMetadataTypeEnum::from(i64_type.ptr_type(AddressSpace::default())),
+                BasicMetadataTypeEnum::from(i64_type),
+            ],
+            false,
+        ),
+        Some(Linkage::External),
+    );
+    type_getter.compiler.execution_engine.add_global_mapping(&function, raven_call_host as usize);
+    return function;
+}
@@ -0,0 +1,80 @@
+use inkwell::memory_buffer::MemoryBuffer;
+use inkwell::types::{AnyType, BasicMetadataTypeEnum, BasicType, BasicTypeEnum, FunctionType};
+use inkwell::values::{BasicMetadataValueEnum, BasicValueEnum, CallableValue, FunctionValue};
+
+use syntax::program::code::InlineCode;
+
+use crate::type_getter::CompilerTypeGetter;
+
+/// Compiles a llvm! or asm! block, calling the raw code with the inputs and returning its output, if it has one.
+/// Inputs and outputs are passed by value, so they should be numbers, bools, or pointers.
+pub fn compile_inline_code<'ctx>(
+    type_getter: &mut CompilerTypeGetter<'ctx>,
+    function: FunctionValue<'ctx>,
+    code: &InlineCode,
+    inputs: &[BasicValueEnum<'ctx>],
+    output: Option<BasicTypeEnum<'ctx>>,
+    id: &mut u64,
+) -> Option<BasicValueEnum<'ctx>> {
+    let parameters = inputs.iter().map(|input| BasicMetadataTypeEnum::from(input.get_type())).collect::<Vec<_>>();
+    let function_type = match output {
+        Some(output) => output.fn_type(&parameters, false),
+        None => type_getter.compiler.context.void_type().fn_type(&parameters, false),
+    };
+
+    let calling = match code {
+        InlineCode::Llvm(body) => CallableValue::from(splice_ir(type_getter, function, body, function_type, id)),
+        InlineCode::Asm(template, constraints) => {
+            // Assembly is assumed to have side effects, so LLVM never removes or reorders it
+            let assembly = type_getter.compiler.context.create_inline_asm(
+                function_type,
+                template.clone(),
+                constraints.clone(),
+                true,
+                false,
+                None,
+                false,
+            );
+            CallableValue::try_from(assembly).unwrap()
+        }
+    };
+
+    let arguments = inputs.iter().map(|input| BasicMetadataValueEnum::from(*input)).collect::<Vec<_>>();
+    let call = type_getter.compiler.builder.build_call(calling, &arguments, &id.to_string()).unwrap();
+    *id += 1;
+    return call.try_as_basic_value().left();
+}
+
+/// Parses the IR as the body of a new function of the type and links it into the module, returning the function.
+/// The inputs are the function's parameters %0, %1, and so on, and the body starts in a block labeled entry,
+/// so the IR's own unnamed values continue from the last input. The function is always inlined into its caller.
+fn splice_ir<'ctx>(
+    type_getter: &CompilerTypeGetter<'ctx>,
+    function: FunctionValue<'ctx>,
+    body: &str,
+    function_type: FunctionType<'ctx>,
+    id: &u64,
+) -> FunctionValue<'ctx> {
+    let name = format!("{}.llvm{}", function.get_name().to_str().unwrap(), id);
+    let parameters = function_type
+        .get_param_types()
+        .iter()
+        .enumerate()
+        .map(|(index, parameter)| format!("{} %{}", parameter.print_to_string(), index))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let returning =
+        function_type.get_return_type().map_or("void".to_string(), |returning| returning.print_to_string().to_string());
+    let source =
+        format!("define internal {} @\"{}\"({}) alwaysinline {{\nentry:\n{}\n}}\n", returning, name, parameters, body);
+
+    // The checker can't parse IR, so invalid IR is only found here
+    let buffer = MemoryBuffer::create_from_memory_range_copy(source.as_bytes(), &name);
+    let module = type_getter
+        .compiler
+        .context
+        .create_module_from_ir(buffer)
+        .unwrap_or_else(|error| panic!("Invalid llvm! in {}: {}", function.get_name().to_str().unwrap(), error));
+    type_getter.compiler.module.link_in_module(module).unwrap();
+    return type_getter.compiler.module.get_function(&name).unwrap();
+}
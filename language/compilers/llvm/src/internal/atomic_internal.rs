@@ -0,0 +1,133 @@
+use crate::compiler::CompilerImpl;
+use crate::internal::instructions::malloc_type;
+use crate::type_getter::CompilerTypeGetter;
+use inkwell::types::IntType;
+use inkwell::values::{FunctionValue, IntValue, PointerValue};
+use inkwell::{AtomicOrdering, AtomicRMWBinOp};
+
+/// The memory orderings, in the order of the codes core's atomic MemoryOrdering uses for them
+static ORDERINGS: [AtomicOrdering; 5] = [
+    AtomicOrdering::Monotonic,
+    AtomicOrdering::Acquire,
+    AtomicOrdering::Release,
+    AtomicOrdering::AcquireRelease,
+    AtomicOrdering::SequentiallyConsistent,
+];
+
+/// Compiles the internal methods of core's atomic types, which are LLVM atomic instructions on the struct's value field.
+/// The ordering is passed as a code, but LLVM needs it to be constant, so each ordering gets its own block which the
+/// code switches between. Once inlined with a constant ordering, the switch is optimized away.
+/// Values narrower than the field, like bools, are widened to it and narrowed back when returned.
+pub fn atomic_internal<'ctx>(
+    type_getter: &CompilerTypeGetter<'ctx>,
+    compiler: &CompilerImpl<'ctx>,
+    name: &String,
+    value: &FunctionValue<'ctx>,
+) -> bool {
+    if !name.starts_with("atomic::") {
+        return false;
+    }
+    // Names look like atomic::AtomicU64::atomic_load, and each method takes self, its values, then the ordering code.
+    // Orderings that LLVM doesn't allow for the operation are checked by Raven before calling it.
+    let method = name.split('$').next().unwrap().rsplit("::").next().unwrap();
+    let orderings: &[usize] = match method {
+        "atomic_load" => &[0, 1, 4],
+        "atomic_store" => &[0, 2, 4],
+        "atomic_swap" | "atomic_compare_exchange" | "atomic_add" | "atomic_sub" => &[0, 1, 2, 3, 4],
+        _ => return false,
+    };
+
+    let params = value.get_params();
+    // The value is the struct's first field, after its type id
+    let field = compiler.builder.build_struct_gep(params.first().unwrap().into_pointer_value(), 1, "1").unwrap();
+    let field_type = field.get_type().get_element_type().into_int_type();
+    let arguments = params[1..params.len() - 1]
+        .iter()
+        .map(|argument| {
+            let loaded = compiler.builder.build_load(argument.into_pointer_value(), "2").unwrap().into_int_value();
+            if loaded.get_type().get_bit_width() < field_type.get_bit_width() {
+                compiler.builder.build_int_z_extend(loaded, field_type, "3").unwrap()
+            } else {
+                loaded
+            }
+        })
+        .collect::<Vec<_>>();
+    let ordering = compiler.builder.build_load(params.last().unwrap().into_pointer_value(), "4").unwrap().into_int_value();
+    let returning = value
+        .get_type()
+        .get_return_type()
+        .map(|returning| malloc_type(type_getter, returning.into_pointer_type().const_zero(), &mut 5));
+
+    let entry = compiler.builder.get_insert_block().unwrap();
+    let end = compiler.context.append_basic_block(*value, "end");
+    let mut cases = Vec::default();
+    for code in orderings {
+        let block = compiler.context.append_basic_block(*value, &format!("ordering{}", code));
+        compiler.builder.position_at_end(block);
+        let result = build_operation(compiler, method, field, field_type, &arguments, ORDERINGS[*code]);
+        if let (Some(returning), Some(result)) = (returning, result) {
+            let returning_type = returning.get_type().get_element_type().into_int_type();
+            let result = if returning_type.get_bit_width() < field_type.get_bit_width() {
+                compiler.builder.build_int_truncate(result, returning_type, "7").unwrap()
+            } else {
+                result
+            };
+            compiler.builder.build_store(returning, result).unwrap();
+        }
+        compiler.builder.build_unconditional_branch(end).unwrap();
+        cases.push((compiler.context.i64_type().const_int(*code as u64, false), block));
+    }
+
+    // Unknown codes can't be made from Raven, so they're sequentially consistent like the last case
+    compiler.builder.position_at_end(entry);
+    compiler.builder.build_switch(ordering, cases.last().unwrap().1, &cases).unwrap();
+    compiler.builder.position_at_end(end);
+    match &returning {
+        Some(returning) => compiler.builder.build_return(Some(returning)).unwrap(),
+        None => compiler.builder.build_return(None).unwrap(),
+    };
+    return true;
+}
+
+/// Builds the atomic operation on the field with the ordering, returning the value it read if it reads one
+fn build_operation<'ctx>(
+    compiler: &CompilerImpl<'ctx>,
+    method: &str,
+    field: PointerValue<'ctx>,
+    field_type: IntType<'ctx>,
+    arguments: &[IntValue<'ctx>],
+    ordering: AtomicOrdering,
+) -> Option<IntValue<'ctx>> {
+    // Atomic loads and stores have to be aligned to their size
+    let alignment = field_type.get_bit_width() / 8;
+    return match method {
+        "atomic_load" => {
+            let loaded = compiler.builder.build_load(field, "6").unwrap();
+            let instruction = loaded.as_instruction_value().unwrap();
+            instruction.set_atomic_ordering(ordering).unwrap();
+            instruction.set_alignment(alignment).unwrap();
+            Some(loaded.into_int_value())
+        }
+        "atomic_store" => {
+            let instruction = compiler.builder.build_store(field, arguments[0]).unwrap();
+            instruction.set_atomic_ordering(ordering).unwrap();
+            instruction.set_alignment(alignment).unwrap();
+            None
+        }
+        "atomic_swap" => {
+            Some(compiler.builder.build_atomicrmw(AtomicRMWBinOp::Xchg, field, arguments[0], ordering).unwrap())
+        }
+        "atomic_add" => Some(compiler.builder.build_atomicrmw(AtomicRMWBinOp::Add, field, arguments[0], ordering).unwrap()),
+        "atomic_sub" => Some(compiler.builder.build_atomicrmw(AtomicRMWBinOp::Sub, field, arguments[0], ordering).unwrap()),
+        _ => {
+            // A failed compare exchange only loads, so it can't have a release ordering
+            let failure = match ordering {
+                AtomicOrdering::Release => AtomicOrdering::Monotonic,
+                AtomicOrdering::AcquireRelease => AtomicOrdering::Acquire,
+                other => other,
+            };
+            let result = compiler.builder.build_cmpxchg(field, arguments[0], arguments[1], ordering, failure).unwrap();
+            Some(compiler.builder.build_extract_value(result, 0, "6").unwrap().into_int_value())
+        }
+    };
+}
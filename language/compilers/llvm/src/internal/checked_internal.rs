@@ -0,0 +1,111 @@
+use crate::compiler::CompilerImpl;
+use crate::internal::instructions::malloc_type;
+use crate::internal::math_internal::is_unsigned;
+use crate::type_getter::CompilerTypeGetter;
+use inkwell::intrinsics::Intrinsic;
+use inkwell::types::BasicType;
+use inkwell::values::{BasicValueEnum, FunctionValue, IntValue};
+use inkwell::{AddressSpace, IntPredicate};
+
+/// Compiles the internal overflowing, wrapping, and saturating math functions of the checked module.
+/// These call LLVM's overflow and saturation intrinsics directly, so they behave the same whether or not
+/// overflow checks are enabled.
+pub fn checked_internal<'ctx>(
+    type_getter: &CompilerTypeGetter<'ctx>,
+    compiler: &CompilerImpl<'ctx>,
+    name: &String,
+    value: &FunctionValue<'ctx>,
+) -> bool {
+    if !name.starts_with("checked::") {
+        return false;
+    }
+    // Names look like checked::WrappingMath<T>_T::wrapping_add$u64
+    let method = name.split('$').next().unwrap().rsplit("::").next().unwrap();
+    let operation = match method.split('_').find(|part| matches!(*part, "add" | "sub" | "mul")) {
+        Some(operation) => operation,
+        None => return false,
+    };
+
+    let params = value.get_params();
+    let pointer_type = params.first().unwrap().into_pointer_value();
+    let first = compiler.builder.build_load(pointer_type, "2").unwrap().into_int_value();
+    let second = compiler.builder.build_load(params.get(1).unwrap().into_pointer_value(), "3").unwrap().into_int_value();
+    let unsigned = is_unsigned(name);
+    let prefix = if unsigned { "u" } else { "s" };
+
+    let (malloc, returning) = if method.ends_with("_overflows") {
+        let malloc = malloc_type(
+            type_getter,
+            type_getter.compiler.context.bool_type().ptr_type(AddressSpace::default()).const_zero(),
+            &mut 0,
+        );
+        let result = call_intrinsic(compiler, &format!("llvm.{}{}.with.overflow", prefix, operation), first, second);
+        (malloc, compiler.builder.build_extract_value(result.into_struct_value(), 1, "4").unwrap().into_int_value())
+    } else if method.starts_with("wrapping_") {
+        let malloc = malloc_type(type_getter, pointer_type.get_type().const_zero(), &mut 0);
+        let returning = match operation {
+            "add" => compiler.builder.build_int_add(first, second, "4"),
+            "sub" => compiler.builder.build_int_sub(first, second, "4"),
+            _ => compiler.builder.build_int_mul(first, second, "4"),
+        };
+        (malloc, returning.unwrap())
+    } else if method.starts_with("saturating_") {
+        let malloc = malloc_type(type_getter, pointer_type.get_type().const_zero(), &mut 0);
+        let returning = if operation == "mul" {
+            build_saturating_mul(compiler, unsigned, first, second)
+        } else {
+            call_intrinsic(compiler, &format!("llvm.{}{}.sat", prefix, operation), first, second).into_int_value()
+        };
+        (malloc, returning)
+    } else {
+        return false;
+    };
+    compiler.builder.build_store(malloc, returning).unwrap();
+    compiler.builder.build_return(Some(&malloc)).unwrap();
+    return true;
+}
+
+/// Calls an LLVM intrinsic that's overloaded on the type of its two integer arguments
+fn call_intrinsic<'ctx>(
+    compiler: &CompilerImpl<'ctx>,
+    intrinsic: &str,
+    first: IntValue<'ctx>,
+    second: IntValue<'ctx>,
+) -> BasicValueEnum<'ctx> {
+    let intrinsic = Intrinsic::find(intrinsic)
+        .unwrap()
+        .get_declaration(&compiler.module, &[first.get_type().as_basic_type_enum()])
+        .unwrap();
+    return compiler
+        .builder
+        .build_call(intrinsic, &[first.into(), second.into()], "5")
+        .unwrap()
+        .try_as_basic_value()
+        .unwrap_left();
+}
+
+/// Builds a multiplication that stops at the type's smallest or largest value instead of overflowing.
+/// LLVM has no saturating multiply for plain integers, so this picks the bound when the overflow intrinsic overflows.
+fn build_saturating_mul<'ctx>(
+    compiler: &CompilerImpl<'ctx>,
+    unsigned: bool,
+    first: IntValue<'ctx>,
+    second: IntValue<'ctx>,
+) -> IntValue<'ctx> {
+    let prefix = if unsigned { "u" } else { "s" };
+    let result = call_intrinsic(compiler, &format!("llvm.{}mul.with.overflow", prefix), first, second).into_struct_value();
+    let wrapped = compiler.builder.build_extract_value(result, 0, "6").unwrap().into_int_value();
+    let overflowed = compiler.builder.build_extract_value(result, 1, "7").unwrap().into_int_value();
+
+    let int_type = first.get_type();
+    let bound = if unsigned {
+        int_type.const_all_ones()
+    } else {
+        // Signed products overflow towards the minimum if exactly one side is negative, and the maximum otherwise
+        let minimum = int_type.const_int(1 << (int_type.get_bit_width() - 1), false);
+        let signs = compiler.builder.build_xor(first, second, "8").unwrap();
+        let negative = compiler.builder.build_int_compare(IntPredicate::SLT, signs, int_type.const_zero(), "9").unwrap();
+        compiler.builder.build_select(negative, minimum, minimum.const_not(), "10").unwrap().into_int_value()
+    };
+    return compiler.builder.build_select(overflowed, bound, wrapped, "11").unwrap().into_int_value();
+}
@@ -0,0 +1,86 @@
+use crate::compiler::CompilerImpl;
+use crate::internal::instructions::malloc_type;
+use crate::type_getter::CompilerTypeGetter;
+use inkwell::values::{FunctionValue, IntValue};
+use inkwell::{AddressSpace, IntPredicate};
+
+/// The FNV-1a 64-bit prime, every byte is mixed into the hash by multiplying with it
+const FNV_PRIME: u64 = 1099511628211;
+
+/// Compiles the internal hashing functions, which use FNV-1a so hashes are stable between runs.
+/// The multiplications wrap on purpose, so these are done here instead of in Raven where overflow checks could panic.
+pub fn hash_internal<'ctx>(
+    type_getter: &CompilerTypeGetter<'ctx>,
+    compiler: &CompilerImpl<'ctx>,
+    name: &String,
+    value: &FunctionValue<'ctx>,
+) -> bool {
+    let params = value.get_params();
+    let i64_type = compiler.context.i64_type();
+    if name.starts_with("hash::hash_u64") || name.starts_with("hash::hash_char") {
+        let malloc = malloc_type(type_getter, i64_type.ptr_type(AddressSpace::default()).const_zero(), &mut 0);
+        let mut state =
+            compiler.builder.build_load(params.first().unwrap().into_pointer_value(), "1").unwrap().into_int_value();
+        let hashing =
+            compiler.builder.build_load(params.get(1).unwrap().into_pointer_value(), "2").unwrap().into_int_value();
+        // Chars are hashed as their code point, so they hash the same as the number
+        let hashing = compiler.builder.build_int_z_extend_or_bit_cast(hashing, i64_type, "5").unwrap();
+
+        // Mixes in each byte of the number, from lowest to highest
+        for byte in 0..8 {
+            let shifted =
+                compiler.builder.build_right_shift(hashing, i64_type.const_int(byte * 8, false), false, "3").unwrap();
+            let masked = compiler.builder.build_and(shifted, i64_type.const_int(0xFF, false), "4").unwrap();
+            state = mix_byte(compiler, state, masked);
+        }
+        compiler.builder.build_store(malloc, state).unwrap();
+        compiler.builder.build_return(Some(&malloc)).unwrap();
+    } else if name.starts_with("hash::hash_str") {
+        let entry = compiler.builder.get_insert_block().unwrap();
+        let looping = compiler.context.append_basic_block(*value, "loop");
+        let body = compiler.context.append_basic_block(*value, "body");
+        let end = compiler.context.append_basic_block(*value, "end");
+
+        let start = compiler.builder.build_load(params.first().unwrap().into_pointer_value(), "1").unwrap().into_int_value();
+        let string = params.get(1).unwrap().into_pointer_value();
+        compiler.builder.build_unconditional_branch(looping).unwrap();
+
+        // Walks the string until the null terminator, mixing in each byte
+        compiler.builder.position_at_end(looping);
+        let index = compiler.builder.build_phi(i64_type, "2").unwrap();
+        let state = compiler.builder.build_phi(i64_type, "3").unwrap();
+        let character = unsafe {
+            compiler.builder.build_in_bounds_gep(string, &[index.as_basic_value().into_int_value()], "4").unwrap()
+        };
+        let character = compiler.builder.build_load(character, "5").unwrap().into_int_value();
+        let finished =
+            compiler.builder.build_int_compare(IntPredicate::EQ, character, character.get_type().const_zero(), "6").unwrap();
+        compiler.builder.build_conditional_branch(finished, end, body).unwrap();
+
+        compiler.builder.position_at_end(body);
+        let extended = compiler.builder.build_int_z_extend(character, i64_type, "7").unwrap();
+        let mixed = mix_byte(compiler, state.as_basic_value().into_int_value(), extended);
+        let next = compiler
+            .builder
+            .build_int_add(index.as_basic_value().into_int_value(), i64_type.const_int(1, false), "8")
+            .unwrap();
+        compiler.builder.build_unconditional_branch(looping).unwrap();
+
+        index.add_incoming(&[(&i64_type.const_zero(), entry), (&next, body)]);
+        state.add_incoming(&[(&start, entry), (&mixed, body)]);
+
+        compiler.builder.position_at_end(end);
+        let malloc = malloc_type(type_getter, i64_type.ptr_type(AddressSpace::default()).const_zero(), &mut 0);
+        compiler.builder.build_store(malloc, state.as_basic_value().into_int_value()).unwrap();
+        compiler.builder.build_return(Some(&malloc)).unwrap();
+    } else {
+        return false;
+    }
+    return true;
+}
+
+/// Mixes a single byte into an FNV-1a hash state
+fn mix_byte<'ctx>(compiler: &CompilerImpl<'ctx>, state: IntValue<'ctx>, byte: IntValue<'ctx>) -> IntValue<'ctx> {
+    let xored = compiler.builder.build_xor(state, byte, "9").unwrap();
+    return compiler.builder.build_int_mul(xored, compiler.context.i64_type().const_int(FNV_PRIME, false), "10").unwrap();
+}
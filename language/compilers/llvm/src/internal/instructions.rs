@@ -1,8 +1,13 @@
 use crate::compiler::CompilerImpl;
+use crate::internal::atomic_internal::atomic_internal;
+use crate::internal::checked_internal::checked_internal;
+use crate::internal::hash_internal::hash_internal;
 use crate::internal::intrinsics::compile_llvm_intrinsics;
 use crate::internal::math_internal::math_internal;
 use crate::internal::string_internal::string_internal;
+use crate::internal::thread_local_internal::thread_local_internal;
 use crate::type_getter::CompilerTypeGetter;
+use data::stats;
 use inkwell::builder::Builder;
 use inkwell::types::{BasicType, BasicTypeEnum};
 use inkwell::values::{BasicMetadataValueEnum, BasicValue, BasicValueEnum, FunctionValue, PointerValue};
@@ -18,7 +23,13 @@ pub fn compile_internal<'ctx>(
     let block = compiler.context.append_basic_block(value, "0");
     compiler.builder.position_at_end(block);
     let params = value.get_params();
-    if string_internal(type_getter, compiler, name, &value) || math_internal(type_getter, compiler, name, &value) {
+    if string_internal(type_getter, compiler, name, &value)
+        || math_internal(type_getter, compiler, name, &value)
+        || hash_internal(type_getter, compiler, name, &value)
+        || checked_internal(type_getter, compiler, name, &value)
+        || thread_local_internal(type_getter, compiler, name, &value)
+        || atomic_internal(type_getter, compiler, name, &value)
+    {
         return;
     }
     if name.starts_with("numbers::Cast") {
@@ -79,6 +90,22 @@ pub fn compile_internal<'ctx>(
 
         let gep = compiler.builder.build_load(gep, "2").unwrap();
         compiler.builder.build_return(Some(&gep)).unwrap();
+    } else if name.starts_with("array::Array") && name.contains("::length") {
+        // The length is stored as an i64 before the first element
+        let length = compiler
+            .builder
+            .build_bitcast(
+                params.first().unwrap().into_pointer_value(),
+                compiler.context.i64_type().ptr_type(AddressSpace::default()),
+                "1",
+            )
+            .unwrap()
+            .into_pointer_value();
+        let length = compiler.builder.build_load(length, "2").unwrap();
+        let malloc =
+            malloc_type(type_getter, compiler.context.i64_type().ptr_type(AddressSpace::default()).const_zero(), &mut 3);
+        compiler.builder.build_store(malloc, length).unwrap();
+        compiler.builder.build_return(Some(&malloc)).unwrap();
     } else if name.starts_with("array::Empty") {
         let size = unsafe {
             type_getter
@@ -117,6 +144,7 @@ pub fn malloc_type<'a>(
     pointer_type: PointerValue<'a>,
     id: &mut u64,
 ) -> PointerValue<'a> {
+    stats::HEAP_ALLOCATIONS.add();
     let size = unsafe {
         type_getter
             .compiler
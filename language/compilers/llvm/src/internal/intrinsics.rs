@@ -1,3 +1,18 @@
+use crate::allocator::{raven_alloc, raven_dealloc, raven_realloc};
+use crate::arena_runtime::{
+    raven_arena_alloc, raven_arena_allocated, raven_arena_drop, raven_arena_enter, raven_arena_entered, raven_arena_exit,
+    raven_arena_free, raven_arena_generation, raven_arena_new,
+};
+use crate::bytes_runtime::{
+    raven_bytes_copy, raven_bytes_from_str, raven_bytes_get, raven_bytes_length, raven_bytes_new, raven_bytes_push,
+    raven_bytes_read_file, raven_bytes_set, raven_bytes_to_str, raven_bytes_write_file, raven_tcp_close, raven_tcp_connect,
+    raven_tcp_receive, raven_tcp_send,
+};
+use crate::float_runtime::{raven_f64_is_valid, raven_f64_parse, raven_f64_to_string, raven_f64_to_string_precision};
+use crate::internal::thread_local_internal::raven_thread_local_key;
+use crate::json_runtime::{raven_json_escape, raven_json_field, raven_json_string, raven_json_u64};
+use crate::os_runtime::{raven_env_var, raven_spawn_process};
+use crate::panic_hook::raven_panic;
 use crate::type_getter::CompilerTypeGetter;
 use inkwell::types::BasicMetadataTypeEnum;
 use inkwell::values::FunctionValue;
@@ -5,11 +20,22 @@ use inkwell::AddressSpace;
 
 /// Compiles a method with the attribute #[llvm_intrinsic]
 pub fn compile_llvm_intrinsics<'ctx>(name: &str, type_getter: &CompilerTypeGetter<'ctx>) -> FunctionValue<'ctx> {
-    if let Some(func) = type_getter.compiler.module.get_function(&name) {
+    // Freestanding programs provide their own allocator and panic handler, which are linked like any other extern
+    let hook = match name {
+        "malloc" => type_getter.syntax.lock().allocator.clone(),
+        "panic" => type_getter.syntax.lock().panic_handler.clone(),
+        _ => None,
+    };
+    let symbol = hook.as_deref().unwrap_or(name);
+    if let Some(func) = type_getter.compiler.module.get_function(symbol) {
         return func;
     }
-    return type_getter.compiler.module.add_function(
-        &name,
+    // Handles to runtime objects like byte buffers and streams are pointers passed as integers
+    let context = type_getter.compiler.context;
+    let handle = BasicMetadataTypeEnum::from(context.i64_type());
+    let string = BasicMetadataTypeEnum::from(context.i8_type().ptr_type(AddressSpace::default()));
+    let function = type_getter.compiler.module.add_function(
+        symbol,
         match name {
             "printf" => type_getter.compiler.context.i32_type().fn_type(
                 &[BasicMetadataTypeEnum::from(type_getter.compiler.context.i8_type().ptr_type(AddressSpace::default()))],
@@ -37,6 +63,10 @@ pub fn compile_llvm_intrinsics<'ctx>(name: &str, type_getter: &CompilerTypeGette
                 &[BasicMetadataTypeEnum::from(type_getter.compiler.context.i8_type().ptr_type(AddressSpace::default()))],
                 false,
             ),
+            "panic" => type_getter.compiler.context.void_type().fn_type(
+                &[BasicMetadataTypeEnum::from(type_getter.compiler.context.i8_type().ptr_type(AddressSpace::default()))],
+                false,
+            ),
             "strcmp" => type_getter.compiler.context.i64_type().fn_type(
                 &[
                     BasicMetadataTypeEnum::from(type_getter.compiler.context.i8_type().ptr_type(AddressSpace::default())),
@@ -44,8 +74,110 @@ pub fn compile_llvm_intrinsics<'ctx>(name: &str, type_getter: &CompilerTypeGette
                 ],
                 false,
             ),
+            "env_var" => type_getter.compiler.context.i8_type().ptr_type(AddressSpace::default()).fn_type(
+                &[BasicMetadataTypeEnum::from(type_getter.compiler.context.i8_type().ptr_type(AddressSpace::default()))],
+                false,
+            ),
+            "spawn_process" => type_getter.compiler.context.i64_type().fn_type(
+                &[
+                    BasicMetadataTypeEnum::from(type_getter.compiler.context.i8_type().ptr_type(AddressSpace::default())),
+                    BasicMetadataTypeEnum::from(type_getter.compiler.context.i8_type().ptr_type(AddressSpace::default())),
+                    BasicMetadataTypeEnum::from(type_getter.compiler.context.i8_type().ptr_type(AddressSpace::default())),
+                ],
+                false,
+            ),
+            "json_escape" | "json_string" => {
+                type_getter.compiler.context.i8_type().ptr_type(AddressSpace::default()).fn_type(
+                    &[BasicMetadataTypeEnum::from(type_getter.compiler.context.i8_type().ptr_type(AddressSpace::default()))],
+                    false,
+                )
+            }
+            "json_field" => type_getter.compiler.context.i8_type().ptr_type(AddressSpace::default()).fn_type(
+                &[
+                    BasicMetadataTypeEnum::from(type_getter.compiler.context.i8_type().ptr_type(AddressSpace::default())),
+                    BasicMetadataTypeEnum::from(type_getter.compiler.context.i8_type().ptr_type(AddressSpace::default())),
+                ],
+                false,
+            ),
+            "json_u64" => type_getter.compiler.context.i64_type().fn_type(
+                &[BasicMetadataTypeEnum::from(type_getter.compiler.context.i8_type().ptr_type(AddressSpace::default()))],
+                false,
+            ),
+            "bytes_new" => context.i64_type().fn_type(&[], false),
+            "bytes_length" => context.i64_type().fn_type(&[handle], false),
+            "bytes_get" | "bytes_copy" | "tcp_send" => context.i64_type().fn_type(&[handle, handle], false),
+            "bytes_set" => context.void_type().fn_type(&[handle, handle, handle], false),
+            "bytes_push" => context.void_type().fn_type(&[handle, handle], false),
+            "bytes_from_str" | "tcp_connect" => context.i64_type().fn_type(&[string], false),
+            "bytes_to_str" => context.i8_type().ptr_type(AddressSpace::default()).fn_type(&[handle], false),
+            "bytes_read_file" | "bytes_write_file" => context.i64_type().fn_type(&[handle, string], false),
+            "tcp_receive" => context.i64_type().fn_type(&[handle, handle, handle], false),
+            "tcp_close" => context.void_type().fn_type(&[handle], false),
+            "f64_to_string" => {
+                context.i8_type().ptr_type(AddressSpace::default()).fn_type(&[context.f64_type().into()], false)
+            }
+            "f64_to_string_precision" => {
+                context.i8_type().ptr_type(AddressSpace::default()).fn_type(&[context.f64_type().into(), handle], false)
+            }
+            "f64_parse" => context.f64_type().fn_type(&[string], false),
+            "f64_is_valid" => context.i64_type().fn_type(&[string], false),
+            "dealloc" => context.void_type().fn_type(&[handle, handle], false),
+            "realloc" => context.i64_type().fn_type(&[handle, handle, handle], false),
+            "arena_new" | "thread_local_key" => context.i64_type().fn_type(&[], false),
+            "arena_alloc" => context.i64_type().fn_type(&[handle, handle], false),
+            "arena_generation" | "arena_allocated" | "arena_entered" => context.i64_type().fn_type(&[handle], false),
+            "arena_free" | "arena_drop" | "arena_enter" | "arena_exit" => context.void_type().fn_type(&[handle], false),
             _ => panic!("Tried to compile unknown LLVM intrinsic {}", name),
         },
         None,
     );
+    if hook.is_some() {
+        return function;
+    }
+    // Panics are handled by the runtime instead of libc, so the hook can be called, and allocations are too,
+    // so they go through the installed allocator.
+    // The os, JSON, bytes, net, arena, thread local, and float functions are too, so they behave the same on every platform.
+    let runtime = match name {
+        "panic" => raven_panic as usize,
+        "malloc" => raven_alloc as usize,
+        "dealloc" => raven_dealloc as usize,
+        "realloc" => raven_realloc as usize,
+        "env_var" => raven_env_var as usize,
+        "spawn_process" => raven_spawn_process as usize,
+        "json_escape" => raven_json_escape as usize,
+        "json_field" => raven_json_field as usize,
+        "json_u64" => raven_json_u64 as usize,
+        "json_string" => raven_json_string as usize,
+        "bytes_new" => raven_bytes_new as usize,
+        "bytes_length" => raven_bytes_length as usize,
+        "bytes_get" => raven_bytes_get as usize,
+        "bytes_set" => raven_bytes_set as usize,
+        "bytes_push" => raven_bytes_push as usize,
+        "bytes_from_str" => raven_bytes_from_str as usize,
+        "bytes_to_str" => raven_bytes_to_str as usize,
+        "bytes_copy" => raven_bytes_copy as usize,
+        "bytes_read_file" => raven_bytes_read_file as usize,
+        "bytes_write_file" => raven_bytes_write_file as usize,
+        "tcp_connect" => raven_tcp_connect as usize,
+        "tcp_send" => raven_tcp_send as usize,
+        "tcp_receive" => raven_tcp_receive as usize,
+        "tcp_close" => raven_tcp_close as usize,
+        "f64_to_string" => raven_f64_to_string as usize,
+        "f64_to_string_precision" => raven_f64_to_string_precision as usize,
+        "f64_parse" => raven_f64_parse as usize,
+        "f64_is_valid" => raven_f64_is_valid as usize,
+        "arena_new" => raven_arena_new as usize,
+        "arena_alloc" => raven_arena_alloc as usize,
+        "arena_generation" => raven_arena_generation as usize,
+        "arena_allocated" => raven_arena_allocated as usize,
+        "arena_free" => raven_arena_free as usize,
+        "arena_drop" => raven_arena_drop as usize,
+        "arena_enter" => raven_arena_enter as usize,
+        "arena_exit" => raven_arena_exit as usize,
+        "arena_entered" => raven_arena_entered as usize,
+        "thread_local_key" => raven_thread_local_key as usize,
+        _ => return function,
+    };
+    type_getter.compiler.execution_engine.add_global_mapping(&function, runtime);
+    return function;
 }
@@ -1,7 +1,10 @@
 use crate::compiler::CompilerImpl;
 use crate::internal::instructions::malloc_type;
+use crate::internal::intrinsics::compile_llvm_intrinsics;
 use crate::type_getter::CompilerTypeGetter;
-use inkwell::values::{BasicValueEnum, FunctionValue};
+use inkwell::intrinsics::Intrinsic;
+use inkwell::types::BasicType;
+use inkwell::values::{BasicValueEnum, FunctionValue, IntValue};
 use inkwell::{AddressSpace, IntPredicate};
 
 /// Compiles internal math functions
@@ -16,40 +19,37 @@ pub fn math_internal<'ctx>(
         let pointer_type = params.first().unwrap().into_pointer_value();
         let malloc = malloc_type(type_getter, pointer_type.get_type().const_zero(), &mut 0);
 
-        let returning = compiler
-            .builder
-            .build_int_add(
-                compiler.builder.build_load(pointer_type, "2").unwrap().into_int_value(),
-                compiler.builder.build_load(params.get(1).unwrap().into_pointer_value(), "3").unwrap().into_int_value(),
-                "1",
-            )
-            .unwrap();
+        let first = compiler.builder.build_load(pointer_type, "2").unwrap().into_int_value();
+        let second = compiler.builder.build_load(params.get(1).unwrap().into_pointer_value(), "3").unwrap().into_int_value();
+        let returning = if compiler.arguments.overflow_checks {
+            build_overflow_checked(compiler, type_getter, "add", is_unsigned(name), first, second)
+        } else {
+            compiler.builder.build_int_add(first, second, "1").unwrap()
+        };
         compiler.builder.build_store(malloc, returning).unwrap();
         compiler.builder.build_return(Some(&malloc)).unwrap();
     } else if name.starts_with("math::Subtract") {
         let pointer_type = params.first().unwrap().into_pointer_value();
         let malloc = malloc_type(type_getter, pointer_type.get_type().const_zero(), &mut 0);
-        let returning = compiler
-            .builder
-            .build_int_sub(
-                compiler.builder.build_load(params.first().unwrap().into_pointer_value(), "2").unwrap().into_int_value(),
-                compiler.builder.build_load(params.get(1).unwrap().into_pointer_value(), "3").unwrap().into_int_value(),
-                "1",
-            )
-            .unwrap();
+        let first = compiler.builder.build_load(pointer_type, "2").unwrap().into_int_value();
+        let second = compiler.builder.build_load(params.get(1).unwrap().into_pointer_value(), "3").unwrap().into_int_value();
+        let returning = if compiler.arguments.overflow_checks {
+            build_overflow_checked(compiler, type_getter, "sub", is_unsigned(name), first, second)
+        } else {
+            compiler.builder.build_int_sub(first, second, "1").unwrap()
+        };
         compiler.builder.build_store(malloc, returning).unwrap();
         compiler.builder.build_return(Some(&malloc)).unwrap();
     } else if name.starts_with("math::Multiply") {
         let pointer_type = params.first().unwrap().into_pointer_value();
         let malloc = malloc_type(type_getter, pointer_type.get_type().const_zero(), &mut 0);
-        let returning = compiler
-            .builder
-            .build_int_mul(
-                compiler.builder.build_load(params.first().unwrap().into_pointer_value(), "2").unwrap().into_int_value(),
-                compiler.builder.build_load(params.get(1).unwrap().into_pointer_value(), "3").unwrap().into_int_value(),
-                "1",
-            )
-            .unwrap();
+        let first = compiler.builder.build_load(pointer_type, "2").unwrap().into_int_value();
+        let second = compiler.builder.build_load(params.get(1).unwrap().into_pointer_value(), "3").unwrap().into_int_value();
+        let returning = if compiler.arguments.overflow_checks {
+            build_overflow_checked(compiler, type_getter, "mul", is_unsigned(name), first, second)
+        } else {
+            compiler.builder.build_int_mul(first, second, "1").unwrap()
+        };
         compiler.builder.build_store(malloc, returning).unwrap();
         compiler.builder.build_return(Some(&malloc)).unwrap();
     } else if name.starts_with("math::Divide") {
@@ -241,6 +241,22 @@ pub fn math_internal<'ctx>(
             .unwrap();
         compiler.builder.build_store(malloc, returning).unwrap();
         compiler.builder.build_return(Some(&malloc)).unwrap();
+    } else if name.split('$').next().unwrap() == "math::select" {
+        // Both values are loaded so LLVM can use a conditional move instead of branching
+        let first = params.get(1).unwrap().into_pointer_value();
+        let malloc = malloc_type(type_getter, first.get_type().const_zero(), &mut 0);
+        let condition = compiler.builder.build_load(params.first().unwrap().into_pointer_value(), "2").unwrap();
+        let returning = compiler
+            .builder
+            .build_select(
+                condition.into_int_value(),
+                compiler.builder.build_load(first, "3").unwrap(),
+                compiler.builder.build_load(params.get(2).unwrap().into_pointer_value(), "4").unwrap(),
+                "1",
+            )
+            .unwrap();
+        compiler.builder.build_store(malloc, returning).unwrap();
+        compiler.builder.build_return(Some(&malloc)).unwrap();
     } else {
         return false;
     }
@@ -272,8 +288,48 @@ fn compile_relational_op(
     compiler.builder.build_return(Some(&malloc)).unwrap();
 }
 
+/// Builds an add, sub, or mul that panics on overflow using LLVM's overflow intrinsics
+fn build_overflow_checked<'ctx>(
+    compiler: &CompilerImpl<'ctx>,
+    type_getter: &CompilerTypeGetter<'ctx>,
+    operation: &str,
+    unsigned: bool,
+    first: IntValue<'ctx>,
+    second: IntValue<'ctx>,
+) -> IntValue<'ctx> {
+    let intrinsic = format!("llvm.{}{}.with.overflow", if unsigned { "u" } else { "s" }, operation);
+    let intrinsic = Intrinsic::find(&intrinsic)
+        .unwrap()
+        .get_declaration(&compiler.module, &[first.get_type().as_basic_type_enum()])
+        .unwrap();
+    let result = compiler
+        .builder
+        .build_call(intrinsic, &[first.into(), second.into()], "4")
+        .unwrap()
+        .try_as_basic_value()
+        .unwrap_left()
+        .into_struct_value();
+    let overflowed = compiler.builder.build_extract_value(result, 1, "5").unwrap().into_int_value();
+
+    let function = compiler.builder.get_insert_block().unwrap().get_parent().unwrap();
+    let overflow_block = compiler.context.append_basic_block(function, "overflow");
+    let continue_block = compiler.context.append_basic_block(function, "continue");
+    compiler.builder.build_conditional_branch(overflowed, overflow_block, continue_block).unwrap();
+
+    compiler.builder.position_at_end(overflow_block);
+    let message = compiler.builder.build_global_string_ptr(&format!("Overflow in {}", operation), "6").unwrap();
+    compiler
+        .builder
+        .build_call(compile_llvm_intrinsics("panic", type_getter), &[message.as_pointer_value().into()], "7")
+        .unwrap();
+    compiler.builder.build_unreachable().unwrap();
+
+    compiler.builder.position_at_end(continue_block);
+    return compiler.builder.build_extract_value(result, 0, "8").unwrap().into_int_value();
+}
+
 /// Returns true if a number is unsigned
-fn is_unsigned(name: &String) -> bool {
+pub fn is_unsigned(name: &String) -> bool {
     if name.ends_with("u64") || name.ends_with("u32") || name.ends_with("u16") || name.ends_with("u8") {
         return true;
     }
@@ -1,5 +1,11 @@
 /// Handles operations with the internal keyword and #[llvm_intrinsics]
 
+/// Internal atomic operations
+pub mod atomic_internal;
+/// Internal overflowing, wrapping, and saturating math
+pub mod checked_internal;
+/// Internal hashing functions
+pub mod hash_internal;
 /// Internal instructions
 pub mod instructions;
 /// Allows access to intrinsic C functions
@@ -10,3 +16,5 @@ pub mod math_internal;
 pub mod string_internal;
 /// Internal structs
 pub mod structs;
+/// Internal thread-local storage
+pub mod thread_local_internal;
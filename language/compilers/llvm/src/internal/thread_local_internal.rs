@@ -0,0 +1,107 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::compiler::CompilerImpl;
+use crate::internal::instructions::malloc_type;
+use crate::type_getter::CompilerTypeGetter;
+use inkwell::module::Linkage;
+use inkwell::values::{FunctionValue, GlobalValue, PointerValue};
+use inkwell::AddressSpace;
+
+/// How many thread locals a program can make, which is the size of each thread's storage
+pub const THREAD_LOCAL_CAPACITY: u64 = 256;
+
+/// The name of the thread-local global holding every thread local's value on the current thread
+static STORAGE: &str = "raven.thread_locals";
+
+/// The next key handed out by raven_thread_local_key. Keys start at 1, so 0 can mean the storage is full
+static NEXT_KEY: AtomicU64 = AtomicU64::new(1);
+
+/// Called by std's thread_local module to get the key of a new thread local, which is its index in the storage,
+/// returning 0 if every key is taken
+pub extern "C" fn raven_thread_local_key() -> u64 {
+    let key = NEXT_KEY.fetch_add(1, Ordering::Relaxed);
+    return if key > THREAD_LOCAL_CAPACITY { 0 } else { key };
+}
+
+/// Compiles the internal methods of std's ThreadLocal, which read and write a thread-local global.
+/// Each thread has its own copy of the global, holding a pointer to each thread local's value or null if it isn't set.
+/// Values are copied in and out, so changing the value returned by get doesn't change the thread local.
+pub fn thread_local_internal<'ctx>(
+    type_getter: &CompilerTypeGetter<'ctx>,
+    compiler: &CompilerImpl<'ctx>,
+    name: &String,
+    value: &FunctionValue<'ctx>,
+) -> bool {
+    if !name.starts_with("thread_local::") {
+        return false;
+    }
+    // Names look like thread_local::ThreadLocal<T>_T::load$u64, and each method takes self, the key, then the value
+    let method = name.split('$').next().unwrap().rsplit("::").next().unwrap();
+    if !matches!(method, "slot_is_set" | "load" | "store" | "remove") {
+        return false;
+    }
+    let params = value.get_params();
+    let key = compiler.builder.build_load(params.get(1).unwrap().into_pointer_value(), "1").unwrap().into_int_value();
+    let slot = unsafe {
+        compiler
+            .builder
+            .build_in_bounds_gep(storage(compiler).as_pointer_value(), &[compiler.context.i64_type().const_zero(), key], "2")
+            .unwrap()
+    };
+    let stored = compiler.builder.build_load(slot, "3").unwrap().into_pointer_value();
+
+    match method {
+        "slot_is_set" => {
+            let bool_type = compiler.context.bool_type();
+            let malloc = malloc_type(type_getter, bool_type.ptr_type(AddressSpace::default()).const_zero(), &mut 4);
+            compiler.builder.build_store(malloc, compiler.builder.build_is_not_null(stored, "5").unwrap()).unwrap();
+            compiler.builder.build_return(Some(&malloc)).unwrap();
+        }
+        "load" => {
+            let returning = value.get_type().get_return_type().unwrap().into_pointer_type();
+            let stored = compiler.builder.build_pointer_cast(stored, returning, "4").unwrap();
+            let malloc = copy(type_getter, compiler, stored, 5);
+            compiler.builder.build_return(Some(&malloc)).unwrap();
+        }
+        "store" => {
+            let malloc = copy(type_getter, compiler, params.get(2).unwrap().into_pointer_value(), 4);
+            let malloc = compiler.builder.build_pointer_cast(malloc, stored.get_type(), "7").unwrap();
+            compiler.builder.build_store(slot, malloc).unwrap();
+            compiler.builder.build_return(None).unwrap();
+        }
+        "remove" => {
+            compiler.builder.build_store(slot, stored.get_type().const_null()).unwrap();
+            compiler.builder.build_return(None).unwrap();
+        }
+        _ => unreachable!(),
+    }
+    return true;
+}
+
+/// Copies the value behind the pointer to the heap, returning the copy
+fn copy<'ctx>(
+    type_getter: &CompilerTypeGetter<'ctx>,
+    compiler: &CompilerImpl<'ctx>,
+    pointer: PointerValue<'ctx>,
+    mut id: u64,
+) -> PointerValue<'ctx> {
+    let malloc = malloc_type(type_getter, pointer.get_type().const_zero(), &mut id);
+    let loaded = compiler.builder.build_load(pointer, &id.to_string()).unwrap();
+    compiler.builder.build_store(malloc, loaded).unwrap();
+    return malloc;
+}
+
+/// Gets the thread-local storage, adding it to the module if it isn't there yet.
+/// Slot 0 is never used, since keys start at 1.
+fn storage<'ctx>(compiler: &CompilerImpl<'ctx>) -> GlobalValue<'ctx> {
+    if let Some(found) = compiler.module.get_global(STORAGE) {
+        return found;
+    }
+    let storage_type =
+        compiler.context.i8_type().ptr_type(AddressSpace::default()).array_type(THREAD_LOCAL_CAPACITY as u32 + 1);
+    let global = compiler.module.add_global(storage_type, None, STORAGE);
+    global.set_linkage(Linkage::Internal);
+    global.set_initializer(&storage_type.const_zero());
+    global.set_thread_local(true);
+    return global;
+}
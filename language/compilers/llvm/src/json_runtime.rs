@@ -0,0 +1,160 @@
+use std::ffi::{c_char, CStr, CString};
+
+/// Reads a string passed from Raven
+fn read(text: *const c_char) -> String {
+    return unsafe { CStr::from_ptr(text) }.to_string_lossy().to_string();
+}
+
+/// Passes a string back to Raven. Raven strings are never freed, so the string is leaked.
+fn write(text: String) -> *const c_char {
+    return CString::new(text).unwrap_or_default().into_raw();
+}
+
+/// Called by std's serialize module to escape text for a JSON string
+pub extern "C" fn raven_json_escape(text: *const c_char) -> *const c_char {
+    let mut output = String::default();
+    for character in read(text).chars() {
+        match character {
+            '"' => output += "\\\"",
+            '\\' => output += "\\\\",
+            '\n' => output += "\\n",
+            '\r' => output += "\\r",
+            '\t' => output += "\\t",
+            character if (character as u32) < 0x20 => output += &format!("\\u{:04x}", character as u32),
+            character => output.push(character),
+        }
+    }
+    return write(output);
+}
+
+/// Called by std's serialize module to find the JSON text of a field's value in an object,
+/// returning an empty string if the text isn't an object or doesn't have the field.
+pub extern "C" fn raven_json_field(object: *const c_char, name: *const c_char) -> *const c_char {
+    let object = read(object);
+    let name = read(name);
+    let bytes = object.trim().as_bytes();
+    if bytes.first() != Some(&b'{') {
+        return write(String::default());
+    }
+
+    let mut index = 1;
+    loop {
+        index = skip_whitespace(bytes, index);
+        if bytes.get(index) != Some(&b'"') {
+            return write(String::default());
+        }
+        let key_end = value_end(bytes, index);
+        let key = unescape(&bytes[index..key_end]);
+        index = skip_whitespace(bytes, key_end);
+        if bytes.get(index) != Some(&b':') {
+            return write(String::default());
+        }
+        let value_start = skip_whitespace(bytes, index + 1);
+        let end = value_end(bytes, value_start);
+        if key == name {
+            return write(String::from_utf8_lossy(&bytes[value_start..end]).to_string());
+        }
+        index = skip_whitespace(bytes, end);
+        if bytes.get(index) != Some(&b',') {
+            return write(String::default());
+        }
+        index += 1;
+    }
+}
+
+/// Called by std's serialize module to read a JSON number, returning 0 if the text isn't one
+pub extern "C" fn raven_json_u64(text: *const c_char) -> u64 {
+    return read(text).trim().parse().unwrap_or(0);
+}
+
+/// Called by std's serialize module to read a JSON string, returning an empty string if the text isn't one
+pub extern "C" fn raven_json_string(text: *const c_char) -> *const c_char {
+    let text = read(text);
+    let bytes = text.trim().as_bytes();
+    if bytes.first() != Some(&b'"') {
+        return write(String::default());
+    }
+    return write(unescape(bytes));
+}
+
+/// Skips whitespace, returning the index of the next character
+fn skip_whitespace(bytes: &[u8], mut index: usize) -> usize {
+    while index < bytes.len() && bytes[index].is_ascii_whitespace() {
+        index += 1;
+    }
+    return index;
+}
+
+/// Finds the index after the end of the JSON value starting at the index
+fn value_end(bytes: &[u8], mut index: usize) -> usize {
+    match bytes.get(index) {
+        Some(b'"') => {
+            index += 1;
+            while index < bytes.len() && bytes[index] != b'"' {
+                if bytes[index] == b'\\' {
+                    index += 1;
+                }
+                index += 1;
+            }
+            return (index + 1).min(bytes.len());
+        }
+        Some(b'{') | Some(b'[') => {
+            // Nested objects and arrays end when their brackets balance, skipping any strings inside them
+            let mut depth = 0;
+            while index < bytes.len() {
+                match bytes[index] {
+                    b'"' => {
+                        index = value_end(bytes, index);
+                        continue;
+                    }
+                    b'{' | b'[' => depth += 1,
+                    b'}' | b']' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            return index + 1;
+                        }
+                    }
+                    _ => {}
+                }
+                index += 1;
+            }
+            return index;
+        }
+        _ => {
+            // Numbers, booleans, and null end at the next separator
+            while index < bytes.len() && !matches!(bytes[index], b',' | b'}' | b']') && !bytes[index].is_ascii_whitespace() {
+                index += 1;
+            }
+            return index;
+        }
+    }
+}
+
+/// Reads a quoted JSON string, replacing its escapes with the characters they stand for
+fn unescape(quoted: &[u8]) -> String {
+    let text = String::from_utf8_lossy(quoted);
+    let inner = text.strip_prefix('"').unwrap_or(&text);
+    let inner = inner.strip_suffix('"').unwrap_or(inner);
+    let mut output = String::default();
+    let mut characters = inner.chars();
+    while let Some(character) = characters.next() {
+        if character != '\\' {
+            output.push(character);
+            continue;
+        }
+        match characters.next() {
+            Some('n') => output.push('\n'),
+            Some('r') => output.push('\r'),
+            Some('t') => output.push('\t'),
+            Some('b') => output.push('\u{8}'),
+            Some('f') => output.push('\u{c}'),
+            Some('u') => {
+                let code = characters.by_ref().take(4).collect::<String>();
+                output.push(u32::from_str_radix(&code, 16).ok().and_then(char::from_u32).unwrap_or('\u{fffd}'));
+            }
+            Some(other) => output.push(other),
+            None => {}
+        }
+    }
+    return output;
+}
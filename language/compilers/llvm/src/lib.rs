@@ -17,14 +17,38 @@ use syntax::program::syntax::{Compiler, Syntax};
 use crate::compiler::CompilerImpl;
 use crate::type_getter::CompilerTypeGetter;
 
+/// The allocator compiled programs get their heap memory from, which embedders can replace
+pub mod allocator;
+/// Runtime functions behind std's arena module, which frees many allocations at once
+pub mod arena_runtime;
+/// Runtime functions behind std's bytes and net modules, like byte buffers, files, and TCP streams
+pub mod bytes_runtime;
 /// The compiler that compiles a syntax
 pub mod compiler;
+/// Counts how many times each line of code runs for test coverage reports
+pub mod coverage;
+/// Emits DWARF debug info mapping the compiled code to Raven functions and lines
+pub mod debug_info;
+/// Runtime functions behind core's float formatting and parsing
+pub mod float_runtime;
 /// Compiles a function to LLVM
 pub mod function_compiler;
+/// Tracks heap allocations per allocation site for heap profiling and leak detection
+pub mod heap_profiler;
+/// Lets Rust closures be called from Raven as extern functions
+pub mod host_functions;
+/// Splices the raw LLVM IR and assembly of llvm! and asm! into functions
+pub mod inline_code;
 /// Implementations of internal types
 pub mod internal;
+/// Runtime functions behind the JSON backend of std's serialize module
+pub mod json_runtime;
 /// A future that waits on main to finish verifying
 pub mod main_future;
+/// Runtime functions behind std's os module, like reading environment variables and spawning processes
+pub mod os_runtime;
+/// Calls the installed panic hook when Raven code panics
+pub mod panic_hook;
 /// Handles translating Raven types into LLVM
 pub mod type_getter;
 /// Utility functions used in other files
@@ -63,8 +87,19 @@ impl<T> Compiler<T> for LLVMCompiler {
     async fn compile(&self, mut receiver: Receiver<()>, syntax: &Arc<Mutex<Syntax>>) -> Option<T> {
         if let Some(main) = CompilerImpl::get_main(&self.arguments, syntax).await {
             if receiver.recv().await.is_some() {
-                let mut binding = CompilerTypeGetter::new(Rc::new(CompilerImpl::new(&self.context)), syntax.clone());
-                CompilerImpl::compile(main, &mut binding, &self.compiling, &self.struct_compiling);
+                heap_profiler::reset();
+                coverage::reset();
+                host_functions::reset();
+                let locator = syntax.lock().source_locator.clone();
+                let mut binding = CompilerTypeGetter::new(
+                    Rc::new(CompilerImpl::new(&self.context, &self.arguments, locator)),
+                    syntax.clone(),
+                );
+                CompilerImpl::compile(main, &mut binding, &self.arguments, &self.compiling, &self.struct_compiling);
+                // Calling a host function with the wrong values is undefined behavior, so the program isn't run
+                if !host_functions::signature_errors().is_empty() {
+                    return None;
+                }
                 return binding.get_target(&self.arguments.target).map(|inner| unsafe { inner.call() });
             }
         } else {
@@ -0,0 +1,24 @@
+use std::env;
+use std::ffi::{c_char, CStr, CString};
+use std::process::Command;
+
+/// Called by the compiled program through std's os module to read an environment variable.
+/// Returns an empty string if the variable isn't set. Raven strings are never freed, so the result is leaked.
+pub extern "C" fn raven_env_var(name: *const c_char) -> *const c_char {
+    let name = unsafe { CStr::from_ptr(name) }.to_string_lossy();
+    let value = env::var(name.as_ref()).unwrap_or_default();
+    return CString::new(value).unwrap_or_default().into_raw();
+}
+
+/// Called by the compiled program through std's os module to run a command with the platform's shell,
+/// like "sh -c command", waiting for it to finish.
+/// Returns the exit code, or u64::MAX if it couldn't be started or was killed by a signal.
+/// Windows exit codes are unsigned, so negative exit codes from unix are kept as their lowest 32 bits.
+pub extern "C" fn raven_spawn_process(program: *const c_char, flag: *const c_char, command: *const c_char) -> u64 {
+    let [program, flag, command] =
+        [program, flag, command].map(|argument| unsafe { CStr::from_ptr(argument) }.to_string_lossy().to_string());
+    return match Command::new(program).arg(flag).arg(command).status() {
+        Ok(status) => status.code().map(|code| code as u32 as u64).unwrap_or(u64::MAX),
+        Err(_) => u64::MAX,
+    };
+}
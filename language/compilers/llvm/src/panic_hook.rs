@@ -0,0 +1,32 @@
+use parking_lot::Mutex;
+use std::ffi::{c_char, CStr};
+use std::process;
+
+/// A hook called with the panic message before a Raven program aborts
+pub type PanicHook = Box<dyn Fn(&str) + Send + Sync>;
+
+/// The installed panic hook, if there is one
+static HOOK: Mutex<Option<PanicHook>> = Mutex::new(None);
+
+/// Installs a hook that is called with the message whenever Raven code panics, replacing any previous hook.
+/// The program is still aborted after the hook returns, because Raven code can't be unwound,
+/// so the hook should report the panic (for example as a failed test) instead of recovering from it.
+pub fn set_panic_hook(hook: PanicHook) {
+    *HOOK.lock() = Some(hook);
+}
+
+/// Removes the installed panic hook, returning it
+pub fn take_panic_hook() -> Option<PanicHook> {
+    return HOOK.lock().take();
+}
+
+/// Called by the compiled program through core's panic function
+pub extern "C" fn raven_panic(message: *const c_char) {
+    let message = unsafe { CStr::from_ptr(message) }.to_string_lossy();
+    // A hook that panics itself would deadlock on the lock, so that falls back to printing the message
+    match HOOK.try_lock() {
+        Some(hook) if hook.is_some() => hook.as_ref().unwrap()(&message),
+        _ => eprintln!("Raven panicked: {}", message),
+    }
+    process::abort();
+}
@@ -8,12 +8,14 @@ use std::sync::Arc;
 use crate::compiler::CompilerImpl;
 use crate::function_compiler::{instance_function, instance_types};
 use crate::internal::structs::get_internal_struct;
+use crate::util::symbol_name;
 use crate::vtable_manager::VTableManager;
 use inkwell::basic_block::BasicBlock;
 use inkwell::execution_engine::JitFunction;
 use inkwell::types::{BasicType, BasicTypeEnum};
 use inkwell::values::{BasicValueEnum, FunctionValue};
 use inkwell::AddressSpace;
+use syntax::mangling::mangle;
 use syntax::program::function::{CodelessFinalizedFunction, FinalizedFunction};
 use syntax::program::syntax::{Main, Syntax};
 use syntax::program::types::FinalizedTypes;
@@ -72,7 +74,7 @@ impl<'ctx> CompilerTypeGetter<'ctx> {
 
     /// Gets the LLVM version of the function
     pub fn get_function(&mut self, function: &Arc<CodelessFinalizedFunction>) -> FunctionValue<'ctx> {
-        match self.compiler.module.get_function(&function.data.name) {
+        match self.compiler.module.get_function(&symbol_name(function)) {
             Some(found) => found,
             None => {
                 return instance_function(function.clone(), self);
@@ -97,7 +99,7 @@ impl<'ctx> CompilerTypeGetter<'ctx> {
     /// Gets the target function that can be called directly from Rust
     pub(crate) fn get_target<T>(&self, target: &str) -> Option<JitFunction<'_, Main<T>>> {
         return unsafe {
-            match self.compiler.execution_engine.get_function(target) {
+            match self.compiler.execution_engine.get_function(&mangle(target)) {
                 Ok(value) => Some(value),
                 Err(_) => None,
             }
@@ -4,8 +4,10 @@ use inkwell::types::BasicType;
 use inkwell::values::FunctionValue;
 use std::ops::Deref;
 use std::sync::Arc;
+use syntax::mangling::mangle;
 use syntax::program::function::CodelessFinalizedFunction;
 use syntax::program::types::FinalizedTypes;
+use syntax::{is_modifier, Modifier};
 
 /// Prints an unformatted string (like the one returned by LLVM's to_string method
 pub fn print_formatted(input: String) {
@@ -62,5 +64,14 @@ pub fn create_function_value<'ctx>(
         None => type_getter.compiler.context.void_type().fn_type(params.as_slice(), false),
     };
 
-    return type_getter.compiler.module.add_function(&function.data.name, llvm_function, linkage);
+    return type_getter.compiler.module.add_function(&symbol_name(function), llvm_function, linkage);
+}
+
+/// Gets the name of the function in the compiled code, which is mangled unless it's linked externally
+pub fn symbol_name(function: &CodelessFinalizedFunction) -> String {
+    return if is_modifier(function.data.modifiers, Modifier::Extern) {
+        function.data.name.clone()
+    } else {
+        mangle(&function.data.name)
+    };
 }
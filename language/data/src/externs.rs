@@ -30,6 +30,15 @@ impl RavenExtern for bool {
     }
 }
 
+impl RavenExtern for u64 {
+    type Input = ();
+
+    // Numbers are stored in structs by value instead of by pointer, so the "pointer" is the number itself
+    unsafe fn translate(raven_type: *mut ()) -> Self {
+        return raven_type as u64;
+    }
+}
+
 impl<T: RavenExtern> RavenExtern for Vec<T> {
     type Input = ();
 
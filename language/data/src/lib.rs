@@ -12,6 +12,10 @@ pub type Main<T> = unsafe extern "C" fn() -> T;
 
 /// Handles the externals for translating Raven types to Rust types
 pub mod externs;
+/// Reports the progress of each build phase
+pub mod progress;
+/// Counts for the --stats report
+pub mod stats;
 /// Tokens
 pub mod tokens;
 
@@ -32,6 +36,43 @@ pub struct CompilerArguments {
     pub target: String,
     /// The temp folder to use while compiling
     pub temp_folder: PathBuf,
+    /// Extra debug output to print after checking, like "checked-ast"
+    pub emit: Vec<String>,
+    /// Whether to run LLVM's link-time optimization passes over the whole program, inlining the std into user code
+    pub lto: bool,
+    /// Whether to count heap allocations per allocation site and print a report after running
+    pub heap_profile: bool,
+    /// Whether to fail if any heap allocations are never freed by the time the program exits
+    pub leak_check: bool,
+    /// Whether to count how many times each line runs, for the coverage report written after running tests
+    pub coverage: bool,
+    /// The LLVM optimization level, from 0 (none) to 3 (aggressive)
+    pub opt_level: u8,
+    /// Whether to optimize for binary size over speed, like -Oz
+    pub opt_size: bool,
+    /// Whether to verify the compiled LLVM module, reporting LLVM's errors instead of crashing on invalid code
+    pub verify_ir: bool,
+    /// Whether to emit DWARF debug info, so debuggers and profilers can map the compiled code to Raven functions and lines
+    pub debug_info: bool,
+    /// Whether integer addition, subtraction, and multiplication panic on overflow instead of wrapping
+    pub overflow_checks: bool,
+    /// The enabled features, checked by #[cfg(feature = "name")] attributes
+    pub features: Vec<String>,
+    /// Whether to hide build progress, for CI and other non-interactive use
+    pub quiet: bool,
+    /// Whether to print counts of what was parsed, instantiated, and compiled after building
+    pub stats: bool,
+    /// Whether to print every instantiation of each generic function and where it's called from after checking
+    pub dump_instantiations: bool,
+    /// Whether to record which function each call resolves to, for the language server's references and renaming
+    pub record_call_sites: bool,
+    /// How deeply expressions and generic types can nest before the checker gives up with an error, or 0 for the default
+    pub recursion_limit: u32,
+    /// The edition of the language to compile the project with, or 0 for the edition set by the project's build file
+    pub edition: u32,
+    /// Whether to compile without the std, for targets without an operating system.
+    /// The program provides its own allocator and panic handler, and can't reach functions that need an operating system
+    pub no_std: bool,
 }
 
 /// Arguments for running Raven
@@ -99,6 +140,11 @@ pub trait SourceSet: Debug + Send + Sync {
 
     /// Clones the source set and boxes it
     fn cloned(&self) -> Box<dyn SourceSet>;
+
+    /// The edition the sources were written for, or 0 for the project's own sources, which use the project's edition
+    fn edition(&self) -> u32 {
+        return 0;
+    }
 }
 
 /// A small type for translating external Raven types into Rust types
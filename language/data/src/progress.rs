@@ -0,0 +1,79 @@
+use std::io::{stdout, IsTerminal, Write};
+use std::time::Instant;
+
+use colored::Colorize;
+
+use crate::CompilerArguments;
+
+/// Reports the progress of a build phase, like parsing or codegen, printing the number of items and the elapsed time
+/// when it finishes. In a terminal the line is updated in place as items finish.
+/// Nothing is printed in quiet mode.
+pub struct Progress {
+    /// The phase's name, like "Parsing"
+    phase: &'static str,
+    /// What the items are, like "files", or empty if the phase isn't split into items
+    unit: &'static str,
+    /// The total number of items, if it's known ahead of time
+    total: Option<usize>,
+    /// The number of finished items
+    done: usize,
+    /// When the phase started
+    start: Instant,
+    /// Whether to print anything
+    enabled: bool,
+    /// Whether the output is a terminal, so the line can be updated in place
+    interactive: bool,
+}
+
+impl Progress {
+    /// Starts a phase, printing it immediately
+    pub fn new(phase: &'static str, unit: &'static str, total: Option<usize>, arguments: &CompilerArguments) -> Self {
+        let progress = Self {
+            phase,
+            unit,
+            total,
+            done: 0,
+            start: Instant::now(),
+            enabled: !arguments.quiet,
+            interactive: stdout().is_terminal(),
+        };
+        progress.print_current();
+        return progress;
+    }
+
+    /// Marks an item as finished
+    pub fn advance(&mut self) {
+        self.done += 1;
+        self.print_current();
+    }
+
+    /// Finishes the phase, printing the number of items and the elapsed time
+    pub fn finish(self) {
+        if !self.enabled {
+            return;
+        }
+        let items = if self.unit.is_empty() { String::default() } else { format!(" {} {}", self.done, self.unit) };
+        let elapsed = format!("({:.2}s)", self.start.elapsed().as_secs_f64());
+        let line = format!("{:>12}{} {}", self.phase.bright_green().bold(), items, elapsed.dimmed());
+        if self.interactive {
+            // Clears the in-progress line before replacing it
+            println!("\r\x1b[2K{}", line);
+        } else {
+            println!("{}", line);
+        }
+    }
+
+    /// Prints the in-progress line, which is only done in a terminal since it's replaced when the phase finishes
+    fn print_current(&self) {
+        if !self.enabled || !self.interactive {
+            return;
+        }
+        let items = match self.total {
+            _ if self.unit.is_empty() => String::default(),
+            Some(total) => format!(" {}/{} {}", self.done, total, self.unit),
+            None => format!(" {} {}", self.done, self.unit),
+        };
+        print!("\r\x1b[2K{:>12}{}", self.phase.bright_cyan().bold(), items);
+        let _ = stdout().flush();
+    }
+}
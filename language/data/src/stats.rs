@@ -0,0 +1,95 @@
+use std::fs;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// A single count in the --stats report. Counting is a single atomic add, so it's always done.
+pub struct Stat {
+    /// The name printed in the report
+    name: &'static str,
+    /// The current count
+    count: AtomicU64,
+}
+
+impl Stat {
+    /// Creates a stat starting at zero
+    const fn new(name: &'static str) -> Self {
+        return Self { name, count: AtomicU64::new(0) };
+    }
+
+    /// Adds one to the stat
+    pub fn add(&self) {
+        self.count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Gets the current count
+    pub fn get(&self) -> u64 {
+        return self.count.load(Ordering::Relaxed);
+    }
+}
+
+/// Functions parsed from source
+pub static PARSED_FUNCTIONS: Stat = Stat::new("Parsed functions");
+/// Structs and traits parsed from source
+pub static PARSED_STRUCTS: Stat = Stat::new("Parsed structs and traits");
+/// Trait implementations parsed from source
+pub static PARSED_IMPLS: Stat = Stat::new("Parsed implementations");
+/// Copies of generic functions made by degeneric with their generics solidified
+pub static FUNCTION_INSTANTIATIONS: Stat = Stat::new("Generic function instantiations");
+/// Copies of generic structs made by degeneric with their generics solidified
+pub static STRUCT_INSTANTIATIONS: Stat = Stat::new("Generic struct instantiations");
+/// Calls compiled to a direct call of a known function
+pub static STATIC_CALLS: Stat = Stat::new("Static calls");
+/// Calls compiled to a lookup in a trait's vtable
+pub static VIRTUAL_CALLS: Stat = Stat::new("Virtual calls");
+/// Heap allocations the compiler emitted, counted once per place in the compiled code and not per run
+pub static HEAP_ALLOCATIONS: Stat = Stat::new("Heap allocation sites");
+
+/// Every stat, in the order they're reported
+static STATS: [&Stat; 8] = [
+    &PARSED_FUNCTIONS,
+    &PARSED_STRUCTS,
+    &PARSED_IMPLS,
+    &FUNCTION_INSTANTIATIONS,
+    &STRUCT_INSTANTIATIONS,
+    &STATIC_CALLS,
+    &VIRTUAL_CALLS,
+    &HEAP_ALLOCATIONS,
+];
+
+/// The peak memory of the process once checking finished, in kilobytes, or 0 if it couldn't be read
+static CHECKER_PEAK_MEMORY: AtomicU64 = AtomicU64::new(0);
+
+/// Resets every stat, called before building a new program
+pub fn reset() {
+    for stat in STATS {
+        stat.count.store(0, Ordering::Relaxed);
+    }
+    CHECKER_PEAK_MEMORY.store(0, Ordering::Relaxed);
+}
+
+/// Records the peak memory once the checker finishes
+pub fn record_checker_memory() {
+    CHECKER_PEAK_MEMORY.store(peak_memory().unwrap_or(0), Ordering::Relaxed);
+}
+
+/// Gets the peak resident memory of the process in kilobytes.
+/// This is only available on Linux, where it's read from /proc/self/status.
+fn peak_memory() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    return line.trim_start_matches("VmHWM:").trim().trim_end_matches("kB").trim().parse().ok();
+}
+
+/// Makes the --stats report of every stat
+pub fn report() -> String {
+    let mut output = String::from("Compilation stats:\n");
+    for stat in STATS {
+        output += &format!("{:>32}: {}\n", stat.name, stat.get());
+    }
+    let memory = CHECKER_PEAK_MEMORY.load(Ordering::Relaxed);
+    if memory == 0 {
+        output += &format!("{:>32}: unknown\n", "Checker peak memory");
+    } else {
+        output += &format!("{:>32}: {} KB\n", "Checker peak memory", memory);
+    }
+    return output;
+}
@@ -39,6 +39,22 @@ impl Token {
         }
         return String::from_utf8_lossy(&buffer[start..=end]).to_string();
     }
+
+    /// Gets the start and end columns of the token in characters instead of bytes, so multi-byte characters
+    /// before or in the token only count once in diagnostics.
+    pub fn character_columns(&self, buffer: &[u8]) -> (u32, u32) {
+        return (
+            character_column(buffer, self.start_offset, self.start.1),
+            character_column(buffer, self.end_offset, self.end.1),
+        );
+    }
+}
+
+/// Converts a byte column at the offset into a character column by counting the characters since the line's start
+fn character_column(buffer: &[u8], offset: usize, column: u32) -> u32 {
+    let offset = offset.min(buffer.len());
+    let line_start = offset.saturating_sub(column as usize);
+    return String::from_utf8_lossy(&buffer[line_start..offset]).chars().count() as u32;
 }
 
 /// This allows for Tokens to be used in the Result type.
@@ -78,12 +94,20 @@ pub struct Span {
     pub start: usize,
     /// The end index in the list of tokens
     pub end: usize,
+    /// What the user wrote that the compiler turned into this code, like "for loop", if the code was desugared
+    pub expanded_from: Option<&'static str>,
 }
 
 impl Span {
     /// Creates a new span
     pub fn new(file: u64, index: usize) -> Self {
-        return Self { file, start: index, end: index };
+        return Self { file, start: index, end: index, expanded_from: None };
+    }
+
+    /// Copies the span for code desugared from the construct at this span.
+    /// Desugared code inside other desugared code keeps the outer construct, since that's what the user wrote.
+    pub fn expanded(&self, from: &'static str) -> Self {
+        return Self { expanded_from: self.expanded_from.or(Some(from)), ..self.clone() };
     }
 
     /// Extends the span to encompass more tokens
@@ -259,5 +283,9 @@ pub enum TokenTypes {
     /// A blank line
     BlankLine = 71,
                 // Added Integer Types take 72 - 78
+    /// The defer keyword
+    Defer = 79,
+    /// The continue keyword
+    Continue = 80,
     
 }
@@ -0,0 +1,170 @@
+use data::tokens::{Token, TokenTypes};
+use data::SourceSet;
+use syntax::operation_util::operation_token;
+use syntax::MODIFIERS;
+
+use crate::parser::code_parser::{CHARACTER_ESCAPES, HEX_ESCAPES};
+use crate::tokens::code_tokenizer::CODE_KEYWORDS;
+use crate::tokens::top_tokenizer::TOP_KEYWORDS;
+
+/// An operator registered by a trait's #[operation] attribute
+#[derive(Clone, Debug)]
+pub struct GrammarOperator {
+    /// The name of the trait
+    pub name: String,
+    /// The operation, with {} for each value like "{}+{}"
+    pub operation: String,
+    /// The priority attribute, or 0
+    pub priority: i64,
+    /// The parse_left attribute, or false
+    pub parse_left: bool,
+}
+
+/// Exports the grammar of the language as JSON, so editor grammars can be generated from the parser.
+/// Operators are defined in Raven code, so they're read from the given sources.
+pub fn export_grammar(sources: &Vec<Box<dyn SourceSet>>) -> String {
+    let mut keywords = TOP_KEYWORDS.iter().map(|keyword| keyword.to_string()).collect::<Vec<_>>();
+    keywords.extend(CODE_KEYWORDS.iter().map(|(keyword, _)| keyword.to_string()));
+    let modifiers = MODIFIERS.iter().map(|modifier| modifier.to_string()).collect::<Vec<_>>();
+
+    let operators = find_operators(sources);
+
+    let mut output = String::from("{\n");
+    output += &format!("  \"keywords\": {},\n", json_list(&keywords));
+    output += &format!("  \"modifiers\": {},\n", json_list(&modifiers));
+    output += "  \"operators\": [\n";
+    for (i, operator) in operators.iter().enumerate() {
+        output += &format!(
+            "    {{\"trait\": {}, \"operation\": {}, \"priority\": {}, \"parse_left\": {}}}{}\n",
+            json_string(&operator.name),
+            json_string(&operator.operation),
+            operator.priority,
+            operator.parse_left,
+            if i + 1 == operators.len() { "" } else { "," }
+        );
+    }
+    output += "  ],\n";
+    output += "  \"literals\": {\n";
+    output += &format!("    \"integer\": {},\n", json_string("[0-9]+"));
+    output += &format!("    \"float\": {},\n", json_string("[0-9]+\\.[0-9]+"));
+    output += &format!("    \"bool\": {},\n", json_list(&vec!["true".to_string(), "false".to_string()]));
+    output += &format!("    \"char\": {},\n", json_string("'.'"));
+    output += &format!("    \"string\": {},\n", json_string("\"([^\"\\\\]|\\\\.)*\""));
+    // Values in braces are interpolated, and doubled braces are literal ones
+    output += &format!("    \"format_string\": {},\n", json_string("f\"([^\"\\\\]|\\\\.)*\""));
+    let mut escapes = CHARACTER_ESCAPES.iter().map(|(name, _)| format!("\\{}", name)).collect::<Vec<_>>();
+    escapes.extend(HEX_ESCAPES.iter().map(|escape| escape.to_string()));
+    output += &format!("    \"escapes\": {}\n", json_list(&escapes));
+    output += "  },\n";
+    let block = vec!["/*".to_string(), "*/".to_string()];
+    output += &format!("  \"comments\": {{\"line\": {}, \"block\": {}}}\n", json_string("//"), json_list(&block));
+    output += "}";
+    return output;
+}
+
+/// Finds every operator in the sources, sorted from the highest priority to the lowest
+pub fn find_operators(sources: &Vec<Box<dyn SourceSet>>) -> Vec<GrammarOperator> {
+    let mut operators = Vec::default();
+    for source in sources {
+        for file in source.get_files() {
+            if !file.path().ends_with("rv") {
+                continue;
+            }
+            let contents = file.contents();
+            for (name, attributes) in trait_attributes(contents.as_bytes(), &file.read()) {
+                operators.extend(to_operator(name, &attributes));
+            }
+        }
+    }
+    operators.sort_by(|first, second| second.priority.cmp(&first.priority).then(first.operation.cmp(&second.operation)));
+    return operators;
+}
+
+/// Prints the precedence table of the operators in the sources, followed by any operators the checker can't order.
+/// Operators with a higher priority are applied first, and equal priorities are grouped by their associativity.
+pub fn operator_table(sources: &Vec<Box<dyn SourceSet>>) -> String {
+    let operators = find_operators(sources);
+    let width = operators.iter().map(|operator| operator.operation.len()).max().unwrap_or(0).max("Operation".len());
+
+    let mut output = format!("{:>8}  {:<width$}  {:<13}  Trait\n", "Priority", "Operation", "Associativity");
+    for operator in &operators {
+        // parse_left operators group to the right, like a = (b = c), everything else groups to the left
+        let associativity = if operator.parse_left { "right" } else { "left" };
+        output +=
+            &format!("{:>8}  {:<width$}  {:<13}  {}\n", operator.priority, operator.operation, associativity, operator.name);
+    }
+
+    for (i, operator) in operators.iter().enumerate() {
+        for other in &operators[i + 1..] {
+            if operator.priority == other.priority
+                && operator.parse_left != other.parse_left
+                && operation_token(&operator.operation) == operation_token(&other.operation)
+            {
+                output += &format!(
+                    "Conflict: {} ({}) and {} ({}) share a token and priority but not parse_left\n",
+                    operator.operation, operator.name, other.operation, other.name
+                );
+            }
+        }
+    }
+    return output;
+}
+
+/// Finds the name and attributes of every trait in a file
+fn trait_attributes(buffer: &[u8], tokens: &Vec<Token>) -> Vec<(String, Vec<String>)> {
+    let mut output = Vec::default();
+    let mut attributes = Vec::default();
+    let mut last = &TokenTypes::Start;
+    for token in tokens {
+        match token.token_type {
+            TokenTypes::Attribute => attributes.push(token.to_string(buffer)),
+            TokenTypes::Identifier if *last == TokenTypes::TraitStart => {
+                output.push((token.to_string(buffer), attributes.clone()));
+                attributes.clear();
+            }
+            TokenTypes::StructStart | TokenTypes::FunctionStart | TokenTypes::ImplStart => attributes.clear(),
+            _ => {}
+        }
+        if token.token_type != TokenTypes::Comment {
+            last = &token.token_type;
+        }
+    }
+    return output;
+}
+
+/// Gets the operator of a trait from its attributes, if it has one
+fn to_operator(name: String, attributes: &Vec<String>) -> Option<GrammarOperator> {
+    let value = |attribute: &str| {
+        attributes
+            .iter()
+            .find_map(|found| found.strip_prefix(attribute).and_then(|inner| inner.strip_prefix('(')))
+            .and_then(|inner| inner.strip_suffix(')'))
+    };
+    return Some(GrammarOperator {
+        operation: value("operation")?.to_string(),
+        priority: value("priority").and_then(|inner| inner.parse().ok()).unwrap_or(0),
+        parse_left: value("parse_left").and_then(|inner| inner.parse().ok()).unwrap_or(false),
+        name,
+    });
+}
+
+/// Turns a list of strings into a JSON array
+fn json_list(values: &Vec<String>) -> String {
+    return format!("[{}]", values.iter().map(|value| json_string(value)).collect::<Vec<_>>().join(", "));
+}
+
+/// Turns a string into an escaped JSON string
+fn json_string(value: &str) -> String {
+    let mut output = String::from("\"");
+    for character in value.chars() {
+        match character {
+            '"' => output += "\\\"",
+            '\\' => output += "\\\\",
+            '\n' => output += "\\n",
+            '\t' => output += "\\t",
+            _ => output.push(character),
+        }
+    }
+    output.push('"');
+    return output;
+}
@@ -21,8 +21,14 @@ use crate::parser::top_parser::parse_top;
 use crate::parser::util::ParserUtils;
 use crate::tokens::tokenizer::Tokenizer;
 
+/// Exports the language's grammar for editors
+pub mod grammar;
 /// The Raven parser
 pub mod parser;
+/// Finds references to definitions, used for renaming
+pub mod references;
+/// Wraps the top-level statements of scripts into an implicit main function
+pub mod script;
 /// The Raven tokenizer
 pub mod tokens;
 
@@ -38,9 +44,13 @@ pub async fn parse(syntax: Arc<Mutex<Syntax>>, handle: Arc<Mutex<HandleWrapper>>
         file_name: name.clone(),
         imports: ImportNameResolver::new(name.clone()),
         handle,
+        defers: Vec::default(),
+        loops: Vec::default(),
     };
 
     parse_top(&mut parser_utils);
+    // Kept so names in the file can be resolved after parsing, like for finding references
+    parser_utils.syntax.lock().file_imports.insert(parser_utils.file, parser_utils.imports.imports);
 }
 
 /// Basic name resolver implementation
@@ -167,3 +177,145 @@ fn read_recursive(base: PathBuf, output: &mut Vec<Box<dyn Readable>>) -> Result<
     }
     return Ok(());
 }
+
+/// An in-memory source set, for compiling code without touching the filesystem (like from stdin, tools, or a REPL)
+#[derive(Clone, Debug, Default)]
+pub struct StringSourceSet {
+    /// Every file's path (like "main.rv" or "folder/file.rv") and source
+    pub files: Vec<(String, String)>,
+}
+
+impl StringSourceSet {
+    /// Creates a source set with a single file
+    pub fn single(path: &str, source: &str) -> Self {
+        return Self { files: vec![(path.to_string(), source.to_string())] };
+    }
+}
+
+/// A single in-memory source file
+pub struct StringSource {
+    /// The file's path, relative to the source set
+    pub path: String,
+    /// The file's source
+    pub source: String,
+}
+
+impl Readable for StringSource {
+    fn read(&self) -> Vec<Token> {
+        let mut tokenizer = Tokenizer::new(self.source.as_bytes());
+        let mut tokens = Vec::default();
+        loop {
+            tokens.push(tokenizer.next());
+            if tokens.last().unwrap().token_type == TokenTypes::EOF {
+                break;
+            }
+        }
+
+        return tokens;
+    }
+
+    fn contents(&self) -> String {
+        return self.source.clone();
+    }
+
+    fn path(&self) -> String {
+        return self.path.clone();
+    }
+
+    fn hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::default();
+        Hash::hash(&self.path, &mut hasher);
+        return hasher.finish();
+    }
+}
+
+impl SourceSet for StringSourceSet {
+    fn get_files(&self) -> Vec<Box<dyn Readable>> {
+        return self
+            .files
+            .iter()
+            .map(|(path, source)| Box::new(StringSource { path: path.clone(), source: source.clone() }) as Box<dyn Readable>)
+            .collect();
+    }
+
+    fn relative(&self, other: &dyn Readable) -> String {
+        let name = other.path().replace(path::MAIN_SEPARATOR, "::").replace('/', "::");
+        return name.strip_suffix(".rv").unwrap_or(&name).to_string();
+    }
+
+    fn cloned(&self) -> Box<dyn SourceSet> {
+        return Box::new(self.clone());
+    }
+}
+
+/// A source set whose files are shadowed by in-memory contents, like an editor's unsaved buffers,
+/// so parsing and diagnostics reflect changes that haven't been saved yet.
+/// Overlaid files keep the path and hash of the file they shadow, so spans still point to the file on disk.
+#[derive(Debug)]
+pub struct OverlaySourceSet {
+    /// The source set being overlaid
+    pub inner: Box<dyn SourceSet>,
+    /// The contents to use instead of the file's contents, keyed by the file's path
+    pub overlays: Arc<HashMap<String, String>>,
+}
+
+/// A file with overlaid contents
+pub struct OverlayFile {
+    /// The file being shadowed
+    pub inner: Box<dyn Readable>,
+    /// The contents used instead of the file's contents
+    pub contents: String,
+}
+
+impl Readable for OverlayFile {
+    fn read(&self) -> Vec<Token> {
+        let mut tokenizer = Tokenizer::new(self.contents.as_bytes());
+        let mut tokens = Vec::default();
+        loop {
+            tokens.push(tokenizer.next());
+            if tokens.last().unwrap().token_type == TokenTypes::EOF {
+                break;
+            }
+        }
+
+        return tokens;
+    }
+
+    fn contents(&self) -> String {
+        return self.contents.clone();
+    }
+
+    fn path(&self) -> String {
+        return self.inner.path();
+    }
+
+    fn hash(&self) -> u64 {
+        return self.inner.hash();
+    }
+}
+
+impl SourceSet for OverlaySourceSet {
+    fn get_files(&self) -> Vec<Box<dyn Readable>> {
+        return self
+            .inner
+            .get_files()
+            .into_iter()
+            .map(|file| match self.overlays.get(&file.path()) {
+                Some(contents) => Box::new(OverlayFile { inner: file, contents: contents.clone() }) as Box<dyn Readable>,
+                None => file,
+            })
+            .collect();
+    }
+
+    fn relative(&self, other: &dyn Readable) -> String {
+        return self.inner.relative(other);
+    }
+
+    fn cloned(&self) -> Box<dyn SourceSet> {
+        return Box::new(OverlaySourceSet { inner: self.inner.cloned(), overlays: self.overlays.clone() });
+    }
+
+    fn edition(&self) -> u32 {
+        return self.inner.edition();
+    }
+}
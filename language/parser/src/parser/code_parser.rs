@@ -1,9 +1,11 @@
 use crate::parser::control_parser::{parse_do_while, parse_for, parse_if, parse_while};
 use crate::parser::operator_parser::parse_operator;
-use crate::parser::util::{parse_generics, ParserUtils};
+use crate::parser::util::{parse_generics, LoopLabels, ParserUtils};
 use data::tokens::{Span, Token, TokenTypes};
+use std::collections::HashMap;
 use std::mem;
 use syntax::async_util::UnparsedType;
+use syntax::desugar::Desugar;
 use syntax::errors::ParsingError;
 use syntax::errors::{ErrorSource, ParsingMessage};
 use syntax::program::code::{EffectType, Effects, Expression, ExpressionType, IntType};
@@ -13,16 +15,231 @@ use syntax::program::function::CodeBody;
 pub fn parse_code(parser_utils: &mut ParserUtils) -> Result<(ExpressionType, CodeBody), ParsingError> {
     let mut lines = Vec::default();
     let mut types = ExpressionType::Line;
-    while let Some(expression) = parse_line(parser_utils, ParseState::None)? {
+    // Defers from outer blocks stay in scope, only this block's are dropped at the end
+    let outer_defers = parser_utils.defers.len();
+    loop {
+        let statement = next_statement(parser_utils);
+        if statement == TokenTypes::Defer {
+            parse_defer(parser_utils)?;
+            continue;
+        }
+        if statement == TokenTypes::Break || statement == TokenTypes::Continue {
+            parse_break(parser_utils, &mut lines)?;
+            types = ExpressionType::Break;
+            continue;
+        }
+
+        let Some(expression) = parse_line(parser_utils, ParseState::None)? else {
+            break;
+        };
         if expression.expression_type != ExpressionType::Line {
             types.clone_from(&expression.expression_type);
         }
-        lines.push(expression);
+        if statement == TokenTypes::Return && !parser_utils.defers.is_empty() {
+            return_with_defers(parser_utils, expression, &mut lines);
+        } else {
+            lines.push(expression);
+        }
+    }
+
+    // Falling off the end of the block runs the block's own defers
+    if !lines.last().is_some_and(|line| line.expression_type != ExpressionType::Line) {
+        run_defers(parser_utils, outer_defers, &mut lines);
     }
+    parser_utils.defers.truncate(outer_defers);
+
     parser_utils.imports.last_id += 1;
     return Ok((types, CodeBody::new(lines, (parser_utils.imports.last_id - 1).to_string())));
 }
 
+/// Parses the body of a loop, so breaks and continues in it jump to the given labels
+pub fn parse_loop_body(
+    parser_utils: &mut ParserUtils,
+    break_label: String,
+    continue_label: String,
+) -> Result<CodeBody, ParsingError> {
+    parser_utils.loops.push(LoopLabels { break_label, continue_label, defers: parser_utils.defers.len() });
+    let body = parse_code(parser_utils);
+    parser_utils.loops.pop();
+    return Ok(body?.1);
+}
+
+/// Gets the type of the token the next statement starts with, skipping over any comments
+fn next_statement(parser_utils: &ParserUtils) -> TokenTypes {
+    return parser_utils.tokens[parser_utils.index..]
+        .iter()
+        .find(|token| token.token_type != TokenTypes::Comment)
+        .map_or(TokenTypes::EOF, |token| token.token_type.clone());
+}
+
+/// Parses a defer statement, which is parsed once and copied to every exit of the block
+fn parse_defer(parser_utils: &mut ParserUtils) -> Result<(), ParsingError> {
+    while parser_utils.tokens[parser_utils.index].token_type != TokenTypes::Defer {
+        parser_utils.index += 1;
+    }
+    parser_utils.index += 1;
+
+    if parser_utils.tokens[parser_utils.index].token_type != TokenTypes::BlockStart {
+        return Err(Span::new(parser_utils.file, parser_utils.index).make_error(ParsingMessage::ExpectedCodeBlock()));
+    }
+    parser_utils.index += 1;
+
+    let span = Span::new(parser_utils.file, parser_utils.index);
+    // Hides the outer defers and loops so a return or break in the body can't leave the defer
+    let outer_defers = mem::take(&mut parser_utils.defers);
+    let outer_loops = mem::take(&mut parser_utils.loops);
+    let parsed = parse_code(parser_utils);
+    parser_utils.defers = outer_defers;
+    parser_utils.loops = outer_loops;
+
+    let (returning, mut body) = parsed?;
+    if returning != ExpressionType::Line {
+        return Err(span.make_error(ParsingMessage::ReturnInDefer()));
+    }
+    // The body falls through to whatever comes after it
+    let desugar = Desugar::new(&span, "defer");
+    body.expressions.push(desugar.line(EffectType::Jump(body.label.clone() + "end")));
+    parser_utils.defers.push(desugar.line(EffectType::CodeBody(body)));
+    return Ok(());
+}
+
+/// Adds a copy of every defer in scope after the first ones to the lines, innermost first
+fn run_defers(parser_utils: &mut ParserUtils, first: usize, lines: &mut Vec<Expression>) {
+    for defer in (first..parser_utils.defers.len()).rev() {
+        let mut copy = parser_utils.defers[defer].clone();
+        relabel(parser_utils, &mut copy.effect);
+        lines.push(copy);
+    }
+}
+
+/// Gives every block in the effect a new label, changing the jumps to them to match,
+/// so copies of the same code can be in the same function
+fn relabel(parser_utils: &mut ParserUtils, effect: &mut Effects) {
+    // Jumps can come before the block they jump to, so every label is found first
+    let mut labels = HashMap::default();
+    for_each_effect(effect, &mut |types| {
+        if let EffectType::CodeBody(body) = types {
+            labels.insert(body.label.clone(), parser_utils.imports.last_id.to_string());
+            parser_utils.imports.last_id += 1;
+        }
+    });
+    for_each_effect(effect, &mut |types| match types {
+        EffectType::CodeBody(body) => body.label.clone_from(&labels[&body.label]),
+        EffectType::Jump(target) => rename_label(target, &labels),
+        EffectType::CompareJump(_, then, other) => {
+            rename_label(then, &labels);
+            rename_label(other, &labels);
+        }
+        _ => {}
+    });
+}
+
+/// Calls the function on the effect and then every effect inside of it
+fn for_each_effect(effect: &mut Effects, function: &mut dyn FnMut(&mut EffectType)) {
+    function(&mut effect.types);
+    match &mut effect.types {
+        EffectType::CodeBody(body) => {
+            for line in &mut body.expressions {
+                for_each_effect(&mut line.effect, function);
+            }
+        }
+        EffectType::Paren(inner)
+        | EffectType::CreateVariable(_, inner, _)
+        | EffectType::CompareJump(inner, _, _)
+        | EffectType::Load(inner, _) => for_each_effect(inner, function),
+        EffectType::ImplementationCall(calling, _, _, arguments, _) => {
+            for_each_effect(calling, function);
+            for argument in arguments {
+                for_each_effect(argument, function);
+            }
+        }
+        EffectType::MethodCall(calling, _, arguments, _) => {
+            if let Some(calling) = calling {
+                for_each_effect(calling, function);
+            }
+            for argument in arguments {
+                for_each_effect(argument, function);
+            }
+        }
+        EffectType::Set(setting, value) => {
+            for_each_effect(setting, function);
+            for_each_effect(value, function);
+        }
+        EffectType::Operation(_, values) | EffectType::CreateArray(values) => {
+            for value in values {
+                for_each_effect(value, function);
+            }
+        }
+        EffectType::CreateStruct(_, fields, base) => {
+            for (_, field) in fields {
+                for_each_effect(field, function);
+            }
+            if let Some(base) = base {
+                for_each_effect(base, function);
+            }
+        }
+        EffectType::NOP
+        | EffectType::Jump(_)
+        | EffectType::LoadVariable(_)
+        | EffectType::Float(_)
+        | EffectType::Int(_, _)
+        | EffectType::Bool(_)
+        | EffectType::Char(_)
+        | EffectType::String(_) => {}
+    }
+}
+
+/// Renames a jump target to the new label of its block, or of the block it's the end of.
+/// Jumps out of the copied code, which can't happen in a defer, are left alone.
+fn rename_label(target: &mut String, labels: &HashMap<String, String>) {
+    if let Some(label) = labels.get(target.as_str()) {
+        target.clone_from(label);
+    } else if let Some(label) = target.strip_suffix("end").and_then(|block| labels.get(block)) {
+        *target = label.clone() + "end";
+    }
+}
+
+/// Adds a return to the lines that runs every defer in scope, innermost first, before returning.
+/// The returned value is computed before the defers run, so they can't change what's returned.
+fn return_with_defers(parser_utils: &mut ParserUtils, returning: Expression, lines: &mut Vec<Expression>) {
+    let desugar = Desugar::new(&returning.effect.span, "return");
+    let returned = if matches!(returning.effect.types, EffectType::NOP) {
+        returning.effect
+    } else {
+        let variable = format!("$defer{}", parser_utils.imports.last_id);
+        parser_utils.imports.last_id += 1;
+        lines.push(desugar.line(EffectType::CreateVariable(variable.clone(), Box::new(returning.effect), false)));
+        desugar.effect(EffectType::LoadVariable(variable))
+    };
+
+    run_defers(parser_utils, 0, lines);
+    lines.push(Expression::new(returning.expression_type, returned));
+}
+
+/// Parses a break or continue, which runs every defer since the start of the loop, innermost first,
+/// before jumping out of the loop or to its next iteration
+fn parse_break(parser_utils: &mut ParserUtils, lines: &mut Vec<Expression>) -> Result<(), ParsingError> {
+    while parser_utils.tokens[parser_utils.index].token_type == TokenTypes::Comment {
+        parser_utils.index += 1;
+    }
+    let span = Span::new(parser_utils.file, parser_utils.index);
+    let continuing = parser_utils.tokens[parser_utils.index].token_type == TokenTypes::Continue;
+    parser_utils.index += 1;
+    if parser_utils.tokens[parser_utils.index].token_type != TokenTypes::LineEnd {
+        return Err(Span::new(parser_utils.file, parser_utils.index).make_error(ParsingMessage::UnexpectedValue()));
+    }
+    parser_utils.index += 1;
+
+    let Some(labels) = parser_utils.loops.last() else {
+        return Err(span.make_error(ParsingMessage::BreakOutsideLoop()));
+    };
+    let target = if continuing { labels.continue_label.clone() } else { labels.break_label.clone() };
+    let first = labels.defers;
+    run_defers(parser_utils, first, lines);
+    lines.push(Expression::new(ExpressionType::Break, Desugar::new(&span, "break").effect(EffectType::Jump(target))));
+    return Ok(());
+}
+
 /// The state of the parser
 #[derive(PartialEq, Clone)]
 pub enum ParseState {
@@ -106,7 +323,13 @@ pub fn parse_line(parser_utils: &mut ParserUtils, state: ParseState) -> Result<O
             }
             TokenTypes::Variable => {
                 let next = parser_utils.tokens.get(parser_utils.index).unwrap();
-                if TokenTypes::ParenOpen == next.token_type {
+                if is_format_string(&token, parser_utils) {
+                    if effect.is_some() {
+                        return Err(span.make_error(ParsingMessage::UnexpectedValue()));
+                    }
+                    parser_utils.index += 1;
+                    effect = Some(parse_format_string(parser_utils, &span)?);
+                } else if TokenTypes::ParenOpen == next.token_type {
                     //Skip because ParenOpen handles this.
                 } else if TokenTypes::Operator == next.token_type {
                     //Skip if a generic method is being called next to preserve the last effect.
@@ -311,10 +534,12 @@ fn parse_basic_line(
             ControlFlow::Skipping
         }        
         TokenTypes::Char => {
-            *effect = Some(Effects::new(
-                Span::new(parser_utils.file, parser_utils.index),
-                EffectType::Char(token.to_string(parser_utils.buffer).as_bytes()[1] as char),
-            ));
+            let character = parse_char(&token.to_string(parser_utils.buffer), &span)?;
+            // Chars are a single byte, so multi-byte characters have to be strings
+            if !character.is_ascii() {
+                return Err(span.make_error(ParsingMessage::MultiByteChar(character)));
+            }
+            *effect = Some(Effects::new(Span::new(parser_utils.file, parser_utils.index), EffectType::Char(character)));
             ControlFlow::Skipping
         }
         TokenTypes::True => {
@@ -330,6 +555,8 @@ fn parse_basic_line(
             ControlFlow::Skipping
         }
         TokenTypes::Let => ControlFlow::Returning(Expression::new(expression_type.clone(), parse_let(parser_utils)?)),
+        TokenTypes::Defer => return Err(span.make_error(ParsingMessage::UnexpectedDefer())),
+        TokenTypes::Break | TokenTypes::Continue => return Err(span.make_error(ParsingMessage::UnexpectedBreak())),
         TokenTypes::If => {
             let expression = parse_if(parser_utils)?;
             let mut expression_type = expression_type.clone();
@@ -360,7 +587,8 @@ fn parse_basic_line(
                             get_effects(parser_utils)?,
                             None,
                         ),
-                        span,
+                        // Spans the method's name, like generic method calls
+                        span: Span::new(parser_utils.file, parser_utils.index - 2),
                     });
                     ControlFlow::Skipping
                 }
@@ -422,47 +650,19 @@ fn parse_string(parser_utils: &mut ParserUtils) -> Result<Effects, ParsingError>
             TokenTypes::StringEscape => {
                 // Escape token
 
-                // get the text from the Raven file starting at the last token up to the current escape character
+                // get the text from the Raven file starting at the last token up to the end of the escape
                 let found = token.to_string(parser_utils.buffer);
 
-                // check if it a hex value, because if it is, then it will 4 characters long (\xAA)
-                let is_hex = found.len() >= 3 && &found[found.len() - 3..found.len() - 2] == "x";
-                let string_end = found.len() - (if is_hex { 4 } else { 2 });
-
-                // add the text to the string, because this text is part of the string in the Raven Code
-                string += &found[0..string_end];
+                // the escape is the only backslash in the token, so the text before it is part of the string
+                let escape = found.find('\\').unwrap();
+                string += &found[0..escape];
 
-                // match the character after the \ to see what type of escape character it is
-                let index = if is_hex { found.len() - 3 } else { found.len() - 1 };
-                match &found[index..=index] {
-                    "n" => {
-                        string += "\n";
-                    }
-                    "t" => {
-                        string += "\t";
-                    }
-                    "r" => {
-                        string += "\r";
-                    }
-                    "\\" => {
-                        string += "\\";
-                    }
-                    "\'" => {
-                        string += "\'";
-                    }
-                    "\"" => {
-                        string += "\"";
-                    }
-                    "x" => {
-                        // Convert the hex to a character, and append it to the string
-                        string.push(
-                            u8::from_str_radix(&found[found.len() - 2..found.len()], 16).expect("Unexpected hex value")
-                                as char,
-                        );
-                    }
-                    _ => {
-                        // not a supported character
-                        panic!("Unexpected escape character: {}", parser_utils.buffer[token.end_offset - 1] as char)
+                match parse_escape(&found[escape..]) {
+                    Some(character) => string.push(character),
+                    // not a supported escape
+                    None => {
+                        return Err(Span::new(parser_utils.file, parser_utils.index - 1)
+                            .make_error(ParsingMessage::InvalidEscape(found[escape..].to_string())))
                     }
                 }
             }
@@ -472,6 +672,223 @@ fn parse_string(parser_utils: &mut ParserUtils) -> Result<Effects, ParsingError>
     }
 }
 
+/// Checks if the variable is the f of a format string, like f"{name}", which has to be right before the quote
+fn is_format_string(token: &Token, parser_utils: &ParserUtils) -> bool {
+    return token.to_string(parser_utils.buffer) == "f"
+        && parser_utils.tokens[parser_utils.index].token_type == TokenTypes::StringStart
+        && parser_utils.buffer.get(token.end_offset) == Some(&b'"');
+}
+
+/// Parses a format string, like f"{name} is {age:?}", into its text and values joined with +.
+/// Values are variables or fields, turned into strings with ToString, and can end in a spec like :? (see FormatSpec).
+/// {{ and }} are literal braces.
+fn parse_format_string(parser_utils: &mut ParserUtils, span: &Span) -> Result<Effects, ParsingError> {
+    let text = match parse_string(parser_utils)?.types {
+        EffectType::String(text) => text,
+        _ => unreachable!(),
+    };
+    let desugar = Desugar::new(span, "format string");
+    let mut output = None;
+    let mut literal = String::default();
+    let mut characters = text[..text.len() - 1].chars().peekable();
+    while let Some(character) = characters.next() {
+        match character {
+            '{' | '}' if characters.peek() == Some(&character) => {
+                characters.next();
+                literal.push(character);
+            }
+            '{' => {
+                let mut value = String::default();
+                loop {
+                    match characters.next() {
+                        Some('}') => break,
+                        Some(character) => value.push(character),
+                        None => return Err(span.make_error(ParsingMessage::InvalidFormat(format!("{{{}", value)))),
+                    }
+                }
+                if !literal.is_empty() {
+                    output = Some(concat(&desugar, output, EffectType::String(mem::take(&mut literal) + "\0")));
+                }
+                let value = format_value(&desugar, &value, span)?;
+                output = Some(concat(&desugar, output, value));
+            }
+            '}' => return Err(span.make_error(ParsingMessage::InvalidFormat("}".to_string()))),
+            _ => literal.push(character),
+        }
+    }
+    if output.is_none() || !literal.is_empty() {
+        output = Some(concat(&desugar, output, EffectType::String(literal + "\0")));
+    }
+    return Ok(output.unwrap());
+}
+
+/// Adds the next part of a format string to the end of the parts before it
+fn concat(desugar: &Desugar, output: Option<Effects>, next: EffectType) -> Effects {
+    return match output {
+        Some(output) => desugar.effect(EffectType::Operation("{}+{}".to_string(), vec![output, desugar.effect(next)])),
+        None => desugar.effect(next),
+    };
+}
+
+/// Turns a value in a format string, like point.x or name:>8, into the call turning it into a string.
+/// Errors if it isn't a variable or field, or its spec isn't valid.
+fn format_value(desugar: &Desugar, value: &str, span: &Span) -> Result<EffectType, ParsingError> {
+    let (path, spec) = value.split_once(':').unwrap_or((value, ""));
+    let spec = FormatSpec::parse(spec)
+        .ok_or_else(|| span.make_error(ParsingMessage::InvalidFormatSpec(format!("{{{}}}", value))))?;
+    let mut loading = None;
+    for name in path.trim().split('.') {
+        if name.is_empty()
+            || name.starts_with(|character: char| character.is_ascii_digit())
+            || !name.chars().all(|character| character.is_alphanumeric() || character == '_')
+        {
+            return Err(span.make_error(ParsingMessage::InvalidFormat(format!("{{{}}}", value))));
+        }
+        loading = Some(match loading {
+            Some(loading) => EffectType::Load(Box::new(desugar.effect(loading)), name.to_string()),
+            None => EffectType::LoadVariable(name.to_string()),
+        });
+    }
+    // Only integers implement Radix and only floats implement Precision, so the checker rejects specs like
+    // {count:.2} or {ratio:x} when it can't find the impl
+    let number = |number: u64| desugar.effect(EffectType::Int(number, IntType::U64));
+    let (traits, method, arguments) = match (spec.radix, spec.precision) {
+        (Some(radix), _) => ("fmt::Radix", "to_string_radix", vec![number(radix)]),
+        (None, Some(precision)) => ("fmt::Precision", "to_string_precision", vec![number(precision)]),
+        _ if spec.debug => ("fmt::Debug", "debug", vec![]),
+        _ => ("fmt::ToString", "to_string", vec![]),
+    };
+    let text = EffectType::ImplementationCall(
+        Box::new(desugar.effect(loading.unwrap())),
+        traits.to_string(),
+        method.to_string(),
+        arguments,
+        None,
+    );
+    let Some(width) = spec.width else {
+        return Ok(text);
+    };
+    let method = if spec.right_align { "pad_left" } else { "pad_right" };
+    return Ok(EffectType::ImplementationCall(
+        Box::new(desugar.effect(text)),
+        "fmt::Pad".to_string(),
+        method.to_string(),
+        vec![number(width), desugar.effect(EffectType::Char(spec.fill))],
+        None,
+    ));
+}
+
+/// The spec after the colon in a format string value, like the >8.2 in {value:>8.2}.
+/// It's an optional fill character and alignment (< for left and > for right), a width, a precision after a period,
+/// and a type: x, o or b for hexadecimal, octal or binary, or ? for Debug.
+/// Values are left-aligned and padded with spaces by default, and a width starting with 0 pads with zeros on the left.
+struct FormatSpec {
+    /// The character the value is padded with
+    fill: char,
+    /// Whether the value is padded on the left instead of the right
+    right_align: bool,
+    /// The width the value is padded to
+    width: Option<u64>,
+    /// How many digits to write after the period
+    precision: Option<u64>,
+    /// The base to write the value in
+    radix: Option<u64>,
+    /// Whether the value is written with Debug instead of ToString
+    debug: bool,
+}
+
+impl FormatSpec {
+    /// Parses a spec, or returns None if it isn't valid
+    fn parse(spec: &str) -> Option<Self> {
+        let mut output =
+            FormatSpec { fill: ' ', right_align: false, width: None, precision: None, radix: None, debug: false };
+        let mut characters = spec.chars();
+        let mut rest = spec;
+        // A character is only a fill if an alignment follows it
+        match (characters.next(), characters.next()) {
+            (Some(fill), Some(align @ ('<' | '>'))) => {
+                output.fill = fill;
+                output.right_align = align == '>';
+                rest = characters.as_str();
+            }
+            (Some(align @ ('<' | '>')), _) => {
+                output.right_align = align == '>';
+                rest = &spec[1..];
+            }
+            (Some('0'), _) => {
+                output.fill = '0';
+                output.right_align = true;
+                rest = &spec[1..];
+            }
+            _ => {}
+        }
+        output.width = take_number(&mut rest);
+        if let Some(precision) = rest.strip_prefix('.') {
+            rest = precision;
+            output.precision = Some(take_number(&mut rest)?);
+        }
+        match rest {
+            "" => {}
+            "?" => output.debug = true,
+            "x" => output.radix = Some(16),
+            "o" => output.radix = Some(8),
+            "b" => output.radix = Some(2),
+            _ => return None,
+        }
+        // Other bases are only for integers and precisions only for floats, so they can't be mixed
+        if output.precision.is_some() && (output.radix.is_some() || output.debug) {
+            return None;
+        }
+        return Some(output);
+    }
+}
+
+/// Takes the number at the start of the text, if there is one
+fn take_number(text: &mut &str) -> Option<u64> {
+    let length = text.find(|character: char| !character.is_ascii_digit()).unwrap_or(text.len());
+    let number = text[..length].parse().ok();
+    *text = &text[length..];
+    return number;
+}
+
+/// Parses a char literal, including the quotes, into its character
+fn parse_char(literal: &str, span: &Span) -> Result<char, ParsingError> {
+    let inner = &literal[1..literal.len() - 1];
+    return if inner.starts_with('\\') {
+        parse_escape(inner).ok_or_else(|| span.make_error(ParsingMessage::InvalidEscape(inner.to_string())))
+    } else {
+        Ok(inner.chars().next().unwrap())
+    };
+}
+
+/// The escapes standing for one character, by the character after the backslash, like n for \n
+pub const CHARACTER_ESCAPES: [(char, char); 7] =
+    [('n', '\n'), ('t', '\t'), ('r', '\r'), ('0', '\0'), ('\\', '\\'), ('\'', '\''), ('"', '"')];
+
+/// The escapes taking hex digits, with an A for each digit: \x takes two, and \u{} one to six
+pub const HEX_ESCAPES: [&str; 2] = ["\\xAA", "\\u{AAAAAA}"];
+
+/// Parses an escape sequence, like \n, \x41 or \u{1F600}, into the character it represents.
+/// Returns None if the escape isn't valid, like an unknown character or a code point that isn't unicode.
+fn parse_escape(escape: &str) -> Option<char> {
+    let kind = escape.chars().nth(1)?;
+    if let Some((_, character)) = CHARACTER_ESCAPES.iter().find(|(name, _)| *name == kind) {
+        return Some(*character);
+    }
+    return match kind {
+        'x' => u8::from_str_radix(escape.get(2..4)?, 16).ok().map(|value| value as char),
+        'u' => {
+            let hex = escape.strip_prefix("\\u{")?.strip_suffix('}')?;
+            // Unicode escapes are up to six hex digits, and surrogates aren't valid characters
+            if hex.is_empty() || hex.len() > 6 {
+                return None;
+            }
+            u32::from_str_radix(hex, 16).ok().and_then(char::from_u32)
+        }
+        _ => None,
+    };
+}
+
 /// Parses a generic method call
 fn parse_generic_method(effect: Option<Effects>, parser_utils: &mut ParserUtils) -> Result<Effects, ParsingError> {
     let name = parser_utils.tokens[parser_utils.index - 2].to_string(parser_utils.buffer);
@@ -514,6 +931,11 @@ fn get_effects(parser_utils: &mut ParserUtils) -> Result<Vec<Effects>, ParsingEr
             if parser_utils.tokens[parser_utils.index - 1].token_type != TokenTypes::ArgumentEnd {
                 break;
             }
+            // A trailing comma before the closing paren
+            if parser_utils.tokens[parser_utils.index].token_type == TokenTypes::ParenClose {
+                parser_utils.index += 1;
+                break;
+            }
         }
     } else {
         parser_utils.index += 1;
@@ -525,6 +947,12 @@ fn get_effects(parser_utils: &mut ParserUtils) -> Result<Vec<Effects>, ParsingEr
 fn parse_let(parser_utils: &mut ParserUtils) -> Result<Effects, ParsingError> {
     let name;
     let mut error_token;
+    // let mut declares a variable that can be set later
+    let mutable = parser_utils.tokens[parser_utils.index].to_string(parser_utils.buffer) == "mut"
+        && parser_utils.tokens[parser_utils.index + 1].token_type == TokenTypes::Variable;
+    if mutable {
+        parser_utils.index += 1;
+    }
     {
         let next = &parser_utils.tokens[parser_utils.index];
         if TokenTypes::Variable == next.token_type {
@@ -544,7 +972,7 @@ fn parse_let(parser_utils: &mut ParserUtils) -> Result<Effects, ParsingError> {
     return match parse_line(parser_utils, ParseState::None)? {
         Some(line) => {
             error_token.extend_span(parser_utils.index - 2);
-            Ok(Effects::new(error_token, EffectType::CreateVariable(name, Box::new(line.effect))))
+            Ok(Effects::new(error_token, EffectType::CreateVariable(name, Box::new(line.effect), mutable)))
         }
         None => Err(Span::new(parser_utils.file, parser_utils.index).make_error(ParsingMessage::UnexpectedVoid())),
     };
@@ -554,7 +982,7 @@ fn parse_let(parser_utils: &mut ParserUtils) -> Result<Effects, ParsingError> {
 fn parse_new(parser_utils: &mut ParserUtils, span: &Span) -> Result<Effects, ParsingError> {
     let mut types: Option<UnparsedType> = None;
 
-    let values;
+    let (values, base);
 
     let type_token = parser_utils.index;
 
@@ -568,7 +996,7 @@ fn parse_new(parser_utils: &mut ParserUtils, span: &Span) -> Result<Effects, Par
                 types = Some(parse_generics(types.unwrap().to_string(), parser_utils).0);
             }
             TokenTypes::BlockStart => {
-                values = parse_new_args(parser_utils, span)?;
+                (values, base) = parse_new_args(parser_utils, span)?;
                 break;
             }
             TokenTypes::InvalidCharacters => {}
@@ -576,13 +1004,20 @@ fn parse_new(parser_utils: &mut ParserUtils, span: &Span) -> Result<Effects, Par
         }
     }
 
-    return Ok(Effects::new(Span::new(parser_utils.file, type_token), EffectType::CreateStruct(types.unwrap(), values)));
+    return Ok(Effects::new(
+        Span::new(parser_utils.file, type_token),
+        EffectType::CreateStruct(types.unwrap(), values, base.map(Box::new)),
+    ));
 }
 
-/// Parses the arguments to a new program
-fn parse_new_args(parser_utils: &mut ParserUtils, span: &Span) -> Result<Vec<(String, Effects)>, ParsingError> {
+/// Parses the arguments to a new program, and the struct to copy the other fields from with ..base if there is one
+fn parse_new_args(
+    parser_utils: &mut ParserUtils,
+    span: &Span,
+) -> Result<(Vec<(String, Effects)>, Option<Effects>), ParsingError> {
     let mut values = Vec::default();
     let mut name = String::default();
+    let mut base = None;
     loop {
         let token: &Token = parser_utils.tokens.get(parser_utils.index).unwrap();
         parser_utils.index += 1;
@@ -595,15 +1030,41 @@ fn parse_new_args(parser_utils: &mut ParserUtils, span: &Span) -> Result<Vec<(St
                         None => return Err(span.make_error(ParsingMessage::ExpectedEffect())),
                     }
                 } else {
+                    // Field init shorthand, where "name," is the same as "name: name,"
                     Effects::new(
-                        Span::new(parser_utils.file, parser_utils.index - 1),
+                        Span::new(parser_utils.file, parser_utils.index - 2),
                         EffectType::LoadVariable(name.clone()),
                     )
                 };
                 values.push((name, effect));
                 name = String::default();
             }
-            TokenTypes::BlockEnd => break,
+            // Update syntax, ..base copies every field that isn't set from base
+            TokenTypes::Period => {
+                if parser_utils.tokens[parser_utils.index].token_type != TokenTypes::Period || base.is_some() {
+                    return Err(
+                        Span::new(parser_utils.file, parser_utils.index - 1).make_error(ParsingMessage::UnexpectedSymbol())
+                    );
+                }
+                parser_utils.index += 1;
+                base = match parse_line(parser_utils, ParseState::New)? {
+                    Some(inner) => Some(inner.effect),
+                    None => return Err(span.make_error(ParsingMessage::ExpectedEffect())),
+                };
+            }
+            TokenTypes::BlockEnd => {
+                // Shorthand for the last field without a trailing comma
+                if !name.is_empty() {
+                    values.push((
+                        name.clone(),
+                        Effects::new(
+                            Span::new(parser_utils.file, parser_utils.index - 2),
+                            EffectType::LoadVariable(name.clone()),
+                        ),
+                    ));
+                }
+                break;
+            }
             TokenTypes::LineEnd => {
                 if parser_utils.tokens.get(parser_utils.index - 2).unwrap().token_type == TokenTypes::BlockEnd {
                     parser_utils.index -= 1;
@@ -616,7 +1077,7 @@ fn parse_new_args(parser_utils: &mut ParserUtils, span: &Span) -> Result<Vec<(St
         }
     }
 
-    return Ok(values);
+    return Ok((values, base));
 }
 
 /// Checks if a type is generic or if it's just followed by an operator
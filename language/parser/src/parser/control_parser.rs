@@ -1,8 +1,9 @@
+use syntax::desugar::Desugar;
 use syntax::errors::{ErrorSource, ParsingError, ParsingMessage};
 use syntax::program::code::{EffectType, Effects, Expression, ExpressionType};
 use syntax::program::function::CodeBody;
 
-use crate::parser::code_parser::{parse_code, parse_line, ParseState};
+use crate::parser::code_parser::{parse_code, parse_line, parse_loop_body, ParseState};
 use crate::ParserUtils;
 use data::tokens::{Span, TokenTypes};
 
@@ -13,6 +14,7 @@ pub fn parse_if(parser_utils: &mut ParserUtils) -> Result<Expression, ParsingErr
     // ex:
     // if value == 2
     // This gets value == 2
+    let mut header = Span::new(parser_utils.file, parser_utils.index - 1);
     let effect = parse_line(parser_utils, ParseState::ControlVariable)?;
     if effect.is_none() {
         return Err(Span::new(parser_utils.file, parser_utils.index).make_error(ParsingMessage::UnexpectedVoid()));
@@ -22,6 +24,8 @@ pub fn parse_if(parser_utils: &mut ParserUtils) -> Result<Expression, ParsingErr
     if parser_utils.tokens[parser_utils.index].token_type != TokenTypes::BlockStart {
         return Err(Span::new(parser_utils.file, parser_utils.index).make_error(ParsingMessage::UnexpectedVoid()));
     }
+    header.extend_span(parser_utils.index - 1);
+    let desugar = Desugar::new(&header, "if statement");
 
     parser_utils.index += 1;
 
@@ -80,12 +84,13 @@ pub fn parse_if(parser_utils: &mut ParserUtils) -> Result<Expression, ParsingErr
     parser_utils.imports.last_id += adding;
     return Ok(Expression::new(
         returning,
-        create_if(effect.unwrap().effect, body, else_ifs, else_body, parser_utils.imports.last_id - adding)?,
+        create_if(&desugar, effect.unwrap().effect, body, else_ifs, else_body, parser_utils.imports.last_id - adding)?,
     ));
 }
 
 /// Parses a for statement into a single expression
 pub fn parse_for(parser_utils: &mut ParserUtils) -> Result<Effects, ParsingError> {
+    let mut header = Span::new(parser_utils.file, parser_utils.index - 1);
     let name = &parser_utils.tokens[parser_utils.index];
     parser_utils.index += 1;
     // Gets the name of the for loop variable
@@ -113,18 +118,23 @@ pub fn parse_for(parser_utils: &mut ParserUtils) -> Result<Effects, ParsingError
     if parser_utils.tokens[parser_utils.index].token_type != TokenTypes::BlockStart {
         return Err(Span::new(parser_utils.file, parser_utils.index - 1).make_error(ParsingMessage::ExpectedCodeBlock()));
     }
+    header.extend_span(parser_utils.index - 1);
+    let desugar = Desugar::new(&header, "for loop");
     parser_utils.index += 1;
 
-    // Parses the body of the for loop
-    let body = parse_code(parser_utils)?.1;
+    // The labels are picked before the body is parsed so breaks and continues can jump to them
+    let id = parser_utils.imports.last_id;
     parser_utils.imports.last_id += 2;
+    // Parses the body of the for loop
+    let body = parse_loop_body(parser_utils, id.to_string() + "end", (id + 1).to_string())?;
 
     // Returns the finished for loop.
-    return create_for(name, effect.unwrap().effect, body, parser_utils.imports.last_id - 2);
+    return create_for(&desugar, name, effect.unwrap().effect, body, id);
 }
 
 /// Parses a while statement into a single expression
 pub fn parse_while(parser_utils: &mut ParserUtils) -> Result<Effects, ParsingError> {
+    let mut header = Span::new(parser_utils.file, parser_utils.index - 1);
     let effect = parse_line(parser_utils, ParseState::ControlVariable)?;
     if effect.is_none() {
         return Err(Span::new(parser_utils.file, parser_utils.index).make_error(ParsingMessage::UnexpectedVoid()));
@@ -133,23 +143,30 @@ pub fn parse_while(parser_utils: &mut ParserUtils) -> Result<Effects, ParsingErr
     if parser_utils.tokens[parser_utils.index].token_type != TokenTypes::BlockStart {
         return Err(Span::new(parser_utils.file, parser_utils.index).make_error(ParsingMessage::UnexpectedVoid()));
     }
+    header.extend_span(parser_utils.index - 1);
+    let desugar = Desugar::new(&header, "while loop");
 
     parser_utils.index += 1;
 
-    let (_returning, body) = parse_code(parser_utils)?;
+    let id = parser_utils.imports.last_id;
     parser_utils.imports.last_id += 1;
-    return create_while(effect.unwrap().effect, body, parser_utils.imports.last_id - 1);
+    let body = parse_loop_body(parser_utils, id.to_string() + "end", id.to_string())?;
+    return create_while(&desugar, effect.unwrap().effect, body, id);
 }
 
 /// Parses a do while into a single expression
 pub fn parse_do_while(parser_utils: &mut ParserUtils) -> Result<Effects, ParsingError> {
+    let desugar = Desugar::new(&Span::new(parser_utils.file, parser_utils.index - 1), "do while loop");
     if parser_utils.tokens.get(parser_utils.index).unwrap().token_type != TokenTypes::BlockStart {
         return Err(Span::new(parser_utils.file, parser_utils.index).make_error(ParsingMessage::UnexpectedVoid()));
     }
 
     parser_utils.index += 1;
 
-    let (_returning, body) = parse_code(parser_utils)?;
+    // The condition gets its own block so a continue can jump to it
+    let id = parser_utils.imports.last_id;
+    parser_utils.imports.last_id += 2;
+    let body = parse_loop_body(parser_utils, id.to_string() + "end", (id + 1).to_string())?;
 
     if parser_utils.tokens[parser_utils.index].token_type != TokenTypes::While {
         return Err(Span::new(parser_utils.file, parser_utils.index).make_error(ParsingMessage::ExpectedWhile()));
@@ -162,45 +179,38 @@ pub fn parse_do_while(parser_utils: &mut ParserUtils) -> Result<Effects, Parsing
         return Err(Span::new(parser_utils.file, parser_utils.index).make_error(ParsingMessage::UnexpectedVoid()));
     }
 
-    parser_utils.imports.last_id += 1;
-    return create_do_while(effect.unwrap().effect, body, parser_utils.imports.last_id - 1);
+    return create_do_while(&desugar, effect.unwrap().effect, body, id);
 }
 
 /// Creates a do while effect from the body and the condition
-fn create_do_while(effect: Effects, mut body: CodeBody, id: u32) -> Result<Effects, ParsingError> {
+fn create_do_while(desugar: &Desugar, effect: Effects, mut body: CodeBody, id: u32) -> Result<Effects, ParsingError> {
     let mut top = Vec::default();
 
-    let label = body.label.clone();
-    body.expressions.push(Expression::new(
-        ExpressionType::Line,
-        Effects::new(Span::default(), EffectType::Jump((id - 1).to_string() + "end")),
-    ));
-    top.push(Expression::new(ExpressionType::Line, Effects::new(Span::default(), EffectType::CodeBody(body))));
-    top.push(Expression::new(
-        ExpressionType::Line,
-        Effects::new(Span::default(), EffectType::CompareJump(Box::new(effect), label, id.to_string() + "end")),
-    ));
+    let condition = CodeBody::new(
+        vec![desugar.line(EffectType::CompareJump(Box::new(effect), body.label.clone(), id.to_string() + "end"))],
+        (id + 1).to_string(),
+    );
+    body.expressions.push(desugar.line(EffectType::Jump((id + 1).to_string())));
+    top.push(desugar.line(EffectType::CodeBody(body)));
+    top.push(desugar.line(EffectType::CodeBody(condition)));
 
-    return Ok(Effects::new(Span::default(), EffectType::CodeBody(CodeBody::new(top, id.to_string()))));
+    return Ok(desugar.effect(EffectType::CodeBody(CodeBody::new(top, id.to_string()))));
 }
 
 /// Creates a while effect from the body and the condition
-fn create_while(effect: Effects, mut body: CodeBody, id: u32) -> Result<Effects, ParsingError> {
+fn create_while(desugar: &Desugar, effect: Effects, mut body: CodeBody, id: u32) -> Result<Effects, ParsingError> {
     let mut top = Vec::default();
 
-    top.push(Expression::new(
-        ExpressionType::Line,
-        Effects::new(Span::default(), EffectType::CompareJump(Box::new(effect), body.label.clone(), id.to_string() + "end")),
-    ));
-    body.expressions
-        .push(Expression::new(ExpressionType::Line, Effects::new(Span::default(), EffectType::Jump(id.to_string()))));
-    top.push(Expression::new(ExpressionType::Line, Effects::new(Span::default(), EffectType::CodeBody(body))));
+    top.push(desugar.line(EffectType::CompareJump(Box::new(effect), body.label.clone(), id.to_string() + "end")));
+    body.expressions.push(desugar.line(EffectType::Jump(id.to_string())));
+    top.push(desugar.line(EffectType::CodeBody(body)));
 
-    return Ok(Effects::new(Span::default(), EffectType::CodeBody(CodeBody::new(top, id.to_string()))));
+    return Ok(desugar.effect(EffectType::CodeBody(CodeBody::new(top, id.to_string()))));
 }
 
 /// Creates an if statement from the body, the effect, the else ifs, and the else body
 fn create_if(
+    desugar: &Desugar,
     effect: Effects,
     body: CodeBody,
     mut else_ifs: Vec<(Effects, CodeBody)>,
@@ -211,10 +221,7 @@ fn create_if(
 
     // Maps the else body, if there is an else_if there needs to be an empty else to put the else if into.
     let mut else_body = if let Some(mut body) = else_body {
-        body.expressions.push(Expression::new(
-            ExpressionType::Line,
-            Effects::new(Span::default(), EffectType::Jump(id.to_string() + "end")),
-        ));
+        body.expressions.push(desugar.line(EffectType::Jump(id.to_string() + "end")));
         Some(body)
     } else if !else_ifs.is_empty() {
         Some(CodeBody::new(Vec::default(), id.to_string()))
@@ -226,29 +233,17 @@ fn create_if(
     // Add every else if statement
     while !else_ifs.is_empty() {
         let (effect, mut body) = else_ifs.remove(0);
-        body.expressions.push(Expression::new(
-            ExpressionType::Line,
-            Effects::new(Span::default(), EffectType::Jump(id.to_string() + "end")),
-        ));
+        body.expressions.push(desugar.line(EffectType::Jump(id.to_string() + "end")));
         // Creates the body of the else if by adding another if statement to the top of the else.
         let inner = CodeBody::new(
             vec![
-                Expression::new(
-                    ExpressionType::Line,
-                    Effects::new(
-                        Span::default(),
-                        EffectType::CompareJump(
-                            Box::new(effect),
-                            body.label.clone(),
-                            else_body.as_ref().unwrap().label.clone(),
-                        ),
-                    ),
-                ),
-                Expression::new(ExpressionType::Line, Effects::new(Span::default(), EffectType::CodeBody(body))),
-                Expression::new(
-                    ExpressionType::Line,
-                    Effects::new(Span::default(), EffectType::CodeBody(else_body.unwrap())),
-                ),
+                desugar.line(EffectType::CompareJump(
+                    Box::new(effect),
+                    body.label.clone(),
+                    else_body.as_ref().unwrap().label.clone(),
+                )),
+                desugar.line(EffectType::CodeBody(body)),
+                desugar.line(EffectType::CodeBody(else_body.unwrap())),
             ],
             temp.to_string(),
         );
@@ -258,108 +253,80 @@ fn create_if(
 
     // Where we jump if the if fails
     let if_jumping = if let Some(body) = &mut else_body {
-        body.expressions.push(Expression::new(
-            ExpressionType::Line,
-            Effects::new(Span::default(), EffectType::Jump(body.label.clone())),
-        ));
+        body.expressions.push(desugar.line(EffectType::Jump(body.label.clone())));
         body.label.clone()
     } else {
         id.to_string() + "end"
     };
 
-    body.expressions.push(Expression::new(
-        ExpressionType::Line,
-        Effects::new(Span::default(), EffectType::Jump(id.to_string() + "end")),
-    ));
+    body.expressions.push(desugar.line(EffectType::Jump(id.to_string() + "end")));
 
     // The CodeBody before the if statement that controls the control flow
     let mut top = CodeBody::new(
         vec![
-            Expression::new(
-                ExpressionType::Line,
-                Effects::new(Span::default(), EffectType::CompareJump(Box::new(effect), body.label.clone(), if_jumping)),
-            ),
-            Expression::new(ExpressionType::Line, Effects::new(Span::default(), EffectType::CodeBody(body))),
+            desugar.line(EffectType::CompareJump(Box::new(effect), body.label.clone(), if_jumping)),
+            desugar.line(EffectType::CodeBody(body)),
         ],
         id.to_string(),
     );
 
     // Add the else body.
     if let Some(body) = else_body {
-        top.expressions
-            .push(Expression::new(ExpressionType::Line, Effects::new(Span::default(), EffectType::CodeBody(body))));
+        top.expressions.push(desugar.line(EffectType::CodeBody(body)));
     }
 
-    return Ok(Effects::new(Span::default(), EffectType::CodeBody(top)));
+    return Ok(desugar.effect(EffectType::CodeBody(top)));
 }
 
 /// Creates a for loop effect from the body and iterator effect
-fn create_for(name: String, effect: Effects, mut body: CodeBody, id: u32) -> Result<Effects, ParsingError> {
+fn create_for(
+    desugar: &Desugar,
+    name: String,
+    effect: Effects,
+    mut body: CodeBody,
+    id: u32,
+) -> Result<Effects, ParsingError> {
     let mut top = Vec::default();
     let variable = format!("$iter{}", id);
-    top.insert(
-        0,
-        Expression::new(
-            ExpressionType::Line,
-            Effects::new(Span::default(), EffectType::CreateVariable(variable.clone(), Box::new(effect))),
-        ),
-    );
-    top.push(Expression::new(ExpressionType::Line, Effects::new(Span::default(), EffectType::Jump((id + 1).to_string()))));
+    top.insert(0, desugar.line(EffectType::CreateVariable(variable.clone(), Box::new(effect), false)));
+    top.push(desugar.line(EffectType::Jump((id + 1).to_string())));
     // Adds a call to the Iter::next function at the top of the for loop.
     body.expressions.insert(
         0,
-        Expression::new(
-            ExpressionType::Line,
-            Effects::new(
-                Span::default(),
-                EffectType::CreateVariable(
-                    name.clone(),
-                    Box::new(Effects::new(
-                        Span::default(),
-                        EffectType::ImplementationCall(
-                            Box::new(Effects::new(Span::default(), EffectType::LoadVariable(variable.clone()))),
-                            "iter::Iter".to_string(),
-                            "next".to_string(),
-                            vec![],
-                            None,
-                        ),
-                    )),
-                ),
-            ),
-        ),
+        desugar.line(EffectType::CreateVariable(
+            name.clone(),
+            Box::new(desugar.effect(EffectType::ImplementationCall(
+                Box::new(desugar.effect(EffectType::LoadVariable(variable.clone()))),
+                "iter::Iter".to_string(),
+                "next".to_string(),
+                vec![],
+                None,
+            ))),
+            false,
+        )),
     );
 
     // Jumps to the header of the for loop after each loop
-    body.expressions
-        .push(Expression::new(ExpressionType::Line, Effects::new(Span::default(), EffectType::Jump((id + 1).to_string()))));
+    body.expressions.push(desugar.line(EffectType::Jump((id + 1).to_string())));
 
     let for_check = CodeBody::new(
-        vec![Expression::new(
-            ExpressionType::Line,
-            Effects::new(
-                Span::default(),
-                EffectType::CompareJump(
-                    Box::new(Effects::new(
-                        Span::default(),
-                        EffectType::ImplementationCall(
-                            Box::new(Effects::new(Span::default(), EffectType::LoadVariable(variable.clone()))),
-                            "iter::Iter".to_string(),
-                            "has_next".to_string(),
-                            vec![],
-                            None,
-                        ),
-                    )),
-                    body.label.clone(),
-                    id.to_string() + "end",
-                ),
-            ),
-        )],
+        vec![desugar.line(EffectType::CompareJump(
+            Box::new(desugar.effect(EffectType::ImplementationCall(
+                Box::new(desugar.effect(EffectType::LoadVariable(variable.clone()))),
+                "iter::Iter".to_string(),
+                "has_next".to_string(),
+                vec![],
+                None,
+            ))),
+            body.label.clone(),
+            id.to_string() + "end",
+        ))],
         (id + 1).to_string(),
     );
     // Checks if the end is reached, and if so jumps to the end of the block.
     // The block after is named id + end so it can be named before it exists.
-    top.push(Expression::new(ExpressionType::Line, Effects::new(Span::default(), EffectType::CodeBody(for_check))));
-    top.push(Expression::new(ExpressionType::Line, Effects::new(Span::default(), EffectType::CodeBody(body))));
+    top.push(desugar.line(EffectType::CodeBody(for_check)));
+    top.push(desugar.line(EffectType::CodeBody(body)));
 
-    return Ok(Effects::new(Span::default(), EffectType::CodeBody(CodeBody::new(top, id.to_string()))));
+    return Ok(desugar.effect(EffectType::CodeBody(CodeBody::new(top, id.to_string()))));
 }
@@ -0,0 +1,218 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use data::tokens::{Span, TokenTypes};
+use syntax::errors::{ErrorSource, ParsingMessage};
+use syntax::Attribute;
+
+use crate::parser::top_parser::parse_top;
+use crate::parser::util::ParserUtils;
+use crate::tokens::tokenizer::Tokenizer;
+
+/// Generates the source of a derived impl from the struct's name with generics, its generics, and its fields
+type Generator = fn(&str, &[String], &[(String, String)]) -> String;
+
+/// Every trait that can be derived, with the module it's in and the function generating its impl
+static DERIVABLE: [(&str, &str, Generator); 6] = [
+    ("Serialize", "serialize", derive_serialize),
+    ("Deserialize", "serialize", derive_deserialize),
+    ("Debug", "fmt", derive_debug),
+    ("Equal", "math", derive_equal),
+    ("Compare", "math", derive_compare),
+    ("Hash", "hash", derive_hash),
+];
+
+/// The field types deserializers can read directly, with the method that reads them
+static DESERIALIZED_PRIMITIVES: [(&str, &str); 3] = [("u64", "read_u64"), ("bool", "read_bool"), ("str", "read_str")];
+
+/// Parses the impls of the traits in the struct's #[derive(...)] attributes, like #[derive(Serialize, Deserialize)].
+/// The impls are generated as source from the struct's declared fields and parsed as if they were written in the same file,
+/// so they're checked and compiled like any other impl.
+pub fn derive(
+    parser_utils: &mut ParserUtils,
+    attributes: &Vec<Attribute>,
+    span: &Span,
+    name: &str,
+    generics: &[String],
+    fields: &[(String, String)],
+) {
+    let full_name = if generics.is_empty() { name.to_string() } else { format!("{}<{}>", name, generics.join(", ")) };
+    let mut source = String::default();
+    let mut imports = Vec::default();
+    for derived in derived_traits(attributes) {
+        match DERIVABLE.iter().find(|(found, _, _)| *found == derived) {
+            Some((_, module, generator)) => {
+                imports.push(module.to_string());
+                source += &generator(&full_name, generics, fields);
+            }
+            None => parser_utils.syntax.lock().errors.push(span.make_error(ParsingMessage::UnknownDerive(derived))),
+        }
+    }
+    if source.is_empty() {
+        return;
+    }
+
+    // The generated source isn't in any file, so it gets its own hash
+    let mut hasher = DefaultHasher::default();
+    Hash::hash(&(&parser_utils.file_name, &source), &mut hasher);
+    let mut tokenizer = Tokenizer::new(source.as_bytes());
+    let mut tokens = Vec::default();
+    loop {
+        tokens.push(tokenizer.next());
+        if tokens.last().unwrap().token_type == TokenTypes::EOF {
+            break;
+        }
+    }
+
+    let mut resolver = parser_utils.imports.clone();
+    resolver.imports.extend(imports);
+    resolver.generics.clear();
+    let mut generated = ParserUtils {
+        buffer: source.as_bytes(),
+        index: 0,
+        tokens,
+        syntax: parser_utils.syntax.clone(),
+        file: hasher.finish(),
+        file_name: parser_utils.file_name.clone(),
+        imports: resolver,
+        handle: parser_utils.handle.clone(),
+        defers: Vec::default(),
+        loops: Vec::default(),
+    };
+    parse_top(&mut generated);
+}
+
+/// Gets the names of the traits in the #[derive(...)] attributes
+fn derived_traits(attributes: &Vec<Attribute>) -> Vec<String> {
+    let mut output = Vec::default();
+    for attribute in attributes {
+        if let Attribute::String(name, value) = attribute {
+            if name == "derive" {
+                output.extend(value.split(',').map(|found| found.trim().to_string()).filter(|found| !found.is_empty()));
+            }
+        }
+    }
+    return output;
+}
+
+/// Makes the generics of a derived impl, with each generic bound by the derived trait.
+/// Any {} in the bound is replaced with the generic, for traits like Equal<T>.
+fn impl_generics(generics: &[String], bound: &str) -> String {
+    if generics.is_empty() {
+        return String::default();
+    }
+    return format!(
+        "<{}>",
+        generics
+            .iter()
+            .map(|generic| format!("{}: {}", generic, bound.replace("{}", generic)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+}
+
+/// Derives Serialize, writing the struct as an object with every field in the order they're declared
+fn derive_serialize(name: &str, generics: &[String], fields: &[(String, String)]) -> String {
+    let mut body = String::from("        serializer.begin_object();\n");
+    for (field, _) in fields {
+        body += &format!("        serializer.field(\"{}\");\n        self.{}.serialize(serializer);\n", field, field);
+    }
+    body += "        serializer.end_object();\n";
+    return format!(
+        "impl{} Serialize for {} {{\n    pub fn serialize(self, serializer: Serializer) {{\n{}    }}\n}}\n",
+        impl_generics(generics, "Serialize"),
+        name,
+        body
+    );
+}
+
+/// Derives Deserialize, reading every field from the object's field with the same name.
+/// Numbers, booleans, and strings are read directly, and other fields are read into their current value.
+fn derive_deserialize(name: &str, generics: &[String], fields: &[(String, String)]) -> String {
+    let mut body = String::default();
+    for (field, types) in fields {
+        body += &match DESERIALIZED_PRIMITIVES.iter().find(|(primitive, _)| primitive == types) {
+            Some((_, method)) => format!("        self.{} = deserializer.field(\"{}\").{}();\n", field, field, method),
+            None => format!("        self.{}.deserialize(deserializer.field(\"{}\"));\n", field, field),
+        };
+    }
+    return format!(
+        "impl{} Deserialize for {} {{\n    pub fn deserialize(mut self, deserializer: Deserializer) {{\n{}    }}\n}}\n",
+        impl_generics(generics, "Deserialize"),
+        name,
+        body
+    );
+}
+
+/// Derives Debug, writing the struct's name and every field with its name, like Point { x: 1, y: 2 }
+fn derive_debug(name: &str, generics: &[String], fields: &[(String, String)]) -> String {
+    let short_name = name.split('<').next().unwrap();
+    let body = if fields.is_empty() {
+        format!("\"{}\"", short_name)
+    } else {
+        let fields = fields
+            .iter()
+            .map(|(field, _)| format!("\"{}: \" + self.{}.debug()", field, field))
+            .collect::<Vec<_>>()
+            .join(" + \", \" + ");
+        format!("\"{} {{ \" + {} + \" }}\"", short_name, fields)
+    };
+    return format!(
+        "impl{} Debug for {} {{\n    pub fn debug(self) -> str {{\n        return {};\n    }}\n}}\n",
+        impl_generics(generics, "Debug"),
+        name,
+        body
+    );
+}
+
+/// Derives Equal, with the structs equal if every field is
+fn derive_equal(name: &str, generics: &[String], fields: &[(String, String)]) -> String {
+    let body = if fields.is_empty() {
+        "true".to_string()
+    } else {
+        fields.iter().map(|(field, _)| format!("self.{} == other.{}", field, field)).collect::<Vec<_>>().join(" && ")
+    };
+    return format!(
+        "impl{} Equal<{}> for {} {{\n    pub fn equal(self, other: {}) -> bool {{\n        return {};\n    }}\n}}\n",
+        impl_generics(generics, "Equal<{}>"),
+        name,
+        name,
+        name,
+        body
+    );
+}
+
+/// Derives Compare, ordering the structs by their first field, then the next field if those are equal, and so on
+fn derive_compare(name: &str, generics: &[String], fields: &[(String, String)]) -> String {
+    let mut body = String::default();
+    for (field, _) in fields {
+        body += &format!(
+            "        if self.{0} < other.{0} {{\n            return Ordering::less();\n        }}\n        \
+            if self.{0} > other.{0} {{\n            return Ordering::greater();\n        }}\n",
+            field
+        );
+    }
+    return format!(
+        "impl{} Compare<{}> for {} {{\n    pub fn compare(self, other: {}) -> Ordering {{\n{}        \
+        return Ordering::equal();\n    }}\n}}\n",
+        impl_generics(generics, "LessThan<{}> + GreaterThan<{}>"),
+        name,
+        name,
+        name,
+        body
+    );
+}
+
+/// Derives Hash, writing every field into the hasher in the order they're declared
+fn derive_hash(name: &str, generics: &[String], fields: &[(String, String)]) -> String {
+    let mut body = String::default();
+    for (field, _) in fields {
+        body += &format!("        self.{}.hash(hasher);\n", field);
+    }
+    return format!(
+        "impl{} Hash for {} {{\n    pub fn hash(self, hasher: Hasher) {{\n{}    }}\n}}\n",
+        impl_generics(generics, "Hash"),
+        name,
+        body
+    );
+}
@@ -43,11 +43,29 @@ pub fn parse_function(
             TokenTypes::ArgumentName => last_arg = token.to_string(parser_utils.buffer),
             TokenTypes::ArgumentType => last_arg_type = token.to_string(parser_utils.buffer),
             TokenTypes::ArgumentEnd => {
+                // Structures are always passed by reference, so "&self" is the same receiver as "self"
+                if let Some(name) = last_arg.strip_prefix('&').filter(|_| last_arg_type.is_empty()) {
+                    last_arg = name.trim().to_string();
+                }
+                // Mutable arguments, like "mut value: u64", can be set in the function
+                let argument_modifiers = match last_arg.strip_prefix("mut ").map(|name| name.trim().to_string()) {
+                    Some(name) => {
+                        last_arg = name;
+                        Modifier::Mut as u8
+                    }
+                    None => 0,
+                };
                 if last_arg_type.is_empty() {
+                    let span = Span::new(parser_utils.file, parser_utils.index - 1);
                     if !parser_utils.imports.parent.is_some() {
-                        return Err(
-                            Span::new(parser_utils.file, parser_utils.index - 1).make_error(ParsingMessage::SelfInStatic())
-                        );
+                        return Err(span.make_error(ParsingMessage::SelfInStatic()));
+                    }
+                    // Only the receiver can leave out its type, and it has to come first
+                    if last_arg != "self" {
+                        return Err(span.make_error(ParsingMessage::MissingArgumentType(last_arg)));
+                    }
+                    if !fields.is_empty() {
+                        return Err(span.make_error(ParsingMessage::ReceiverNotFirst()));
                     }
 
                     fields.push(Box::pin(to_field(
@@ -59,14 +77,14 @@ pub fn parse_function(
                             vec![],
                         ),
                         Vec::default(),
-                        0,
+                        argument_modifiers,
                         last_arg,
                     )));
                 } else {
                     fields.push(Box::pin(to_field(
                         parser_utils.get_struct(&Span::new(parser_utils.file, parser_utils.index - 1), last_arg_type),
                         Vec::default(),
-                        0,
+                        argument_modifiers,
                         last_arg,
                     )));
                     last_arg_type = String::default();
@@ -4,6 +4,8 @@
 pub mod code_parser;
 /// Parser for control statements like if, for, while, etc...
 pub mod control_parser;
+/// Generates the impls of #[derive(...)] traits
+pub mod derive;
 /// Parser for functions
 pub mod function_parser;
 /// Parser for operators
@@ -48,6 +48,13 @@ pub fn parse_operator(
 
     if right.is_some() {
         while parser_utils.tokens.get(parser_utils.index - 1).unwrap().token_type == TokenTypes::ArgumentEnd {
+            // A trailing comma before the closing bracket, so close the last element like it had no comma
+            let next = &parser_utils.tokens[parser_utils.index];
+            if next.token_type == TokenTypes::Operator && next.to_string(parser_utils.buffer) == "]" {
+                parser_utils.index += 1;
+                right = Some(close_array(right.unwrap(), parser_utils)?);
+                break;
+            }
             (index, tokens) = (parser_utils.index.clone(), parser_utils.tokens.len());
             let mut next_element_token = Span::new(parser_utils.file, parser_utils.index);
             let next = parse_line(parser_utils, ParseState::InOperator)?.map(|inner| inner.effect);
@@ -122,3 +129,15 @@ pub fn parse_operator(
         span: Span::new(parser_utils.file, parser_utils.index),
     });
 }
+
+/// Closes an array literal that ended in a trailing comma by attaching the closing bracket to its last element
+fn close_array(array: Effects, parser_utils: &mut ParserUtils) -> Result<Effects, ParsingError> {
+    return Ok(match array.types {
+        EffectType::CreateArray(mut inner) => {
+            let last = inner.pop().unwrap();
+            inner.push(parse_operator(Some(last), parser_utils, &ParseState::InOperator)?);
+            Effects::new(array.span, EffectType::CreateArray(inner))
+        }
+        _ => parse_operator(Some(array), parser_utils, &ParseState::InOperator)?,
+    });
+}
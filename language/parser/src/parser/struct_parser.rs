@@ -11,6 +11,7 @@ use syntax::program::syntax::Syntax;
 use syntax::program::types::Types;
 use syntax::{get_modifier, is_modifier, Attribute, Modifier, ParsingFuture, TraitImplementor};
 
+use crate::parser::derive::derive;
 use crate::parser::function_parser::parse_function;
 use crate::parser::top_parser::{parse_attribute, parse_import, parse_modifier};
 use crate::parser::util::ParserUtils;
@@ -22,6 +23,8 @@ pub fn parse_structure(
     modifiers: Vec<Modifier>,
 ) -> Result<UnfinalizedStruct, ParsingError> {
     let modifiers = get_modifier(modifiers.as_slice());
+    // Disabled structs are still parsed to skip over them, but their functions aren't added to the program
+    let enabled = parser_utils.is_enabled(&attributes);
 
     let mut member_modifiers = Vec::default();
     let mut member_attributes = Vec::default();
@@ -31,6 +34,8 @@ pub fn parse_structure(
     let mut fields = Vec::default();
     let mut generics = IndexMap::default();
     let mut functions = Vec::default();
+    // The name and type of every enabled field as written, for #[derive(...)]
+    let mut declared = Vec::default();
     while parser_utils.tokens.len() != parser_utils.index {
         let token: &Token = parser_utils.tokens.get(parser_utils.index).unwrap();
         let token: Token = token.clone();
@@ -67,24 +72,35 @@ pub fn parse_structure(
                 } else {
                     parser_utils.file_name = format!("{}::{}", parser_utils.file_name, name);
                 }
+                let function_enabled = enabled && parser_utils.is_enabled(&member_attributes);
                 let function = parse_function(
                     parser_utils,
                     is_modifier(modifiers, Modifier::Trait),
                     member_attributes,
                     member_modifiers,
                 );
-                functions.push(ParserUtils::add_function(&parser_utils.syntax, parser_utils.file_name.clone(), function));
+                if function_enabled {
+                    let file_name = parser_utils.file_name.clone();
+                    functions.push(ParserUtils::add_function(&parser_utils.syntax, file_name, function));
+                }
                 parser_utils.file_name = file;
                 member_attributes = Vec::default();
                 member_modifiers = Vec::default();
             }
             TokenTypes::FieldName => {
-                fields.push(parse_field(
-                    parser_utils,
-                    token.to_string(parser_utils.buffer),
-                    member_attributes,
-                    member_modifiers,
-                ));
+                let field_enabled = parser_utils.is_enabled(&member_attributes);
+                let field_start = parser_utils.index;
+                let field_name = token.to_string(parser_utils.buffer);
+                let field = parse_field(parser_utils, field_name.clone(), member_attributes, member_modifiers);
+                if field_enabled {
+                    fields.push(field);
+                    if let Some(field_type) = parser_utils.tokens[field_start..parser_utils.index]
+                        .iter()
+                        .find(|found| found.token_type == TokenTypes::FieldType)
+                    {
+                        declared.push((field_name, field_type.to_string(parser_utils.buffer)));
+                    }
+                }
                 member_attributes = Vec::default();
                 member_modifiers = Vec::default();
             }
@@ -94,6 +110,13 @@ pub fn parse_structure(
         }
     }
 
+    // Functions after the structure aren't in it, so they can't take self
+    parser_utils.imports.parent = None;
+    if enabled {
+        let generic_names = generics.keys().cloned().collect::<Vec<_>>();
+        derive(parser_utils, &attributes, &start, &name, &generic_names, &declared);
+    }
+
     let data = if is_modifier(modifiers, Modifier::Internal) && !is_modifier(modifiers, Modifier::Trait) {
         get_internal(name)
     } else {
@@ -135,6 +158,8 @@ pub fn parse_implementor(
                 let name = token.to_string(parser_utils.buffer);
                 let temp = Some(UnparsedType::Basic(name.clone()));
                 if state == 0 {
+                    // Impls without a trait are on the base, so it's the parent of self
+                    parser_utils.imports.parent = temp.clone();
                     base = temp;
                     base_span = Some(Span::new(parser_utils.file, parser_utils.index - 1));
                     state = 1;
@@ -154,6 +179,7 @@ pub fn parse_implementor(
                     };
                     if state == 1 {
                         let found = UnparsedType::Generic(Box::new(base.unwrap()), type_generics);
+                        parser_utils.imports.parent = Some(found.clone());
                         base = Some(found);
                         base_span.as_mut().unwrap().extend_span(parser_utils.index - 1);
                     } else {
@@ -182,11 +208,14 @@ pub fn parse_implementor(
                 } else {
                     parser_utils.file_name = format!("{}::{}", parser_utils.file_name, base.as_ref().unwrap());
                 }
+                let function_enabled = parser_utils.is_enabled(&member_attributes);
                 let function = match parse_function(parser_utils, false, member_attributes, member_modifiers) {
                     Ok(inner) => inner,
                     Err(error) => return (Err(error), "error".to_string(), "error".to_string()),
                 };
-                functions.push(function);
+                if function_enabled {
+                    functions.push(function);
+                }
                 parser_utils.file_name = file;
                 member_attributes = Vec::default();
                 member_modifiers = Vec::default();
@@ -231,8 +260,15 @@ pub fn parse_implementor(
         None
     };
 
+    let mut span = base_span.clone().unwrap();
+    if let Some(implementor_span) = &implementor_span {
+        span.extend_span(implementor_span.end);
+    }
+
+    // Functions after the impl aren't in it, so they can't take self
+    parser_utils.imports.parent = None;
     return (
-        Ok(TraitImplementor { base: base_future, generics, implementor: implementor_future, functions, attributes }),
+        Ok(TraitImplementor { base: base_future, generics, implementor: implementor_future, functions, attributes, span }),
         base.unwrap().to_string(),
         implementor.map(|inner| inner.to_string()).unwrap_or("none".to_string()),
     );
@@ -287,6 +323,8 @@ pub fn parse_generics(parser_utils: &mut ParserUtils, generics: &mut IndexMap<St
                 generics.insert(name.clone(), bounds);
                 bounds = Vec::default();
                 unparsed_bounds = Vec::default();
+                // Cleared so a trailing comma doesn't add the last generic again
+                name = String::default();
             }
             TokenTypes::GenericBound => {
                 let token = parser_utils.tokens.get(parser_utils.index - 1).unwrap();
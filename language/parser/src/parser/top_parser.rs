@@ -28,7 +28,13 @@ pub fn parse_top(parser_utils: &mut ParserUtils) {
             TokenTypes::AttributesStart => parse_attribute(parser_utils, &mut attributes),
             TokenTypes::ModifiersStart => parse_modifier(parser_utils, &mut modifiers),
             TokenTypes::FunctionStart => {
+                let enabled = parser_utils.is_enabled(&attributes);
                 let function = parse_function(parser_utils, false, attributes, modifiers);
+                if !enabled {
+                    attributes = vec![];
+                    modifiers = vec![];
+                    continue;
+                }
                 let function = ParserUtils::add_function(&parser_utils.syntax, parser_utils.file_name.clone(), function);
                 let process_manager = parser_utils.syntax.lock().process_manager.cloned();
                 parser_utils.handle.lock().spawn(
@@ -46,20 +52,32 @@ pub fn parse_top(parser_utils: &mut ParserUtils) {
                 modifiers = vec![];
             }
             TokenTypes::StructStart => {
+                let enabled = parser_utils.is_enabled(&attributes);
                 let structure = parse_structure(parser_utils, attributes, modifiers);
-                parser_utils.add_struct(structure);
+                if enabled {
+                    parser_utils.add_struct(structure);
+                }
                 attributes = vec![];
                 modifiers = vec![];
             }
             TokenTypes::TraitStart => {
                 modifiers.push(Modifier::Trait);
+                let enabled = parser_utils.is_enabled(&attributes);
                 let structure = parse_structure(parser_utils, attributes, modifiers);
-                parser_utils.add_struct(structure);
+                if enabled {
+                    parser_utils.add_struct(structure);
+                }
                 attributes = Vec::default();
                 modifiers = Vec::default();
             }
             TokenTypes::ImplStart => {
+                let enabled = parser_utils.is_enabled(&attributes);
                 let (trait_implementor, base, implementor) = parse_implementor(parser_utils, attributes, modifiers);
+                if !enabled {
+                    attributes = Vec::default();
+                    modifiers = Vec::default();
+                    continue;
+                }
                 let process_manager = {
                     let mut locked = parser_utils.syntax.lock();
                     locked.async_manager.parsing_impls += 1;
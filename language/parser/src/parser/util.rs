@@ -3,15 +3,18 @@ use std::sync::Arc;
 
 use indexmap::IndexMap;
 
+use data::stats;
 use data::tokens::{Span, Token, TokenTypes};
 use syntax::async_util::{HandleWrapper, NameResolver, UnparsedType};
 use syntax::errors::ParsingError;
+use syntax::program::code::Expression;
 use syntax::program::function::{CodeBody, FunctionData, UnfinalizedFunction};
 use syntax::program::r#struct::{StructData, UnfinalizedStruct};
 use syntax::program::syntax::Syntax;
 use syntax::program::types::{FinalizedTypes, Types};
 use syntax::{
-    FinishedStructImplementor, FinishedTraitImplementor, ParsingFuture, ProcessManager, TopElement, TraitImplementor,
+    Attribute, FinishedStructImplementor, FinishedTraitImplementor, ParsingFuture, ProcessManager, TopElement,
+    TraitImplementor,
 };
 
 use crate::ImportNameResolver;
@@ -34,9 +37,28 @@ pub struct ParserUtils<'a> {
     pub imports: ImportNameResolver,
     /// Handle for spawning async tasks
     pub handle: Arc<Mutex<HandleWrapper>>,
+    /// The parsed body of every defer that's in scope, innermost last
+    pub defers: Vec<Expression>,
+    /// Every loop the code being parsed is in, innermost last
+    pub loops: Vec<LoopLabels>,
+}
+
+/// Where a break or continue inside a loop jumps to
+pub struct LoopLabels {
+    /// The label a break jumps to, right after the loop
+    pub break_label: String,
+    /// The label a continue jumps to, which checks whether to run the loop again
+    pub continue_label: String,
+    /// The amount of defers in scope when the loop started, the ones after it run before leaving an iteration
+    pub defers: usize,
 }
 
 impl<'a> ParserUtils<'a> {
+    /// Checks if the element with the attributes is enabled by its #[cfg(...)] attributes
+    pub fn is_enabled(&self, attributes: &Vec<Attribute>) -> bool {
+        return Attribute::is_cfg_enabled(attributes, &self.syntax.lock().features);
+    }
+
     /// Returns a future for getting a struct given its name
     pub fn get_struct(&self, span: &Span, name: String) -> ParsingFuture<Types> {
         if name.is_empty() {
@@ -149,11 +171,13 @@ impl<'a> ParserUtils<'a> {
                 functions,
                 chalk_type,
                 generics,
+                span: implementor.span,
             };
 
             {
                 let mut locked = syntax.lock();
-                locked.implementations.push(Arc::new(output));
+                locked.add_implementation(Arc::new(output));
+                stats::PARSED_IMPLS.add();
 
                 locked.async_manager.parsing_impls -= 1;
                 for waker in &locked.async_manager.impl_waiters {
@@ -0,0 +1,293 @@
+use std::collections::HashSet;
+
+use data::tokens::{Span, Token, TokenTypes};
+use data::{Readable, SourceSet};
+use syntax::program::syntax::Syntax;
+use syntax::TopElement;
+
+/// The different ways a definition can be referenced
+#[derive(Clone, Debug, PartialEq)]
+pub enum ReferenceType {
+    /// The definition itself, like the name after "struct" or "fn"
+    Definition,
+    /// A plain usage of the name
+    Name,
+    /// A usage prefixed by its module path, like math::Add
+    Qualified,
+    /// An operator implicitly calling the definition through its #[operation] attribute
+    Operator,
+}
+
+/// A single usage of a definition in a file
+#[derive(Clone, Debug)]
+pub struct Reference {
+    /// The path of the file the reference is in
+    pub file: String,
+    /// The type of reference
+    pub reference_type: ReferenceType,
+    /// The starting line and UTF-16 column in that line of the name, like LSP positions.
+    pub start: (u32, u32),
+    /// The offset to the start of the name
+    pub start_offset: usize,
+    /// The ending line and UTF-16 column in that line of the name.
+    pub end: (u32, u32),
+    /// The offset to the end of the name
+    pub end_offset: usize,
+}
+
+/// A whole identifier in a file that may reference a definition
+struct Name {
+    /// The index of the token the name is in
+    token: usize,
+    /// The offset to the start of the name
+    start: usize,
+    /// The offset to the end of the name
+    end: usize,
+    /// The name with the path written before it, like math::Add
+    path: String,
+    /// Whether the name is called, like foo(), which the checker resolves
+    called: bool,
+    /// The type of reference, if it is one
+    reference_type: ReferenceType,
+}
+
+/// Finds every usage of the definition, given its full name like math::Add, in the source sets.
+/// Each name is resolved with the checked syntax, so a different definition with the same name isn't a reference:
+/// calls are resolved to the function the checker found (see Syntax::call_sites), since which method is called
+/// depends on the type it's called on, and other names are resolved through the file's imports like the checker does.
+/// Only the name part of each reference is spanned (the "Add" in "math::Add"), so renaming is a simple replace.
+/// Operator references can't be renamed, but are returned so callers can warn about them.
+pub fn find_references(sources: &Vec<Box<dyn SourceSet>>, syntax: &Syntax, definition: &str) -> Vec<Reference> {
+    let name = definition.rsplit("::").next().unwrap().as_bytes();
+    let operations = syntax
+        .operations
+        .iter()
+        .filter(|(_, found)| found.name == definition)
+        .map(|(operation, _)| operation.replace("{}", "").replace("{+}", ""))
+        .collect::<Vec<_>>();
+
+    let mut output = Vec::default();
+    for file in source_files(sources) {
+        let contents = file.contents();
+        let buffer = contents.as_bytes();
+        let tokens = file.read();
+        for found in find_names(buffer, &tokens, |start, end| &buffer[start..end] == name) {
+            if resolve(syntax, file.hash(), &found).is_some_and(|resolved| resolved == definition) {
+                output.push(make_reference(&*file, buffer, found.reference_type, found.start, found.end));
+            }
+        }
+        find_operators(&*file, buffer, &tokens, &operations, &mut output);
+    }
+    return output;
+}
+
+/// Finds the full name of the definition the name at the offset in the file refers to, like math::Add
+pub fn definition_at(sources: &Vec<Box<dyn SourceSet>>, syntax: &Syntax, path: &str, offset: usize) -> Option<String> {
+    let file = source_files(sources).into_iter().find(|file| file.path() == path)?;
+    let contents = file.contents();
+    // The cursor can be at either end of the name
+    let found = find_names(contents.as_bytes(), &file.read(), |start, end| start <= offset && offset <= end);
+    return resolve(syntax, file.hash(), found.first()?);
+}
+
+/// Gets every Raven file in the source sets
+fn source_files(sources: &Vec<Box<dyn SourceSet>>) -> Vec<Box<dyn Readable>> {
+    return sources.iter().flat_map(|source| source.get_files()).filter(|file| file.path().ends_with("rv")).collect();
+}
+
+/// Resolves a name in the file to the full name of the struct or function it refers to
+fn resolve(syntax: &Syntax, file: u64, name: &Name) -> Option<String> {
+    let at_name = |span: &Span| span.file == file && span.start == name.token;
+    if name.reference_type == ReferenceType::Definition {
+        let structures = syntax.structures.types.values().map(|found| (found.name(), found.get_span()));
+        let functions = syntax.functions.types.values().map(|found| (found.name(), found.get_span()));
+        return structures.chain(functions).find(|(_, span)| at_name(span)).map(|(found, _)| found.clone());
+    }
+    if name.called {
+        if let Some((found, _)) = syntax.call_sites.iter().flatten().find(|(_, spans)| spans.iter().any(at_name)) {
+            return Some(found.clone());
+        }
+    }
+    return syntax.resolve_name(file, &name.path);
+}
+
+/// Finds every whole identifier that the filter accepts, given its start and end offsets, in the tokens that can
+/// reference a definition, like the "math" and "Add" in "math::Add<T>".
+/// Arguments and variables declared with let or for shadow definitions with the same name for the rest of the function,
+/// so they and their uses aren't names of a definition.
+fn find_names(buffer: &[u8], tokens: &Vec<Token>, filter: impl Fn(usize, usize) -> bool) -> Vec<Name> {
+    let mut output = Vec::default();
+    let mut last = &TokenTypes::Start;
+    let mut locals: HashSet<&[u8]> = HashSet::default();
+    for (index, token) in tokens.iter().enumerate() {
+        if token.token_type == TokenTypes::FunctionStart {
+            locals.clear();
+        }
+        if matches!(
+            token.token_type,
+            TokenTypes::Identifier
+                | TokenTypes::Variable
+                | TokenTypes::CallingType
+                | TokenTypes::ArgumentName
+                | TokenTypes::ArgumentType
+                | TokenTypes::ReturnType
+                | TokenTypes::FieldName
+                | TokenTypes::FieldType
+                | TokenTypes::Generic
+                | TokenTypes::GenericBound
+        ) {
+            let definition = matches!(last, TokenTypes::StructStart | TokenTypes::TraitStart | TokenTypes::FunctionStart);
+            let (start, end) = trimmed(buffer, token);
+            // The "mut" in "let mut value" is its own token, so the variable is the token after it
+            let declaring = token.token_type == TokenTypes::ArgumentName
+                || matches!(last, TokenTypes::Let | TokenTypes::For)
+                || (*last == TokenTypes::Variable && is_let_mut(buffer, tokens, index));
+            let mut word = start;
+            while word < end {
+                if !is_identifier(buffer[word]) {
+                    word += 1;
+                    continue;
+                }
+                let mut word_end = word;
+                while word_end < end && is_identifier(buffer[word_end]) {
+                    word_end += 1;
+                }
+                let text = &buffer[word..word_end];
+                let plain = word == start && word_end == end && token.token_type == TokenTypes::Variable;
+                if declaring && text != b"mut" {
+                    locals.insert(text);
+                    word = word_end;
+                    continue;
+                }
+                let called =
+                    word_end == end && tokens.get(index + 1).is_some_and(|next| next.token_type == TokenTypes::ParenOpen);
+                if plain && !called && locals.contains(text) {
+                    word = word_end;
+                    continue;
+                }
+                if filter(word, word_end) {
+                    // The path before the name, like the "math::" in "math::Add"
+                    let mut path_start = word;
+                    while path_start >= start + 2 && &buffer[path_start - 2..path_start] == b"::" {
+                        path_start -= 2;
+                        while path_start > start && is_identifier(buffer[path_start - 1]) {
+                            path_start -= 1;
+                        }
+                    }
+                    let reference_type = if definition {
+                        ReferenceType::Definition
+                    } else if path_start < word {
+                        ReferenceType::Qualified
+                    } else {
+                        ReferenceType::Name
+                    };
+                    output.push(Name {
+                        token: index,
+                        start: word,
+                        end: word_end,
+                        path: String::from_utf8_lossy(&buffer[path_start..word_end]).to_string(),
+                        called,
+                        reference_type,
+                    });
+                }
+                word = word_end;
+            }
+        }
+        if token.token_type != TokenTypes::Comment {
+            last = &token.token_type;
+        }
+    }
+    return output;
+}
+
+/// Whether the token is the variable in "let mut value", which comes after the "mut" token
+fn is_let_mut(buffer: &[u8], tokens: &Vec<Token>, index: usize) -> bool {
+    if index < 2 || tokens[index - 2].token_type != TokenTypes::Let {
+        return false;
+    }
+    let (start, end) = trimmed(buffer, &tokens[index - 1]);
+    return &buffer[start..end] == b"mut";
+}
+
+/// Finds every operator calling one of the operations, which are written without their arguments like "+"
+fn find_operators(
+    file: &dyn Readable,
+    buffer: &[u8],
+    tokens: &Vec<Token>,
+    operations: &Vec<String>,
+    output: &mut Vec<Reference>,
+) {
+    if operations.is_empty() {
+        return;
+    }
+    let mut i = 0;
+    while i < tokens.len() {
+        if matches!(tokens[i].token_type, TokenTypes::Operator | TokenTypes::Equals) {
+            // Multi-character operators are split into one token per character, so the run has to be joined
+            let (start, _) = trimmed(buffer, &tokens[i]);
+            let mut end = tokens[i].end_offset;
+            while i + 1 < tokens.len()
+                && matches!(tokens[i + 1].token_type, TokenTypes::Operator | TokenTypes::Equals)
+                && trimmed(buffer, &tokens[i + 1]).0 == end
+            {
+                i += 1;
+                end = tokens[i].end_offset;
+            }
+            let operator = String::from_utf8_lossy(&buffer[start..end]);
+            if operations.iter().any(|operation| *operation == operator) {
+                output.push(make_reference(file, buffer, ReferenceType::Operator, start, end));
+            }
+        }
+        i += 1;
+    }
+}
+
+/// Whether the byte can be part of an identifier. Any byte of a multi-byte character counts, since identifiers
+/// can contain non-ASCII characters
+fn is_identifier(character: u8) -> bool {
+    return character.is_ascii_alphanumeric() || character == b'_' || !character.is_ascii();
+}
+
+/// Gets the offsets of the token without any surrounding whitespace
+fn trimmed(buffer: &[u8], token: &Token) -> (usize, usize) {
+    let mut start = token.start_offset;
+    let mut end = token.end_offset;
+    while start < end && buffer[start].is_ascii_whitespace() {
+        start += 1;
+    }
+    while end > start && buffer[end - 1].is_ascii_whitespace() {
+        end -= 1;
+    }
+    return (start, end);
+}
+
+/// Creates a reference, finding the line and column of the offsets
+fn make_reference(
+    file: &dyn Readable,
+    buffer: &[u8],
+    reference_type: ReferenceType,
+    start_offset: usize,
+    end_offset: usize,
+) -> Reference {
+    return Reference {
+        file: file.path(),
+        reference_type,
+        start: position(buffer, start_offset),
+        start_offset,
+        end: position(buffer, end_offset),
+        end_offset,
+    };
+}
+
+/// Gets the line and UTF-16 column in that line of the offset, which is how LSP positions count columns
+fn position(buffer: &[u8], offset: usize) -> (u32, u32) {
+    let mut line = 1;
+    let mut line_start = 0;
+    for (i, character) in buffer[..offset].iter().enumerate() {
+        if *character == b'\n' {
+            line += 1;
+            line_start = i + 1;
+        }
+    }
+    return (line, String::from_utf8_lossy(&buffer[line_start..offset]).encode_utf16().count() as u32);
+}
@@ -0,0 +1,152 @@
+use data::tokens::{Token, TokenTypes};
+use syntax::MODIFIERS;
+
+use crate::tokens::tokenizer::{Tokenizer, TokenizerState};
+use crate::tokens::top_tokenizer::TOP_KEYWORDS;
+
+/// The statements that end at their block instead of a semicolon
+static BLOCK_STATEMENTS: [&str; 5] = ["if", "for", "while", "do", "defer"];
+
+/// Wraps the top-level statements of a script into an implicit main function, so a file like a quick script
+/// doesn't need any boilerplate. Files that already have a main function or have no top-level statements
+/// are returned unchanged.
+/// The statements keep their lines so errors in them point to the right place, and the functions, structs,
+/// and other top elements are moved after main.
+pub fn wrap_script(source: &str) -> String {
+    let chunks = split_top_level(source);
+    if !chunks.iter().any(|chunk| !chunk.element) || chunks.iter().any(|chunk| chunk.element && is_main(chunk.text)) {
+        return source.to_string();
+    }
+
+    // The shebang line stays first so the tokenizer skips it
+    let mut last = shebang_end(source.as_bytes());
+    let mut statements = format!("{}fn main() {{", &source[..last]);
+    let mut elements = String::default();
+    for chunk in &chunks {
+        statements += &source[last..chunk.start];
+        if chunk.element {
+            // Blanked out to keep the lines of the statements after it
+            statements +=
+                &chunk.text.chars().map(|character| if character == '\n' { '\n' } else { ' ' }).collect::<String>();
+            elements += chunk.text;
+            elements += "\n";
+        } else {
+            statements += chunk.text;
+        }
+        last = chunk.start + chunk.text.len();
+    }
+    statements += &source[last..];
+    return format!("{}\n}}\n{}", statements, elements);
+}
+
+/// A top-level top element or statement in a script
+struct Chunk<'a> {
+    /// The byte the chunk starts at in the source
+    start: usize,
+    /// The chunk's text
+    text: &'a str,
+    /// Whether the chunk is a top element like a function, or a statement otherwise
+    element: bool,
+}
+
+/// Splits a script into its top elements and statements. Comments between them aren't part of any chunk.
+fn split_top_level(source: &str) -> Vec<Chunk> {
+    let tokens = tokenize(source);
+    // Tokens start at the end of the last one, so their text has the whitespace before them
+    let text = |token: &Token| source[token.start_offset..token.end_offset].trim_start();
+    let mut chunks = Vec::default();
+    let mut index = 0;
+    while index < tokens.len() {
+        let first = text(&tokens[index]);
+        let start = tokens[index].end_offset - first.len();
+        let element = first == "#" || is_element_word(first);
+        let block = !element && (BLOCK_STATEMENTS.contains(&first) || first == "{");
+
+        // Depth of (), [], and {}
+        let mut depth = 0;
+        while index < tokens.len() {
+            let token = &tokens[index];
+            index += 1;
+            match token.token_type {
+                TokenTypes::ParenOpen | TokenTypes::BlockStart => depth += 1,
+                TokenTypes::ParenClose => depth -= 1,
+                TokenTypes::BlockEnd | TokenTypes::CodeEnd => {
+                    depth -= 1;
+                    if depth == 0 && (element || block) {
+                        // Else blocks and do while loops continue the statement
+                        let next = tokens.get(index).map_or("", text);
+                        if element || !(next == "else" || (next == "while" && first == "do")) {
+                            break;
+                        }
+                    }
+                }
+                TokenTypes::LineEnd if depth == 0 => break,
+                TokenTypes::Operator => match text(token) {
+                    "[" => depth += 1,
+                    "]" => depth -= 1,
+                    _ => {}
+                },
+                _ => {}
+            }
+        }
+        let end = tokens[index - 1].end_offset;
+        chunks.push(Chunk { start, text: &source[start..end], element });
+    }
+    return chunks;
+}
+
+/// Tokenizes the whole script as code, without the comments
+fn tokenize(source: &str) -> Vec<Token> {
+    let mut tokenizer = Tokenizer::new(source.as_bytes());
+    tokenizer.state = TokenizerState::CODE;
+    let mut tokens = Vec::default();
+    loop {
+        let token = tokenizer.next();
+        match token.token_type {
+            TokenTypes::EOF => return tokens,
+            TokenTypes::Comment => {}
+            _ => tokens.push(token),
+        }
+        // An extra } ends the code, but everything in a script is tokenized as code
+        if tokenizer.state == TokenizerState::TOP_ELEMENT {
+            tokenizer.state = TokenizerState::CODE;
+        }
+    }
+}
+
+/// Checks if the word starts a top element, like "fn" or "pub"
+fn is_element_word(word: &str) -> bool {
+    // "for" is also used by impls, but at the top level of a script it's a loop
+    return (word != "for" && TOP_KEYWORDS.contains(&word))
+        || MODIFIERS.iter().any(|modifier| modifier.to_string().split('(').next().unwrap() == word);
+}
+
+/// Checks if a top element is the main function
+fn is_main(element: &str) -> bool {
+    let mut element = element.trim_start();
+    // Skip the attributes, which are always first
+    while element.starts_with("#[") {
+        element = element[element.find(']').map(|end| end + 1).unwrap_or(element.len())..].trim_start();
+    }
+    let mut words = element.split(|character: char| !(character.is_alphanumeric() || character == '_' || character == '('));
+    let mut word = words.find(|word| !word.is_empty());
+    while word.is_some_and(|word| word != "fn" && is_element_word(word.split('(').next().unwrap())) {
+        word = words.find(|word| !word.is_empty());
+    }
+    return word == Some("fn")
+        && words.find(|word| !word.is_empty()).is_some_and(|word| word.split('(').next() == Some("main"));
+}
+
+/// Returns the index after the "#!/usr/bin/env magpie" line and its newline at the start of a script, or 0 if there isn't one
+fn shebang_end(bytes: &[u8]) -> usize {
+    // The newline is kept with it, or main would be part of the shebang's comment
+    return if bytes.starts_with(b"#!") { (skip_line(bytes, 0) + 1).min(bytes.len()) } else { 0 };
+}
+
+/// Returns the index of the end of the line
+fn skip_line(bytes: &[u8], mut index: usize) -> usize {
+    while index < bytes.len() && bytes[index] != b'\n' {
+        index += 1;
+    }
+    return index;
+}
@@ -1,12 +1,16 @@
 use crate::tokens::tokenizer::{Tokenizer, TokenizerState};
-use crate::tokens::util::{parse_acceptable, parse_numbers};
+use crate::tokens::util::{is_identifier_start, parse_acceptable, parse_numbers, skip_escape};
 use data::tokens::{Token, TokenTypes};
 
 /// Gets the next token in a block of code.
 pub fn next_code_token(tokenizer: &mut Tokenizer) -> Token {
     return if let Some(found) = check_keywords(tokenizer) {
         found
-    } else if TokenTypes::Period == tokenizer.last.token_type && tokenizer.buffer[tokenizer.index].is_ascii_alphabetic() {
+    } else if TokenTypes::Period == tokenizer.last.token_type
+        // Two periods are a range or a struct update like ..other, which is a variable and not a field
+        && tokenizer.buffer[..tokenizer.last.end_offset - 1].last() != Some(&b'.')
+        && tokenizer.char_at(tokenizer.index).is_some_and(is_identifier_start)
+    {
         parse_acceptable(tokenizer, TokenTypes::CallingType)
     } else if tokenizer.matches("{") {
         tokenizer.bracket_depth += 1;
@@ -42,7 +46,11 @@ pub fn next_code_token(tokenizer: &mut Tokenizer) -> Token {
         };
         tokenizer.make_token(TokenTypes::StringStart)
     } else if tokenizer.matches("'") {
-        tokenizer.index += 1;
+        if tokenizer.buffer.get(tokenizer.index) == Some(&b'\\') {
+            skip_escape(tokenizer);
+        } else {
+            tokenizer.index += tokenizer.char_at(tokenizer.index).map_or(1, char::len_utf8);
+        }
         if tokenizer.matches("'") {
             tokenizer.make_token(TokenTypes::Char)
         } else {
@@ -52,20 +60,52 @@ pub fn next_code_token(tokenizer: &mut Tokenizer) -> Token {
         let found = tokenizer.next_included()?;
         if tokenizer.matches("//") {
             tokenizer.parse_to_line_end(TokenTypes::Comment)
-        } else if (found as char).is_alphabetic() || found == b'_' {
-            // A character or an underscore is a variable.
+        } else if let Some(start) = tokenizer.char_at(tokenizer.index - 1).filter(|start| is_identifier_start(*start)) {
+            // A character or an underscore is a variable. Only its first byte was read, so the rest of it is skipped.
+            tokenizer.index += start.len_utf8() - 1;
             let temp = parse_acceptable(tokenizer, TokenTypes::Variable);
+            // llvm! and asm! are written like macros, so the ! is part of their name
+            if INLINE_CODE.contains(&temp.to_string(tokenizer.buffer).as_str())
+                && tokenizer.buffer.get(tokenizer.index) == Some(&b'!')
+                && matches!(tokenizer.buffer.get(tokenizer.index + 1), Some(b'(') | Some(b'<'))
+            {
+                tokenizer.index += 1;
+                return tokenizer.make_token(TokenTypes::Variable);
+            }
             temp
         } else if found >= b'0' && found <= b'9' {
             // A number is a number.
             parse_numbers(tokenizer)
         } else {
-            // Everything else is an operator.
+            // Everything else is an operator, which shouldn't split a multi-byte character.
+            tokenizer.index += tokenizer.char_at(tokenizer.index - 1).map_or(1, char::len_utf8) - 1;
             tokenizer.make_token(TokenTypes::Operator)
         }
     };
 }
 
+/// The names of the macros that splice raw code into unsafe functions
+pub static INLINE_CODE: [&str; 2] = ["llvm", "asm"];
+
+/// Every keyword in a block of code and the token it's turned into
+pub static CODE_KEYWORDS: [(&str, TokenTypes); 15] = [
+    ("return", TokenTypes::Return),
+    ("break", TokenTypes::Break),
+    ("continue", TokenTypes::Continue),
+    ("switch", TokenTypes::Switch),
+    ("true", TokenTypes::True),
+    ("false", TokenTypes::False),
+    ("for", TokenTypes::For),
+    ("while", TokenTypes::While),
+    ("new", TokenTypes::New),
+    ("if", TokenTypes::If),
+    ("do", TokenTypes::Do),
+    ("else", TokenTypes::Else),
+    ("in", TokenTypes::In),
+    ("let", TokenTypes::Let),
+    ("defer", TokenTypes::Defer),
+];
+
 /// Seperatae function to check basic keywords to tokenize
 pub fn check_keywords(tokenizer: &mut Tokenizer) -> Option<Token> {
     return Some(if tokenizer.matches(";") {
@@ -76,34 +116,10 @@ pub fn check_keywords(tokenizer: &mut Tokenizer) -> Option<Token> {
         tokenizer.make_token(TokenTypes::ParenOpen)
     } else if tokenizer.matches(")") {
         tokenizer.make_token(TokenTypes::ParenClose)
-    } else if tokenizer.matches_word("return") {
-        tokenizer.make_token(TokenTypes::Return)
-    } else if tokenizer.matches_word("break") {
-        tokenizer.make_token(TokenTypes::Break)
-    } else if tokenizer.matches_word("switch") {
-        tokenizer.make_token(TokenTypes::Switch)
-    } else if tokenizer.matches_word("true") {
-        tokenizer.make_token(TokenTypes::True)
-    } else if tokenizer.matches_word("false") {
-        tokenizer.make_token(TokenTypes::False)
-    } else if tokenizer.matches_word("for") {
-        tokenizer.make_token(TokenTypes::For)
-    } else if tokenizer.matches_word("while") {
-        tokenizer.make_token(TokenTypes::While)
-    } else if tokenizer.matches_word("new") {
-        tokenizer.make_token(TokenTypes::New)
-    } else if tokenizer.matches_word("if") {
-        tokenizer.make_token(TokenTypes::If)
-    } else if tokenizer.matches_word("do") {
-        tokenizer.make_token(TokenTypes::Do)
-    } else if tokenizer.matches_word("else") {
-        tokenizer.make_token(TokenTypes::Else)
-    } else if tokenizer.matches_word("in") {
-        tokenizer.make_token(TokenTypes::In)
+    } else if let Some((_, token_type)) = CODE_KEYWORDS.iter().find(|(keyword, _)| tokenizer.matches_word(keyword)) {
+        tokenizer.make_token(token_type.clone())
     } else if tokenizer.matches(":") {
         tokenizer.make_token(TokenTypes::Colon)
-    } else if tokenizer.matches_word("let") {
-        tokenizer.make_token(TokenTypes::Let)
     } else if tokenizer.matches("=") {
         tokenizer.make_token(TokenTypes::Equals)
     } else {
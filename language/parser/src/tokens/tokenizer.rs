@@ -1,7 +1,8 @@
 use crate::tokens::code_tokenizer::next_code_token;
 use crate::tokens::top_tokenizer::{next_func_token, next_implementation_token, next_struct_token, next_top_token};
-use crate::tokens::util::{next_generic, parse_string};
+use crate::tokens::util::{is_identifier_continue, next_generic, parse_string};
 use data::tokens::{Token, TokenTypes};
+use std::str;
 
 /// This program keeps track of the variables required for the tokenizing.
 pub struct Tokenizer<'a> {
@@ -64,7 +65,12 @@ impl<'a> Tokenizer<'a> {
 
     /// Gets the next token in the file
     pub fn next(&mut self) -> Token {
-        if self.matches("//") {
+        // A "#!/usr/bin/env magpie" line at the start of the file lets scripts be run directly
+        if self.index == 0 && self.buffer.starts_with(b"#!") {
+            self.parse_to_line_end(TokenTypes::Comment);
+            self.last = self.make_token(TokenTypes::Comment);
+            return self.last.clone();
+        } else if self.matches("//") {
             self.parse_to_line_end(TokenTypes::Comment);
             self.last = self.make_token(TokenTypes::Comment);
             return self.last.clone();
@@ -143,7 +149,7 @@ impl<'a> Tokenizer<'a> {
                 return false;
             }
         }
-        return if !self.buffer[self.index].is_ascii_alphabetic() {
+        return if !self.char_at(self.index).is_some_and(is_identifier_continue) {
             true
         } else {
             self.load(&state);
@@ -199,6 +205,17 @@ impl<'a> Tokenizer<'a> {
         return self.parse_to_line_end(TokenTypes::InvalidCharacters);
     }
 
+    /// Decodes the UTF-8 character starting at the index, or None if there isn't a valid one
+    pub fn char_at(&self, index: usize) -> Option<char> {
+        let bytes = self.buffer.get(index..(index + 4).min(self.len))?;
+        let valid = match str::from_utf8(bytes) {
+            Ok(valid) => valid,
+            // Only the first character matters, so ignore anything invalid after it
+            Err(error) => str::from_utf8(&bytes[..error.valid_up_to()]).unwrap(),
+        };
+        return valid.chars().next();
+    }
+
     /// Creates a token between the last token and the current position
     pub fn make_token(&self, token_type: TokenTypes) -> Token {
         return Token::new(
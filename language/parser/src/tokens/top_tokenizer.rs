@@ -2,6 +2,9 @@ use crate::tokens::tokenizer::{Tokenizer, TokenizerState};
 use crate::tokens::util::{parse_attribute_val, parse_modifier, parse_to_character};
 use data::tokens::{Token, TokenTypes};
 
+/// Every keyword outside of a block of code, must be kept in sync with next_top_token and get_top_element
+pub static TOP_KEYWORDS: [&str; 6] = ["import", "fn", "struct", "trait", "impl", "for"];
+
 /// Handles when the tokenizer isn't in any other state.
 pub fn next_top_token(tokenizer: &mut Tokenizer) -> Token {
     if tokenizer.index == tokenizer.len {
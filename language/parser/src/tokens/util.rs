@@ -34,14 +34,50 @@ pub fn parse_acceptable(tokenizer: &mut Tokenizer, token_type: TokenTypes) -> To
         if tokenizer.index == tokenizer.len {
             return tokenizer.make_token(TokenTypes::EOF);
         }
-        let character = tokenizer.buffer[tokenizer.index] as char;
-        if !character.is_alphanumeric() && character != ':' && character != '_' {
+        let character = tokenizer.char_at(tokenizer.index).unwrap_or(' ');
+        if !is_identifier_continue(character) && character != ':' {
             if tokenizer.buffer[tokenizer.index - 1] == b':' {
                 tokenizer.index -= 1;
             }
             return tokenizer.make_token(token_type);
         }
-        tokenizer.index += 1;
+        tokenizer.index += character.len_utf8();
+    }
+}
+
+/// Checks if a character can start an identifier, which follows UAX #31 with underscores also allowed
+pub fn is_identifier_start(character: char) -> bool {
+    return character == '_' || unicode_ident::is_xid_start(character);
+}
+
+/// Checks if a character can be part of an identifier after the first character, following UAX #31
+pub fn is_identifier_continue(character: char) -> bool {
+    return unicode_ident::is_xid_continue(character);
+}
+
+/// Skips past an escape sequence starting at the tokenizer's index, like \n, \x41 or \u{1F600}.
+/// Invalid escapes are still skipped, so the parser can report them, but never past the end of the literal.
+pub fn skip_escape(tokenizer: &mut Tokenizer) {
+    // Skip the \ and the character after it
+    tokenizer.index = (tokenizer.index + 2).min(tokenizer.len);
+    match tokenizer.buffer[tokenizer.index - 1] {
+        // Hex escapes are up to two more hex digits
+        b'x' => {
+            let end = (tokenizer.index + 2).min(tokenizer.len);
+            while tokenizer.index < end && tokenizer.buffer[tokenizer.index].is_ascii_hexdigit() {
+                tokenizer.index += 1;
+            }
+        }
+        // Unicode escapes go until the closing bracket
+        b'u' if tokenizer.buffer.get(tokenizer.index) == Some(&b'{') => {
+            while tokenizer.index < tokenizer.len && !b"}\"'\n".contains(&tokenizer.buffer[tokenizer.index]) {
+                tokenizer.index += 1;
+            }
+            if tokenizer.buffer.get(tokenizer.index) == Some(&b'}') {
+                tokenizer.index += 1;
+            }
+        }
+        _ => {}
     }
 }
 
@@ -123,16 +159,10 @@ pub fn parse_string(tokenizer: &mut Tokenizer) -> Token {
                 };
             }
             b'\\' => {
-                // if it is a hex value, then increment the tokenizer by an extra 2 because
-                // the escape character is 4 characters long instead of 2 (ex. \xAA)
-                if tokenizer.buffer[tokenizer.index] == b'x' {
-                    tokenizer.index += 2;
-                }
-
-                // increment the tokenizer so that it includes the \
-                // if you didn't do this, then the character being escaped (ex. n or t or r)
-                //   would be included in the string
-                tokenizer.index += 1;
+                // include the whole escape in the token, so the escaped characters (ex. n, t, or x41)
+                //   aren't read as part of the string
+                tokenizer.index -= 1;
+                skip_escape(tokenizer);
 
                 return tokenizer.make_token(TokenTypes::StringEscape);
             }
@@ -145,7 +175,12 @@ pub fn parse_string(tokenizer: &mut Tokenizer) -> Token {
 pub fn next_generic(tokenizer: &mut Tokenizer) -> Token {
     return match &tokenizer.last.token_type {
         TokenTypes::GenericsStart | TokenTypes::GenericEnd => {
-            parse_to_character(tokenizer, TokenTypes::Generic, &[b':', b',', b'>', b'<'])
+            // A trailing comma before the closing bracket
+            if tokenizer.last.token_type == TokenTypes::GenericEnd && tokenizer.matches(">") {
+                end_generic(tokenizer)
+            } else {
+                parse_to_character(tokenizer, TokenTypes::Generic, &[b':', b',', b'>', b'<'])
+            }
         }
         //              T       : Test       <             Other   <             Second  >               >               ,          E       : Yep
         //GenericsStart Generic GenericBound GenericsStart Generic GenericsStart Generic GenericBoundEnd GenericBoundEnd GenericEnd Generic GenericBound
@@ -158,22 +193,7 @@ pub fn next_generic(tokenizer: &mut Tokenizer) -> Token {
             } else if tokenizer.matches(",") {
                 tokenizer.make_token(TokenTypes::GenericEnd)
             } else if tokenizer.matches(">") {
-                tokenizer.generic_depth -= 1;
-                if tokenizer.generic_depth == 0 {
-                    // The generics are done, break of out the generic state
-                    tokenizer.state = match tokenizer.state {
-                        TokenizerState::GENERIC_TO_FUNC => TokenizerState::FUNCTION,
-                        TokenizerState::GENERIC_TO_FUNC_TO_STRUCT_TOP => TokenizerState::FUNCTION_TO_STRUCT_TOP,
-                        TokenizerState::GENERIC_TO_STRUCT => TokenizerState::STRUCTURE,
-                        TokenizerState::GENERIC_TO_IMPL => TokenizerState::IMPLEMENTATION,
-                        _ => panic!("Unexpected generic state!"),
-                    };
-                    // Reset the generic depth variable in the tokenizer
-                    tokenizer.generic_depth = 1;
-                    tokenizer.make_token(TokenTypes::GenericsEnd)
-                } else {
-                    tokenizer.make_token(TokenTypes::GenericBoundEnd)
-                }
+                end_generic(tokenizer)
             } else {
                 tokenizer.handle_invalid()
             }
@@ -181,3 +201,23 @@ pub fn next_generic(tokenizer: &mut Tokenizer) -> Token {
         token_type => panic!("How'd you get here? {:?}", token_type),
     };
 }
+
+/// Ends a generic bracket after the > is matched, leaving the generic state if it's the outermost one
+fn end_generic(tokenizer: &mut Tokenizer) -> Token {
+    tokenizer.generic_depth -= 1;
+    return if tokenizer.generic_depth == 0 {
+        // The generics are done, break of out the generic state
+        tokenizer.state = match tokenizer.state {
+            TokenizerState::GENERIC_TO_FUNC => TokenizerState::FUNCTION,
+            TokenizerState::GENERIC_TO_FUNC_TO_STRUCT_TOP => TokenizerState::FUNCTION_TO_STRUCT_TOP,
+            TokenizerState::GENERIC_TO_STRUCT => TokenizerState::STRUCTURE,
+            TokenizerState::GENERIC_TO_IMPL => TokenizerState::IMPLEMENTATION,
+            _ => panic!("Unexpected generic state!"),
+        };
+        // Reset the generic depth variable in the tokenizer
+        tokenizer.generic_depth = 1;
+        tokenizer.make_token(TokenTypes::GenericsEnd)
+    } else {
+        tokenizer.make_token(TokenTypes::GenericBoundEnd)
+    };
+}
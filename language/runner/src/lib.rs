@@ -15,6 +15,15 @@ use syntax::program::syntax::Compiler;
 /// The main Raven runner
 pub mod runner;
 
+/// Lets embedders replace the allocator compiled programs get their heap memory from
+pub use compiler_llvm::allocator::{set_allocator, take_allocator, Allocator, SystemAllocator};
+/// Lets test harnesses read how many times each line ran when coverage is enabled
+pub use compiler_llvm::coverage::{coverage_sites, CoverageSite};
+/// Lets embedders register Rust closures as Raven extern functions
+pub use compiler_llvm::host_functions::{clear_host_functions, register_host_function, HostFunction, HostType, HostValue};
+/// Lets embedders and test harnesses capture panics in Raven code
+pub use compiler_llvm::panic_hook::{set_panic_hook, take_panic_hook, PanicHook};
+
 /// Gets the compiler given the name and the compiling Arcs (so they can be passed to the compiler)
 pub fn get_compiler<T>(
     compiling: Arc<DashMap<String, Arc<FinalizedFunction>>>,
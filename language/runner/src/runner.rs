@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+use std::fs;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -7,11 +9,17 @@ use tokio::sync::mpsc;
 use tokio::sync::mpsc::{Receiver, Sender};
 use tokio::time;
 
+use checker::freestanding::check_freestanding;
 use checker::output::TypesChecker;
-use data::{Arguments, CompilerArguments};
+use compiler_llvm::{heap_profiler, host_functions};
+use data::progress::Progress;
+use data::tokens::Span;
+use data::{stats, Arguments, CompilerArguments};
 use parser::parse;
 use syntax::async_util::HandleWrapper;
-use syntax::errors::ParsingError;
+use syntax::errors::{ErrorSource, ParsingError, ParsingMessage};
+use syntax::metadata::CrateMetadata;
+use syntax::program::printer::{CheckedPrinter, SourceLocator};
 use syntax::program::syntax::Syntax;
 
 use crate::{get_compiler, JoinWaiter};
@@ -20,11 +28,28 @@ pub fn create_syntax(settings: &Arguments) -> Arc<Mutex<Syntax>> {
     let handle = Arc::new(Mutex::new(HandleWrapper::new(settings.cpu_runtime.handle().clone())));
     let mut syntax = Syntax::new(Box::new(TypesChecker::new(handle.clone(), settings.runner_settings.include_references())));
     syntax.async_manager.target.clone_from(&settings.runner_settings.compiler_arguments.target);
+    syntax.features.clone_from(&settings.runner_settings.compiler_arguments.features);
+    if settings.runner_settings.compiler_arguments.recursion_limit != 0 {
+        syntax.recursion_limit = settings.runner_settings.compiler_arguments.recursion_limit;
+    }
+    if settings.runner_settings.compiler_arguments.edition != 0 {
+        syntax.edition = settings.runner_settings.compiler_arguments.edition;
+    }
+    if settings.runner_settings.compiler_arguments.dump_instantiations {
+        syntax.instantiations = Some(HashMap::default());
+    }
+    if settings.runner_settings.compiler_arguments.record_call_sites {
+        syntax.call_sites = Some(HashMap::default());
+    }
+    if settings.runner_settings.compiler_arguments.debug_info {
+        syntax.source_locator = Some(Arc::new(SourceLocator::new(&settings.runner_settings.sources)));
+    }
     return Arc::new(Mutex::new(syntax));
 }
 
 pub async fn build(syntax: Arc<Mutex<Syntax>>, settings: &Arguments) -> Result<(), Vec<ParsingError>> {
     let handle = syntax.lock().process_manager.handle().clone();
+    stats::reset();
 
     let mut handles = Vec::default();
     // Parses source, getting handles and building into the unresolved syntax.
@@ -33,6 +58,9 @@ pub async fn build(syntax: Arc<Mutex<Syntax>>, settings: &Arguments) -> Result<(
             if !file.path().ends_with("rv") {
                 continue;
             }
+            if source_set.edition() != 0 {
+                syntax.lock().file_editions.insert(file.hash(), source_set.edition());
+            }
 
             handles.push(
                 settings
@@ -45,6 +73,8 @@ pub async fn build(syntax: Arc<Mutex<Syntax>>, settings: &Arguments) -> Result<(
         }
     }
 
+    let compiler_arguments = &settings.runner_settings.compiler_arguments;
+    let mut progress = Progress::new("Parsing", "files", Some(handles.len()), compiler_arguments);
     let mut errors = Vec::default();
     //Join any parsing errors
     for handle in handles {
@@ -52,7 +82,9 @@ pub async fn build(syntax: Arc<Mutex<Syntax>>, settings: &Arguments) -> Result<(
             Err(error) => errors.push(Error::new(error)),
             Ok(_) => {}
         }
+        progress.advance();
     }
+    progress.finish();
 
     if !errors.is_empty() {
         for error in errors {
@@ -61,6 +93,7 @@ pub async fn build(syntax: Arc<Mutex<Syntax>>, settings: &Arguments) -> Result<(
         panic!("Error detected!");
     }
 
+    let progress = Progress::new("Checking", "", None, compiler_arguments);
     syntax.lock().finish();
 
     let mut errors = vec![];
@@ -82,10 +115,72 @@ pub async fn build(syntax: Arc<Mutex<Syntax>>, settings: &Arguments) -> Result<(
         }
     }
 
+    progress.finish();
+    stats::record_checker_memory();
+
     errors.append(&mut syntax.lock().errors);
+    if errors.is_empty() && settings.runner_settings.compiler_arguments.no_std {
+        errors.append(&mut check_freestanding(&syntax));
+    }
+    if errors.is_empty() && settings.runner_settings.compiler_arguments.emit.iter().any(|emit| emit == "checked-ast") {
+        print_checked(&syntax, settings);
+    }
+    if settings.runner_settings.compiler_arguments.dump_instantiations {
+        print_instantiations(&syntax, settings);
+    }
+    if errors.is_empty() && settings.runner_settings.compiler_arguments.emit.iter().any(|emit| emit == "metadata") {
+        write_metadata(&syntax, settings);
+    }
     return if errors.is_empty() { Ok(()) } else { Err(errors) };
 }
 
+/// Prints every checked function with the type and span of each effect
+fn print_checked(syntax: &Arc<Mutex<Syntax>>, settings: &Arguments) {
+    let mut printer = CheckedPrinter::new(&settings.runner_settings.sources);
+    let locked = syntax.lock();
+    let mut functions = locked.compiling.iter().map(|pair| pair.value().clone()).collect::<Vec<_>>();
+    functions.sort_by(|first, second| first.data.name.cmp(&second.data.name));
+    for function in functions {
+        println!("{}", printer.print_function(&function));
+    }
+}
+
+/// Prints every instantiation of each generic function with the places calling it, to find what's making code large.
+/// Functions with the most instantiations are first.
+fn print_instantiations(syntax: &Arc<Mutex<Syntax>>, settings: &Arguments) {
+    let locator = SourceLocator::new(&settings.runner_settings.sources);
+    let locked = syntax.lock();
+    let mut functions: HashMap<&str, Vec<(&str, &Vec<Span>)>> = HashMap::default();
+    for (name, calls) in locked.instantiations.iter().flatten() {
+        let (base, generics) = name.split_once('$').unwrap_or((name.as_str(), ""));
+        functions.entry(base).or_default().push((generics, calls));
+    }
+    let mut functions = functions.into_iter().collect::<Vec<_>>();
+    functions.sort_by(|first, second| second.1.len().cmp(&first.1.len()).then(first.0.cmp(second.0)));
+
+    println!("Generic instantiations:");
+    for (base, mut instantiations) in functions {
+        instantiations.sort_by(|first, second| first.0.cmp(second.0));
+        let calls = instantiations.iter().map(|(_, calls)| calls.len()).sum::<usize>();
+        println!("{} ({} instantiations, {} calls)", base, instantiations.len(), calls);
+        for (generics, calls) in instantiations {
+            println!("    {}<{}>", base, generics);
+            for call in calls {
+                println!("        {}", locator.location(call));
+            }
+        }
+    }
+}
+
+/// Writes the crate's metadata to target/crate.rvmd
+fn write_metadata(syntax: &Arc<Mutex<Syntax>>, settings: &Arguments) {
+    let compiler_arguments = &settings.runner_settings.compiler_arguments;
+    let name = compiler_arguments.temp_folder.parent().and_then(|folder| folder.file_name()).unwrap_or_default();
+    let metadata = CrateMetadata::from_syntax(name.to_string_lossy().to_string(), &syntax.lock());
+    fs::create_dir_all(&compiler_arguments.temp_folder).unwrap();
+    fs::write(compiler_arguments.temp_folder.join("crate.rvmd"), metadata.write()).unwrap();
+}
+
 /// Runs Raven to completion with the given arguments
 pub async fn run<T: Send + 'static>(
     syntax: Arc<Mutex<Syntax>>,
@@ -105,7 +200,43 @@ pub async fn run<T: Send + 'static>(
     build(syntax.clone(), settings).await?;
 
     go_sender.send(()).await.unwrap();
-    return Ok(receiver.recv().await.unwrap());
+    let result = receiver.recv().await.unwrap();
+    let errors = host_functions::signature_errors()
+        .into_iter()
+        .map(|(span, function, reason)| span.make_error(ParsingMessage::HostSignature(function, reason)))
+        .collect::<Vec<_>>();
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    if settings.runner_settings.compiler_arguments.stats {
+        print!("{}", stats::report());
+    }
+    if settings.runner_settings.compiler_arguments.heap_profile {
+        print_heap_profile(settings);
+    }
+    if settings.runner_settings.compiler_arguments.leak_check {
+        // Each leaking site is an error pointing to the code that allocated it
+        let errors = heap_profiler::leaked_sites()
+            .into_iter()
+            .map(|site| site.span.make_error(ParsingMessage::LeakedAllocation(site.function, site.live)))
+            .collect::<Vec<_>>();
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+    }
+    return Ok(result);
+}
+
+/// Prints the allocations made at each allocation site while running, most allocations first
+fn print_heap_profile(settings: &Arguments) {
+    let locator = SourceLocator::new(&settings.runner_settings.sources);
+    let sites = heap_profiler::allocation_sites();
+    let count: u64 = sites.iter().map(|site| site.count).sum();
+    let bytes: u64 = sites.iter().map(|site| site.bytes).sum();
+    println!("Heap profile: {} allocations, {} bytes", count, bytes);
+    for site in sites {
+        println!("{:>10} {:>12}B  {} @ {}", site.count, site.bytes, site.function, locator.location(&site.span));
+    }
 }
 
 /// Runs the compiler, waiting for the receiver before running the main function then sending the result on the sender.
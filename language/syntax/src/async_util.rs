@@ -15,7 +15,7 @@ use data::tokens::Span;
 
 use crate::errors::{ErrorSource, ParsingMessage};
 use crate::program::function::display_parenless;
-use crate::program::syntax::Syntax;
+use crate::program::syntax::{with_import, Syntax};
 use crate::program::types::FinalizedTypes;
 use crate::{FinishedStructImplementor, ParsingError, TopElement};
 
@@ -70,13 +70,7 @@ impl<T: TopElement> AsyncTypesGetter<T> {
         not_trait: bool,
     ) -> Option<Result<Arc<T>, ParsingError>> {
         // Add the prefix to the name, if any.
-        let name = if prefix.is_empty() {
-            self.getting.clone()
-        } else if prefix.ends_with(&self.getting) {
-            prefix
-        } else {
-            prefix + "::" + &*self.getting.clone()
-        };
+        let name = if prefix.is_empty() { self.getting.clone() } else { with_import(&prefix, &self.getting) };
 
         let getting = T::get_manager(locked);
         //Look for a program of that name
@@ -112,8 +106,7 @@ impl<T: TopElement> AsyncTypesGetter<T> {
         }
 
         for import in imports {
-            let import =
-                if import.ends_with(&self.getting) { import.clone() } else { format!("{}::{}", import, self.getting) };
+            let import = with_import(import, &self.getting);
             if let Some(found) = manager.wakers.remove(&import) {
                 for waker in found {
                     waker.wake();
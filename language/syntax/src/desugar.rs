@@ -0,0 +1,33 @@
+use data::tokens::Span;
+
+use crate::program::code::{EffectType, Effects, Expression, ExpressionType};
+
+/// Builds the effects a construct is desugared into, like the Iter calls of a for loop or the trait call of an operator.
+/// Every effect gets the span of what the user wrote, marked as expanded from the construct, so errors in the
+/// generated code point at the construct instead of nowhere.
+pub struct Desugar {
+    /// The span of the construct being desugared
+    span: Span,
+}
+
+impl Desugar {
+    /// Starts desugaring the construct at the span, with a name like "for loop" for diagnostics
+    pub fn new(span: &Span, construct: &'static str) -> Self {
+        return Self { span: span.expanded(construct) };
+    }
+
+    /// The span every generated effect gets
+    pub fn span(&self) -> Span {
+        return self.span.clone();
+    }
+
+    /// Makes a generated effect
+    pub fn effect(&self, types: EffectType) -> Effects {
+        return Effects::new(self.span.clone(), types);
+    }
+
+    /// Makes a generated line of code
+    pub fn line(&self, types: EffectType) -> Expression {
+        return Expression::new(ExpressionType::Line, self.effect(types));
+    }
+}
@@ -14,6 +14,10 @@ pub enum ParsingMessage {
     UnexpectedIf(),
     UnexpectedElse(),
     UnexpectedFor(),
+    UnexpectedDefer(),
+    ReturnInDefer(),
+    UnexpectedBreak(),
+    BreakOutsideLoop(),
     UnexpectedToken(),
     UnexpectedSymbol(),
     UnexpectedVoid(),
@@ -26,20 +30,46 @@ pub enum ParsingMessage {
     ExpectedWhile(),
     ExtraSymbol(),
     SelfInStatic(),
+    MissingArgumentType(String),
+    ReceiverNotFirst(),
+    NotAMethod(String),
+    MissingReceiver(String),
     FailedToFind(String),
     UnexpectedCharacters(),
     DuplicateStructure(),
     DuplicateFunction(),
     UnknownField(String),
+    UnknownDerive(String),
     IncorrectBoundsLength(),
     MismatchedTypes(FinalizedTypes, FinalizedTypes),
+    ExpectedBool(FinalizedTypes),
     UnknownOperation(String),
+    ExpressionTooDeep(u32),
+    TypeTooDeep(String, u32),
+    InfiniteInstantiation(Vec<String>),
+    UnorderedOperators(String, String, i64),
     UnknownFunction(),
     MissingArgument(),
     AmbiguousMethod(String),
     NoMethod(String, FinalizedTypes),
     NoImpl(FinalizedTypes, String),
     NoTraitImpl(FinalizedTypes, FinalizedTypes),
+    NoMatchingImpl(FinalizedTypes, FinalizedTypes, Vec<String>),
+    NotComparable(FinalizedTypes, &'static str),
+    LeakedAllocation(String, u64),
+    MultiByteChar(char),
+    InvalidEscape(String),
+    InvalidFormat(String),
+    InvalidFormatSpec(String),
+    AssignToTemporary(),
+    AssignToImmutable(String),
+    MissingHook(&'static str, &'static str),
+    DuplicateHook(&'static str),
+    InvalidHook(&'static str, &'static str),
+    HostedFunction(String),
+    UnsafeInlineCode(&'static str),
+    InvalidInlineCode(&'static str, &'static str),
+    HostSignature(String, String),
 }
 
 impl Display for ParsingMessage {
@@ -52,6 +82,10 @@ impl Display for ParsingMessage {
             ParsingMessage::UnexpectedIf() => write!(f, "Unexpected if! Did you forget a semicolon?"),
             ParsingMessage::UnexpectedElse() => write!(f, "Unexpected else!"),
             ParsingMessage::UnexpectedFor() => write!(f, "Unexpected for! Did you forget a semicolon?"),
+            ParsingMessage::UnexpectedDefer() => write!(f, "Unexpected defer! Defers have to be their own statement."),
+            ParsingMessage::ReturnInDefer() => write!(f, "Can't return from inside a defer!"),
+            ParsingMessage::UnexpectedBreak() => write!(f, "Unexpected break! Breaks have to be their own statement."),
+            ParsingMessage::BreakOutsideLoop() => write!(f, "Can't break or continue outside of a loop!"),
             ParsingMessage::UnexpectedToken() => write!(f, "Unexpected token, expected variable name!"),
             ParsingMessage::UnexpectedSymbol() => write!(f, "Unexpected symbol, expected equals!"),
             ParsingMessage::UnexpectedVoid() => write!(f, "Expected a value, found void!"),
@@ -66,15 +100,43 @@ impl Display for ParsingMessage {
             ParsingMessage::ExpectedIn() => write!(f, "Missing \"in\" in for loop."),
             ParsingMessage::ExtraSymbol() => write!(f, "Extra symbol!"),
             ParsingMessage::SelfInStatic() => write!(f, "self in static function!"),
+            ParsingMessage::MissingArgumentType(name) => {
+                write!(f, "Argument {} needs a type, only the self receiver can leave it out!", name)
+            }
+            ParsingMessage::ReceiverNotFirst() => write!(f, "self must be the first argument!"),
+            ParsingMessage::NotAMethod(name) => {
+                write!(f, "{} doesn't take self, call it on the type like Type::{}() instead!", name, name)
+            }
+            ParsingMessage::MissingReceiver(name) => {
+                write!(f, "{} takes self, call it on a value like value.{}() instead!", name, name)
+            }
             ParsingMessage::FailedToFind(name) => write!(f, "Failed to find type {}, did you import it correctly?", name),
             ParsingMessage::UnexpectedCharacters() => write!(f, "Unexpected characters!"),
             ParsingMessage::DuplicateStructure() => write!(f, "Duplicate structure!"),
             ParsingMessage::DuplicateFunction() => write!(f, "Duplicate function!"),
             ParsingMessage::UnknownField(field) => write!(f, "Unknown field {}!", field),
+            ParsingMessage::UnknownDerive(derive) => write!(f, "{} can't be derived!", derive),
             ParsingMessage::IncorrectBoundsLength() => write!(f, "Incorrect bounds length!"),
             ParsingMessage::MismatchedTypes(found, bound) => {
                 write!(f, "{} isn't of type {}", fix_type(found), fix_type(bound))
             }
+            ParsingMessage::ExpectedBool(found) => {
+                write!(f, "Expected a bool for the condition, found {}!", fix_type(found))
+            }
+            ParsingMessage::UnorderedOperators(operation, other, priority) => write!(
+                f,
+                "Operations {} and {} share a token and priority {} but not parse_left, so they can't be ordered!",
+                operation, other, priority
+            ),
+            ParsingMessage::ExpressionTooDeep(limit) => {
+                write!(f, "Expression too deeply nested, the limit is {} (see --recursion-limit)!", limit)
+            }
+            ParsingMessage::TypeTooDeep(name, limit) => {
+                write!(f, "Type {} too deeply nested, the limit is {} (see --recursion-limit)!", name, limit)
+            }
+            ParsingMessage::InfiniteInstantiation(chain) => {
+                write!(f, "Generics are instantiated forever, each call nests them deeper: {}", chain.join(" -> "))
+            }
             ParsingMessage::UnknownOperation(operation) => write!(f, "Unknown operation '{}'", operation),
             ParsingMessage::UnknownFunction() => write!(f, "Unknown function!"),
             ParsingMessage::MissingArgument() => write!(f, "Incorrect arguments length!"),
@@ -86,11 +148,65 @@ impl Display for ParsingMessage {
             ParsingMessage::NoTraitImpl(base, traits) => {
                 write!(f, "No implementation of {} for {}", fix_type(traits), fix_type(base))
             }
+            ParsingMessage::NotComparable(base, traits) => {
+                write!(f, "{} can't be compared with this operator, it has to implement {}!", fix_type(base), traits)
+            }
+            ParsingMessage::NoMatchingImpl(base, traits, reasons) => {
+                write!(
+                    f,
+                    "No implementation of {} for {}, none of the implementations match:",
+                    fix_type(traits),
+                    fix_type(base)
+                )?;
+                for reason in reasons {
+                    write!(f, "\n    {}", reason)?;
+                }
+                Ok(())
+            }
+            ParsingMessage::LeakedAllocation(function, count) => {
+                write!(f, "{} allocations in {} were never freed!", count, function)
+            }
+            ParsingMessage::MultiByteChar(character) => {
+                write!(f, "{:?} doesn't fit in a single byte char, use a str instead!", character)
+            }
+            ParsingMessage::InvalidEscape(escape) => write!(f, "Invalid escape {}!", escape),
+            ParsingMessage::InvalidFormat(format) => {
+                write!(f, "Invalid format {}, expected a variable or field like {{name}} or {{point.x:?}}!", format)
+            }
+            ParsingMessage::InvalidFormatSpec(spec) => {
+                write!(f, "Invalid format spec {}, expected one like <8, >8.2, 08x or ?!", spec)
+            }
+            ParsingMessage::AssignToTemporary() => {
+                write!(f, "Can only assign to a variable or a field, the value would be thrown away!")
+            }
+            ParsingMessage::AssignToImmutable(variable) => {
+                write!(f, "Can't assign to {} because it isn't mutable! Declare it with let mut", variable)
+            }
+            ParsingMessage::MissingHook(attribute, example) => {
+                write!(f, "--no-std programs need a function marked #[{}], like #[{}] {}", attribute, attribute, example)
+            }
+            ParsingMessage::DuplicateHook(attribute) => write!(f, "Only one function can be marked #[{}]!", attribute),
+            ParsingMessage::InvalidHook(attribute, example) => {
+                write!(f, "#[{}] must be on an extern function like {}", attribute, example)
+            }
+            ParsingMessage::HostedFunction(name) => {
+                write!(f, "{} needs an operating system, so it can't be called with --no-std!", name)
+            }
+            ParsingMessage::UnsafeInlineCode(name) => {
+                write!(f, "{}! can only be used in unsafe functions, since the compiler can't check it", name)
+            }
+            ParsingMessage::InvalidInlineCode(name, example) => {
+                write!(f, "{}! needs its code as string literals before its inputs, like {}", name, example)
+            }
+            ParsingMessage::HostSignature(function, reason) => {
+                write!(f, "The host function registered for {} doesn't match its declaration, {}!", function, reason)
+            }
         };
     }
 }
 
-fn fix_type(types: &FinalizedTypes) -> String {
+/// Formats a type for an error message, without the mangled generics in its name
+pub fn fix_type(types: &FinalizedTypes) -> String {
     let mut string = types.to_string();
     if let Some(start) = string.find('$') {
         string.replace_range(start..string.find('<').unwrap_or(string.len()), "");
@@ -155,21 +271,24 @@ impl ParsingError {
             token.start = (token.end.0, 0);
         }
 
-        if token.end_offset == token.start_offset {
-            token.start_offset -= 1;
-        }
+        // Columns are counted in characters so multi-byte characters line up with the printed line
+        let (column, end_column) = token.character_columns(contents.as_bytes());
+        let width = (end_column.saturating_sub(column) as usize).max(1);
 
         let line = contents.lines().nth((token.start.0 as usize).max(1) - 1).unwrap_or("???");
         println!("{}", self.message.to_string().bright_red());
-        println!("{}", format!("in file {}:{}:{}", file.path(), token.start.0, token.start.1).bright_red());
+        println!("{}", format!("in file {}:{}:{}", file.path(), token.start.0, column).bright_red());
         println!("{} {}", " ".repeat(token.start.0.to_string().len()), "|".bright_cyan());
         println!("{} {} {}", token.start.0.to_string().bright_cyan(), "|".bright_cyan(), line.bright_red());
         println!(
             "{} {} {}{}",
             " ".repeat(token.start.0.to_string().len()),
             "|".bright_cyan(),
-            " ".repeat(token.start.1 as usize),
-            "^".repeat(token.end_offset - token.start_offset).bright_red()
+            " ".repeat(column as usize),
+            "^".repeat(width).bright_red()
         );
+        if let Some(construct) = self.span.expanded_from {
+            println!("{}", format!("note: in the code generated for this {}", construct).bright_cyan());
+        }
     }
 }
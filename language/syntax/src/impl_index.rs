@@ -0,0 +1,71 @@
+use std::collections::HashMap;
+
+use crate::program::types::FinalizedTypes;
+use crate::FinishedTraitImplementor;
+
+/// Indexes the trait implementations in Syntax::implementations by the trait and type's names,
+/// so looking up the implementations of a trait or type doesn't scan every implementation.
+/// Implementations are stored by their position in Syntax::implementations, which is also their chalk ImplId.
+#[derive(Default)]
+pub struct ImplIndex {
+    /// The implementations of each trait, by the trait's name without generics
+    by_trait: HashMap<String, Vec<usize>>,
+    /// The implementations for each type, by the type's name without generics
+    by_type: HashMap<String, Vec<usize>>,
+    /// Implementations for a generic or array type, which could apply to any type
+    blanket: Vec<usize>,
+}
+
+impl ImplIndex {
+    /// Indexes the implementation at the position in Syntax::implementations
+    pub fn add(&mut self, position: usize, implementor: &FinishedTraitImplementor) {
+        if let Some(name) = Self::key(&implementor.target) {
+            self.by_trait.entry(name).or_default().push(position);
+        }
+        match Self::key(&implementor.base) {
+            Some(name) => self.by_type.entry(name).or_default().push(position),
+            None => self.blanket.push(position),
+        }
+    }
+
+    /// The key a type is indexed by, which is its struct's name without generics.
+    /// Returns None for generic types, which aren't a specific struct.
+    pub fn key(types: &FinalizedTypes) -> Option<String> {
+        return match types {
+            FinalizedTypes::Generic(_, _) | FinalizedTypes::Array(_) => None,
+            _ => types.inner_struct_safe().map(|found| found.data.name.split('<').next().unwrap().to_string()),
+        };
+    }
+
+    /// Gets the implementations of the trait with the key, which is empty if it has none.
+    pub fn of_trait(&self, key: &str) -> &[usize] {
+        return self.by_trait.get(key).map(Vec::as_slice).unwrap_or_default();
+    }
+
+    /// Gets the implementations for the type with the key, not including blanket implementations.
+    pub fn of_type(&self, key: &str) -> &[usize] {
+        return self.by_type.get(key).map(Vec::as_slice).unwrap_or_default();
+    }
+
+    /// Gets the implementations that could apply to any type, like impl<T: Bound> Trait for T
+    pub fn blanket(&self) -> &[usize] {
+        return &self.blanket;
+    }
+
+    /// Finds the keys of every indexed trait with the name, either the full name like "iter::Iter" or just "Iter"
+    pub fn trait_keys(&self, name: &str) -> Vec<&String> {
+        return Self::matching(self.by_trait.keys(), name);
+    }
+
+    /// Finds the keys of every indexed type with the name, either the full name or the last part
+    pub fn type_keys(&self, name: &str) -> Vec<&String> {
+        return Self::matching(self.by_type.keys(), name);
+    }
+
+    /// Finds the keys matching the name, sorted so results are the same every run
+    fn matching<'a>(keys: impl Iterator<Item = &'a String>, name: &str) -> Vec<&'a String> {
+        let mut output: Vec<_> = keys.filter(|key| *key == name || key.rsplit("::").next() == Some(name)).collect();
+        output.sort();
+        return output;
+    }
+}
@@ -24,7 +24,8 @@ use parking_lot::Mutex;
 /// - Data Type trait used a simple wrapper to access the static data (see FunctionData or StructData) of an object with data
 /// - Top Element trait used to allow generic access to function and struct types
 /// - Trait implementors struct for storing implementor data
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::env;
 use std::fmt::{Debug, Display, Formatter};
 use std::future::Future;
 use std::hash::Hash;
@@ -37,8 +38,16 @@ pub mod async_util;
 pub mod chalk_interner;
 /// Implements the chalk types for Syntax
 pub mod chalk_support;
+/// Builds the effects that constructs like loops and operators are desugared into
+pub mod desugar;
 /// Has all the error-related structs
 pub mod errors;
+/// Indexes trait implementations by trait and type, for finding what implements what
+pub mod impl_index;
+/// Mangles names into symbols for the compiled code
+pub mod mangling;
+/// The serialized public interface of a crate, written by --emit=metadata
+pub mod metadata;
 /// Utility functions for operations
 pub mod operation_util;
 /// Handles the types required to hold the program in memory
@@ -56,7 +65,8 @@ use data::tokens::Span;
 pub type ParsingFuture<T> = Pin<Box<dyn Future<Output = Result<T, ParsingError>> + Send>>;
 
 /// All the modifiers, used for modifier parsing and debug output.
-pub static MODIFIERS: [Modifier; 4] = [Modifier::Public, Modifier::Protected, Modifier::Extern, Modifier::Internal];
+pub static MODIFIERS: [Modifier; 5] =
+    [Modifier::Public, Modifier::Protected, Modifier::Extern, Modifier::Internal, Modifier::Unsafe];
 
 /// All the modifiers structures/functions/fields can have
 #[derive(Clone, Copy, PartialEq, Debug)]
@@ -71,6 +81,10 @@ pub enum Modifier {
     Internal = 0b1000,
     /// Hidden from the user, only used internally
     Trait = 0b1_0000,
+    /// Mutable function arguments, which can be set in the function
+    Mut = 0b10_0000,
+    /// Unsafe functions can splice raw LLVM IR and assembly into their code with llvm! and asm!
+    Unsafe = 0b100_0000,
 }
 
 impl Display for Modifier {
@@ -81,6 +95,8 @@ impl Display for Modifier {
             Modifier::Extern => write!(f, "extern"),
             Modifier::Internal => write!(f, "internal"),
             Modifier::Trait => panic!("Shouldn't display trait modifier!"),
+            Modifier::Mut => write!(f, "mut"),
+            Modifier::Unsafe => write!(f, "unsafe"),
         };
     }
 }
@@ -143,6 +159,38 @@ impl Attribute {
         return None;
     }
 
+    /// Checks if every #[cfg(...)] attribute allows the element to be compiled.
+    /// #[cfg(feature = "name")] requires the feature to be enabled,
+    /// #[cfg(env = "NAME")] requires the environment variable to be set when compiling,
+    /// and #[cfg(target_os = "linux")] or #[cfg(target_family = "unix")] require compiling for that platform.
+    /// Unknown conditions are never enabled.
+    pub fn is_cfg_enabled(attributes: &Vec<Attribute>, features: &Vec<String>) -> bool {
+        for attribute in attributes {
+            let condition = match attribute {
+                Attribute::String(name, condition) if name == "cfg" => condition,
+                _ => continue,
+            };
+            let enabled = match condition.split_once('=') {
+                Some((key, value)) => {
+                    let value = value.trim().trim_matches('"');
+                    match key.trim() {
+                        "feature" => features.iter().any(|feature| feature == value),
+                        "env" => env::var_os(value).is_some(),
+                        // Raven is compiled just-in-time, so the target is always the platform the compiler runs on
+                        "target_os" => env::consts::OS == value,
+                        "target_family" => env::consts::FAMILY == value,
+                        _ => false,
+                    }
+                }
+                None => false,
+            };
+            if !enabled {
+                return false;
+            }
+        }
+        return true;
+    }
+
     /// Converts the attribute to a string attribute or returns None if it's a different type
     pub fn as_string_attribute(&self) -> Option<&String> {
         match self {
@@ -207,6 +255,12 @@ pub trait ProcessManager: Send + Sync {
     /// Gets the current function generics mutably
     fn mut_generics(&mut self) -> &mut HashMap<String, FinalizedTypes>;
 
+    /// Gets the names of the generic instantiations that led to the current one, outermost first
+    fn instantiations(&self) -> &Vec<String>;
+
+    /// Gets the instantiation chain mutably
+    fn mut_instantiations(&mut self) -> &mut Vec<String>;
+
     /// Clones the process manager, generally pretty fast because most data is Arc'd
     fn cloned(&self) -> Box<dyn ProcessManager>;
 }
@@ -216,15 +270,20 @@ pub trait ProcessManager: Send + Sync {
 pub struct SimpleVariableManager {
     /// The variables and their type
     pub variables: HashMap<String, FinalizedTypes>,
+    /// The variables that can be set, which are declared with let mut or are mut arguments
+    pub mutable: HashSet<String>,
 }
 
 impl SimpleVariableManager {
     /// Gets the variable manager for the function, filling in the function parameters
     pub fn for_function(codeless: &CodelessFinalizedFunction) -> Self {
-        let mut variable_manager = SimpleVariableManager { variables: HashMap::default() };
+        let mut variable_manager = SimpleVariableManager { variables: HashMap::default(), mutable: HashSet::default() };
 
         for field in &codeless.arguments {
             variable_manager.variables.insert(field.field.name.clone(), field.field.field_type.clone());
+            if is_modifier(field.modifiers, Modifier::Mut) {
+                variable_manager.mutable.insert(field.field.name.clone());
+            }
         }
 
         return variable_manager;
@@ -232,10 +291,13 @@ impl SimpleVariableManager {
 
     /// Gets the variable manager for the function, filling in the function parameters
     pub fn for_final_function(codeless: &FinalizedFunction) -> Self {
-        let mut variable_manager = SimpleVariableManager { variables: HashMap::default() };
+        let mut variable_manager = SimpleVariableManager { variables: HashMap::default(), mutable: HashSet::default() };
 
         for field in &codeless.fields {
             variable_manager.variables.insert(field.field.name.clone(), field.field.field_type.clone());
+            if is_modifier(field.modifiers, Modifier::Mut) {
+                variable_manager.mutable.insert(field.field.name.clone());
+            }
         }
 
         return variable_manager;
@@ -319,6 +381,8 @@ pub struct TraitImplementor {
     pub attributes: Vec<Attribute>,
     /// The implementor's functions
     pub functions: Vec<UnfinalizedFunction>,
+    /// The span of the impl's header, from the base type to the implementing type
+    pub span: Span,
 }
 
 /// Finished impl block for a type.
@@ -337,6 +401,8 @@ pub struct FinishedTraitImplementor {
     pub attributes: Vec<Attribute>,
     /// All ths functions in this implementor
     pub functions: Vec<Arc<FunctionData>>,
+    /// The span of the impl's header, from Iter<T> to NumberIter<T>
+    pub span: Span,
 }
 
 /// Finished impl block for a type.
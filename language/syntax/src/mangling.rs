@@ -0,0 +1,133 @@
+/// The prefix of every mangled symbol
+pub static MANGLED_PREFIX: &str = "_RV";
+
+/// Mangles a function or struct name into a symbol that only contains letters, digits, and underscores.
+///
+/// The scheme is:
+/// symbol   := "_RV" segment+ ["G" segment]
+/// segment  := (length of the escaped text in decimal) escaped
+///
+/// Each segment is one part of the path (split on "::"), and the optional "G" segment holds the generics
+/// of a degenericed function or struct (everything after the $, which can have paths of its own like foo$main::Box).
+/// Letters and digits are kept as is, "_" is escaped as "__", and every other byte (or a digit at the start of a
+/// segment) is escaped as "_" followed by two lowercase hex digits.
+///
+/// For example, main::Box::new$u64 is mangled to _RV4main3Box3newG3u64
+pub fn mangle(name: &str) -> String {
+    let mut output = MANGLED_PREFIX.to_string();
+    let (path, generics) = split_path(name);
+    for segment in path {
+        push_segment(&mut output, segment);
+    }
+    if let Some(generics) = generics {
+        output.push('G');
+        push_segment(&mut output, generics);
+    }
+    return output;
+}
+
+/// Demangles a symbol mangled with mangle into a readable name, with generics in angle brackets.
+/// For example, _RV4main3Box3newG3u64 is demangled to main::Box::new<u64>
+/// Returns None if the symbol isn't a valid mangled symbol.
+pub fn demangle(symbol: &str) -> Option<String> {
+    let mut remaining = symbol.strip_prefix(MANGLED_PREFIX)?.as_bytes();
+    let mut segments = Vec::default();
+    while !remaining.is_empty() {
+        let mut segment = read_segment(&mut remaining)?;
+        if remaining.first() == Some(&b'G') {
+            remaining = &remaining[1..];
+            segment = format!("{}<{}>", segment, read_segment(&mut remaining)?);
+        }
+        segments.push(segment);
+    }
+    return if segments.is_empty() { None } else { Some(segments.join("::")) };
+}
+
+/// Demangles every mangled symbol in the text, leaving everything else as is
+pub fn demangle_text(text: &str) -> String {
+    let mut output = String::default();
+    let mut remaining = text;
+    while let Some(start) = remaining.find(MANGLED_PREFIX) {
+        output += &remaining[..start];
+        let length = remaining[start..]
+            .find(|character: char| !character.is_ascii_alphanumeric() && character != '_')
+            .unwrap_or(remaining.len() - start);
+        let symbol = &remaining[start..start + length];
+        output += &demangle(symbol).unwrap_or(symbol.to_string());
+        remaining = &remaining[start + length..];
+    }
+    output += remaining;
+    return output;
+}
+
+/// Splits a name on "::" into its path and its generics, the part after the $.
+/// Any "::" in the generics, like foo$main::Box, or in angle brackets, like Add<math::u64>, isn't split on.
+fn split_path(name: &str) -> (Vec<&str>, Option<&str>) {
+    let mut output = Vec::default();
+    let mut depth = 0;
+    let mut last = 0;
+    let bytes = name.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'<' => depth += 1,
+            b'>' => depth -= 1,
+            // The generics are always the end of the name, so nothing after the $ is part of the path
+            b'$' if depth == 0 => {
+                output.push(&name[last..i]);
+                return (output, Some(&name[i + 1..]));
+            }
+            b':' if depth == 0 && bytes.get(i + 1) == Some(&b':') => {
+                output.push(&name[last..i]);
+                i += 1;
+                last = i + 1;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    output.push(&name[last..]);
+    return (output, None);
+}
+
+/// Escapes and adds a single segment to the output
+fn push_segment(output: &mut String, segment: &str) {
+    let mut escaped = String::default();
+    for (i, byte) in segment.bytes().enumerate() {
+        // A digit at the start would be read as part of the length, so it has to be escaped
+        if byte.is_ascii_alphanumeric() && !(i == 0 && byte.is_ascii_digit()) {
+            escaped.push(byte as char);
+        } else if byte == b'_' {
+            escaped += "__";
+        } else {
+            escaped += &format!("_{:02x}", byte);
+        }
+    }
+    *output += &format!("{}{}", escaped.len(), escaped);
+}
+
+/// Reads and unescapes a single segment, moving the input past it
+fn read_segment(input: &mut &[u8]) -> Option<String> {
+    let current: &[u8] = *input;
+    let digits = current.iter().take_while(|character| character.is_ascii_digit()).count();
+    let length: usize = String::from_utf8_lossy(&current[..digits]).parse().ok()?;
+    let escaped = current.get(digits..digits + length)?;
+    *input = &current[digits + length..];
+
+    let mut output = Vec::default();
+    let mut i = 0;
+    while i < escaped.len() {
+        if escaped[i] != b'_' {
+            output.push(escaped[i]);
+            i += 1;
+        } else if escaped.get(i + 1) == Some(&b'_') {
+            output.push(b'_');
+            i += 2;
+        } else {
+            let hex = String::from_utf8_lossy(escaped.get(i + 1..i + 3)?).to_string();
+            output.push(u8::from_str_radix(&hex, 16).ok()?);
+            i += 3;
+        }
+    }
+    return String::from_utf8(output).ok();
+}
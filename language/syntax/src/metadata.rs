@@ -0,0 +1,534 @@
+use indexmap::IndexMap;
+
+use crate::program::code::FinalizedMemberField;
+use crate::program::function::CodelessFinalizedFunction;
+use crate::program::r#struct::FinalizedStruct;
+use crate::program::syntax::Syntax;
+use crate::program::types::FinalizedTypes;
+use crate::Attribute;
+
+/// The magic bytes at the start of every metadata file
+pub static METADATA_MAGIC: &[u8; 4] = b"RVMD";
+/// The version of the metadata format, which must be incremented on every incompatible change
+pub static METADATA_VERSION: u32 = 3;
+
+/// The public interface of a compiled crate: its structs, function signatures, and implementations.
+/// This is meant to be everything a dependent crate needs to be checked against it, but nothing loads it into a
+/// syntax yet, so dependencies are still parsed from their source.
+///
+/// The binary format is little-endian, starting with METADATA_MAGIC and METADATA_VERSION (u32).
+/// Strings and lists are prefixed by their length (u64), optional values by a bool (u8), and enums by a tag (u8).
+/// Types refer to structs by name instead of embedding them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CrateMetadata {
+    /// The crate's name
+    pub name: String,
+    /// The features the crate was compiled with, since they change which elements exist
+    pub features: Vec<String>,
+    /// The edition the crate was written for, so dependents don't check it with their own edition
+    pub edition: u32,
+    /// Every struct and trait
+    pub structs: Vec<MetadataStruct>,
+    /// Every function's signature, including generic functions
+    pub functions: Vec<MetadataFunction>,
+    /// Every impl block, of traits and of structs
+    pub implementations: Vec<MetadataImpl>,
+}
+
+/// A type, referring to structs by name
+#[derive(Clone, Debug, PartialEq)]
+pub enum MetadataType {
+    /// A basic struct
+    Struct(String),
+    /// A type with generic types
+    GenericType(Box<MetadataType>, Vec<MetadataType>),
+    /// A reference to a type
+    Reference(Box<MetadataType>),
+    /// A generic with bounds
+    Generic(String, Vec<MetadataType>),
+    /// An array
+    Array(Box<MetadataType>),
+}
+
+/// A struct field or function argument
+#[derive(Clone, Debug, PartialEq)]
+pub struct MetadataField {
+    /// The field's name
+    pub name: String,
+    /// The field's modifiers
+    pub modifiers: u8,
+    /// The field's attributes
+    pub attributes: Vec<Attribute>,
+    /// The field's type
+    pub field_type: MetadataType,
+}
+
+/// A struct or trait
+#[derive(Clone, Debug, PartialEq)]
+pub struct MetadataStruct {
+    /// The struct's name
+    pub name: String,
+    /// The struct's numerical ID
+    pub id: u64,
+    /// The struct's modifiers
+    pub modifiers: u8,
+    /// The struct's attributes
+    pub attributes: Vec<Attribute>,
+    /// The struct's generics and their bounds
+    pub generics: Vec<(String, Vec<MetadataType>)>,
+    /// The struct's fields
+    pub fields: Vec<MetadataField>,
+    /// The names of the trait's functions, if it's a trait
+    pub functions: Vec<String>,
+}
+
+/// A function's signature
+#[derive(Clone, Debug, PartialEq)]
+pub struct MetadataFunction {
+    /// The function's name
+    pub name: String,
+    /// The function's modifiers
+    pub modifiers: u8,
+    /// The function's attributes
+    pub attributes: Vec<Attribute>,
+    /// The function's generics and their bounds
+    pub generics: Vec<(String, Vec<MetadataType>)>,
+    /// The function's arguments
+    pub arguments: Vec<MetadataField>,
+    /// The function's return type
+    pub return_type: Option<MetadataType>,
+}
+
+/// An impl block
+#[derive(Clone, Debug, PartialEq)]
+pub struct MetadataImpl {
+    /// The trait being implemented, or the struct for impls without a trait
+    pub target: MetadataType,
+    /// The type the trait is implemented for, or None for impls without a trait
+    pub base: Option<MetadataType>,
+    /// The impl's generics and their bounds
+    pub generics: Vec<(String, Vec<MetadataType>)>,
+    /// The impl's attributes
+    pub attributes: Vec<Attribute>,
+    /// The names of the impl's functions
+    pub functions: Vec<String>,
+}
+
+impl CrateMetadata {
+    /// Gets the metadata of a finished syntax
+    pub fn from_syntax(name: String, syntax: &Syntax) -> Self {
+        let mut structs =
+            syntax.strut_compiling.iter().map(|pair| MetadataStruct::from_struct(pair.value())).collect::<Vec<_>>();
+        structs.sort_by(|first, second| first.name.cmp(&second.name));
+
+        let mut functions = syntax
+            .compiling
+            .iter()
+            .chain(syntax.generics.iter())
+            .map(|pair| MetadataFunction::from_function(&pair.value().to_codeless()))
+            .collect::<Vec<_>>();
+        functions.sort_by(|first, second| first.name.cmp(&second.name));
+
+        let mut implementations = syntax
+            .implementations
+            .iter()
+            .map(|implementor| MetadataImpl {
+                target: MetadataType::from_types(&implementor.target),
+                base: Some(MetadataType::from_types(&implementor.base)),
+                generics: from_generics(&implementor.generics),
+                attributes: implementor.attributes.clone(),
+                functions: implementor.functions.iter().map(|function| function.name.clone()).collect(),
+            })
+            .collect::<Vec<_>>();
+        for implementors in syntax.struct_implementations.values() {
+            implementations.extend(implementors.iter().map(|implementor| MetadataImpl {
+                target: MetadataType::from_types(&implementor.target),
+                base: None,
+                generics: from_generics(&implementor.generics),
+                attributes: implementor.attributes.clone(),
+                functions: implementor.functions.iter().map(|function| function.name.clone()).collect(),
+            }));
+        }
+
+        return Self {
+            name,
+            features: syntax.features.clone(),
+            edition: syntax.edition,
+            structs,
+            functions,
+            implementations,
+        };
+    }
+
+    /// Serializes the metadata to bytes
+    pub fn write(&self) -> Vec<u8> {
+        let mut writer = MetadataWriter::default();
+        writer.output.extend_from_slice(METADATA_MAGIC);
+        writer.output.extend_from_slice(&METADATA_VERSION.to_le_bytes());
+        writer.string(&self.name);
+        writer.list(&self.features, |writer, found| writer.string(found));
+        writer.u64(self.edition as u64);
+        writer.list(&self.structs, |writer, found| found.write(writer));
+        writer.list(&self.functions, |writer, found| found.write(writer));
+        writer.list(&self.implementations, |writer, found| found.write(writer));
+        return writer.output;
+    }
+
+    /// Deserializes metadata written by write, erroring if it's invalid or from a different version
+    pub fn read(bytes: &[u8]) -> Result<Self, String> {
+        let mut reader = MetadataReader { bytes, index: 0 };
+        if reader.bytes(4)? != METADATA_MAGIC {
+            return Err("Not a Raven metadata file!".to_string());
+        }
+        let version = u32::from_le_bytes(reader.bytes(4)?.try_into().unwrap());
+        if version != METADATA_VERSION {
+            return Err(format!("Metadata version {} doesn't match the compiler's version {}!", version, METADATA_VERSION));
+        }
+        let output = Self {
+            name: reader.string()?,
+            features: reader.list(MetadataReader::string)?,
+            edition: reader.u64()? as u32,
+            structs: reader.list(MetadataStruct::read)?,
+            functions: reader.list(MetadataFunction::read)?,
+            implementations: reader.list(MetadataImpl::read)?,
+        };
+        if reader.index != bytes.len() {
+            return Err("Extra data at the end of the metadata!".to_string());
+        }
+        return Ok(output);
+    }
+}
+
+impl MetadataType {
+    /// Converts a finalized type
+    pub fn from_types(types: &FinalizedTypes) -> Self {
+        return match types {
+            FinalizedTypes::Struct(structure) => MetadataType::Struct(structure.data.name.clone()),
+            FinalizedTypes::GenericType(base, generics) => MetadataType::GenericType(
+                Box::new(MetadataType::from_types(base)),
+                generics.iter().map(MetadataType::from_types).collect(),
+            ),
+            FinalizedTypes::Reference(inner) => MetadataType::Reference(Box::new(MetadataType::from_types(inner))),
+            FinalizedTypes::Generic(name, bounds) => {
+                MetadataType::Generic(name.clone(), bounds.iter().map(MetadataType::from_types).collect())
+            }
+            FinalizedTypes::Array(inner) => MetadataType::Array(Box::new(MetadataType::from_types(inner))),
+        };
+    }
+
+    /// Writes this to the metadata
+    fn write(&self, writer: &mut MetadataWriter) {
+        match self {
+            MetadataType::Struct(name) => {
+                writer.u8(0);
+                writer.string(name);
+            }
+            MetadataType::GenericType(base, generics) => {
+                writer.u8(1);
+                base.write(writer);
+                writer.list(generics, |writer, found| found.write(writer));
+            }
+            MetadataType::Reference(inner) => {
+                writer.u8(2);
+                inner.write(writer);
+            }
+            MetadataType::Generic(name, bounds) => {
+                writer.u8(3);
+                writer.string(name);
+                writer.list(bounds, |writer, found| found.write(writer));
+            }
+            MetadataType::Array(inner) => {
+                writer.u8(4);
+                inner.write(writer);
+            }
+        }
+    }
+
+    /// Reads this from the metadata
+    fn read(reader: &mut MetadataReader) -> Result<Self, String> {
+        return Ok(match reader.u8()? {
+            0 => MetadataType::Struct(reader.string()?),
+            1 => MetadataType::GenericType(Box::new(MetadataType::read(reader)?), reader.list(MetadataType::read)?),
+            2 => MetadataType::Reference(Box::new(MetadataType::read(reader)?)),
+            3 => MetadataType::Generic(reader.string()?, reader.list(MetadataType::read)?),
+            4 => MetadataType::Array(Box::new(MetadataType::read(reader)?)),
+            tag => return Err(format!("Unknown type tag {}!", tag)),
+        });
+    }
+}
+
+impl MetadataField {
+    /// Converts a finalized field
+    fn from_field(field: &FinalizedMemberField) -> Self {
+        return Self {
+            name: field.field.name.clone(),
+            modifiers: field.modifiers,
+            attributes: field.attributes.clone(),
+            field_type: MetadataType::from_types(&field.field.field_type),
+        };
+    }
+
+    /// Writes this to the metadata
+    fn write(&self, writer: &mut MetadataWriter) {
+        writer.string(&self.name);
+        writer.u8(self.modifiers);
+        writer.list(&self.attributes, write_attribute);
+        self.field_type.write(writer);
+    }
+
+    /// Reads this from the metadata
+    fn read(reader: &mut MetadataReader) -> Result<Self, String> {
+        return Ok(Self {
+            name: reader.string()?,
+            modifiers: reader.u8()?,
+            attributes: reader.list(read_attribute)?,
+            field_type: MetadataType::read(reader)?,
+        });
+    }
+}
+
+impl MetadataStruct {
+    /// Converts a finalized struct
+    fn from_struct(structure: &FinalizedStruct) -> Self {
+        return Self {
+            name: structure.data.name.clone(),
+            id: structure.data.id,
+            modifiers: structure.data.modifiers,
+            attributes: structure.data.attributes.clone(),
+            generics: from_generics(&structure.generics),
+            fields: structure.fields.iter().map(MetadataField::from_field).collect(),
+            functions: structure.data.functions.iter().map(|function| function.name.clone()).collect(),
+        };
+    }
+
+    /// Writes this to the metadata
+    fn write(&self, writer: &mut MetadataWriter) {
+        writer.string(&self.name);
+        writer.u64(self.id);
+        writer.u8(self.modifiers);
+        writer.list(&self.attributes, write_attribute);
+        write_generics(writer, &self.generics);
+        writer.list(&self.fields, |writer, found| found.write(writer));
+        writer.list(&self.functions, |writer, found| writer.string(found));
+    }
+
+    /// Reads this from the metadata
+    fn read(reader: &mut MetadataReader) -> Result<Self, String> {
+        return Ok(Self {
+            name: reader.string()?,
+            id: reader.u64()?,
+            modifiers: reader.u8()?,
+            attributes: reader.list(read_attribute)?,
+            generics: read_generics(reader)?,
+            fields: reader.list(MetadataField::read)?,
+            functions: reader.list(MetadataReader::string)?,
+        });
+    }
+}
+
+impl MetadataFunction {
+    /// Converts a function's signature
+    fn from_function(function: &CodelessFinalizedFunction) -> Self {
+        return Self {
+            name: function.data.name.clone(),
+            modifiers: function.data.modifiers,
+            attributes: function.data.attributes.clone(),
+            generics: from_generics(&function.generics),
+            arguments: function.arguments.iter().map(MetadataField::from_field).collect(),
+            return_type: function.return_type.as_ref().map(MetadataType::from_types),
+        };
+    }
+
+    /// Writes this to the metadata
+    fn write(&self, writer: &mut MetadataWriter) {
+        writer.string(&self.name);
+        writer.u8(self.modifiers);
+        writer.list(&self.attributes, write_attribute);
+        write_generics(writer, &self.generics);
+        writer.list(&self.arguments, |writer, found| found.write(writer));
+        writer.option(&self.return_type, |writer, found| found.write(writer));
+    }
+
+    /// Reads this from the metadata
+    fn read(reader: &mut MetadataReader) -> Result<Self, String> {
+        return Ok(Self {
+            name: reader.string()?,
+            modifiers: reader.u8()?,
+            attributes: reader.list(read_attribute)?,
+            generics: read_generics(reader)?,
+            arguments: reader.list(MetadataField::read)?,
+            return_type: reader.option(MetadataType::read)?,
+        });
+    }
+}
+
+impl MetadataImpl {
+    /// Writes this to the metadata
+    fn write(&self, writer: &mut MetadataWriter) {
+        self.target.write(writer);
+        writer.option(&self.base, |writer, found| found.write(writer));
+        write_generics(writer, &self.generics);
+        writer.list(&self.attributes, write_attribute);
+        writer.list(&self.functions, |writer, found| writer.string(found));
+    }
+
+    /// Reads this from the metadata
+    fn read(reader: &mut MetadataReader) -> Result<Self, String> {
+        return Ok(Self {
+            target: MetadataType::read(reader)?,
+            base: reader.option(MetadataType::read)?,
+            generics: read_generics(reader)?,
+            attributes: reader.list(read_attribute)?,
+            functions: reader.list(MetadataReader::string)?,
+        });
+    }
+}
+
+/// Converts generics and their bounds, keeping their order
+fn from_generics(generics: &IndexMap<String, Vec<FinalizedTypes>>) -> Vec<(String, Vec<MetadataType>)> {
+    return generics
+        .iter()
+        .map(|(name, bounds)| (name.clone(), bounds.iter().map(MetadataType::from_types).collect()))
+        .collect();
+}
+
+/// Writes generics and their bounds
+fn write_generics(writer: &mut MetadataWriter, generics: &Vec<(String, Vec<MetadataType>)>) {
+    writer.list(generics, |writer, (name, bounds)| {
+        writer.string(name);
+        writer.list(bounds, |writer, found| found.write(writer));
+    });
+}
+
+/// Reads generics and their bounds
+fn read_generics(reader: &mut MetadataReader) -> Result<Vec<(String, Vec<MetadataType>)>, String> {
+    return reader.list(|reader| Ok((reader.string()?, reader.list(MetadataType::read)?)));
+}
+
+/// Writes an attribute as its tag, name, and value
+fn write_attribute(writer: &mut MetadataWriter, attribute: &Attribute) {
+    match attribute {
+        Attribute::Basic(name) => {
+            writer.u8(0);
+            writer.string(name);
+        }
+        Attribute::Integer(name, value) => {
+            writer.u8(1);
+            writer.string(name);
+            writer.u64(*value as u64);
+        }
+        Attribute::Bool(name, value) => {
+            writer.u8(2);
+            writer.string(name);
+            writer.u8(*value as u8);
+        }
+        Attribute::String(name, value) => {
+            writer.u8(3);
+            writer.string(name);
+            writer.string(value);
+        }
+    }
+}
+
+/// Reads an attribute written by write_attribute
+fn read_attribute(reader: &mut MetadataReader) -> Result<Attribute, String> {
+    return Ok(match reader.u8()? {
+        0 => Attribute::Basic(reader.string()?),
+        1 => Attribute::Integer(reader.string()?, reader.u64()? as i64),
+        2 => Attribute::Bool(reader.string()?, reader.u8()? != 0),
+        3 => Attribute::String(reader.string()?, reader.string()?),
+        tag => return Err(format!("Unknown attribute tag {}!", tag)),
+    });
+}
+
+/// Writes the primitive parts of the format
+#[derive(Default)]
+struct MetadataWriter {
+    /// The written bytes
+    output: Vec<u8>,
+}
+
+impl MetadataWriter {
+    /// Writes a byte
+    fn u8(&mut self, value: u8) {
+        self.output.push(value);
+    }
+
+    /// Writes a little-endian u64
+    fn u64(&mut self, value: u64) {
+        self.output.extend_from_slice(&value.to_le_bytes());
+    }
+
+    /// Writes a length-prefixed string
+    fn string(&mut self, value: &str) {
+        self.u64(value.len() as u64);
+        self.output.extend_from_slice(value.as_bytes());
+    }
+
+    /// Writes a length-prefixed list, writing each value with the given function
+    fn list<T>(&mut self, values: &Vec<T>, write: impl Fn(&mut Self, &T)) {
+        self.u64(values.len() as u64);
+        for value in values {
+            write(self, value);
+        }
+    }
+
+    /// Writes whether the value exists, then the value if it does
+    fn option<T>(&mut self, value: &Option<T>, write: impl Fn(&mut Self, &T)) {
+        self.u8(value.is_some() as u8);
+        if let Some(value) = value {
+            write(self, value);
+        }
+    }
+}
+
+/// Reads the primitive parts of the format, erroring instead of panicking on truncated data
+struct MetadataReader<'a> {
+    /// The bytes being read
+    bytes: &'a [u8],
+    /// The index of the next byte
+    index: usize,
+}
+
+impl<'a> MetadataReader<'a> {
+    /// Reads the next bytes, erroring if there aren't enough left
+    fn bytes(&mut self, length: usize) -> Result<&'a [u8], String> {
+        let found = self
+            .bytes
+            .get(self.index..self.index.saturating_add(length))
+            .ok_or_else(|| "Unexpected end of metadata!".to_string())?;
+        self.index += length;
+        return Ok(found);
+    }
+
+    /// Reads a byte
+    fn u8(&mut self) -> Result<u8, String> {
+        return Ok(self.bytes(1)?[0]);
+    }
+
+    /// Reads a little-endian u64
+    fn u64(&mut self) -> Result<u64, String> {
+        return Ok(u64::from_le_bytes(self.bytes(8)?.try_into().unwrap()));
+    }
+
+    /// Reads a length-prefixed string
+    fn string(&mut self) -> Result<String, String> {
+        let length = self.u64()? as usize;
+        return String::from_utf8(self.bytes(length)?.to_vec()).map_err(|error| error.to_string());
+    }
+
+    /// Reads a length-prefixed list, reading each value with the given function
+    fn list<T>(&mut self, read: impl Fn(&mut Self) -> Result<T, String>) -> Result<Vec<T>, String> {
+        let length = self.u64()?;
+        let mut output = Vec::default();
+        for _ in 0..length {
+            output.push(read(self)?);
+        }
+        return Ok(output);
+    }
+
+    /// Reads an optional value
+    fn option<T>(&mut self, read: impl Fn(&mut Self) -> Result<T, String>) -> Result<Option<T>, String> {
+        return if self.u8()? != 0 { Ok(Some(read(self)?)) } else { Ok(None) };
+    }
+}
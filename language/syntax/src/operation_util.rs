@@ -1,6 +1,6 @@
 use crate::program::r#struct::StructData;
 use crate::program::syntax::Syntax;
-use crate::ParsingError;
+use crate::{Attribute, ParsingError};
 use parking_lot::Mutex;
 use std::future::Future;
 use std::pin::Pin;
@@ -47,3 +47,18 @@ impl Future for OperationGetter {
         return Poll::Pending;
     }
 }
+
+/// Gets the token of an operation, which is the operation without its values, like "+" for "{}+{}"
+pub fn operation_token(operation: &str) -> String {
+    return operation.replace("{+}", "").replace("{}", "");
+}
+
+/// Gets the priority of an operation and whether it parses left from its attributes, defaulting to 0 and false
+pub fn operation_order(attributes: &Vec<Attribute>) -> (i64, bool) {
+    let priority =
+        Attribute::find_attribute("priority", attributes).map(|inner| inner.as_int_attribute().unwrap_or(0)).unwrap_or(0);
+    let parse_left = Attribute::find_attribute("parse_left", attributes)
+        .map(|inner| inner.as_bool_attribute().unwrap_or(false))
+        .unwrap_or(false);
+    return (priority, parse_left);
+}
@@ -140,8 +140,8 @@ pub enum EffectType {
     NOP,
     /// An effect wrapped in parenthesis, just a wrapper around the effect to prevent issues with operator merging.
     Paren(Box<Effects>),
-    /// Creates a variable with the given name and value.
-    CreateVariable(String, Box<Effects>),
+    /// Creates a variable with the given name and value, and whether it's mutable (declared with let mut).
+    CreateVariable(String, Box<Effects>, bool),
     /// Label of jumping to body
     Jump(String),
     /// Comparison effect, and label to jump to the first if true, second if false
@@ -162,8 +162,9 @@ pub enum EffectType {
     Load(Box<Effects>, String),
     /// An unresolved operation, sent to the checker to resolve, with the given arguments.
     Operation(String, Vec<Effects>),
-    /// Struct to create and a tuple of the name of the field and the argument.
-    CreateStruct(UnparsedType, Vec<(String, Effects)>),
+    /// Struct to create, a tuple of the name of the field and the argument, and the struct to copy the remaining
+    /// fields from (for update syntax like ..other).
+    CreateStruct(UnparsedType, Vec<(String, Effects)>, Option<Box<Effects>>),
     /// Creates an array of the given effects.
     CreateArray(Vec<Effects>),
     /// A float
@@ -269,6 +270,17 @@ pub enum FinalizedEffectType {
     ReferenceLoad(Box<FinalizedEffects>),
     /// Stores an effect on the stack.
     StackStore(Box<FinalizedEffects>),
+    /// Splices raw code from llvm! or asm! into the function, passing it the inputs and getting the output type back.
+    InlineCode(InlineCode, Vec<FinalizedEffects>, Option<FinalizedTypes>),
+}
+
+/// Raw code spliced into an unsafe function, which the compiler passes to LLVM without checking
+#[derive(Clone, Debug)]
+pub enum InlineCode {
+    /// The body of an LLVM IR function that takes the inputs as %0, %1, and so on, and returns the output
+    Llvm(String),
+    /// An assembly template and its LLVM constraints, which list the output first and then each input
+    Asm(String, String),
 }
 
 impl FinalizedEffectType {
@@ -321,6 +333,8 @@ impl FinalizedEffectType {
             Self::HeapAllocate(_) => panic!("Tried to get a type from a heap alloc!"),
             // Returns the target type as an array type.
             Self::CreateArray(types, _) => types.clone().map(|inner| FinalizedTypes::Array(Box::new(inner))),
+            // Inline code returns its declared output.
+            Self::InlineCode(_, _, output) => output.clone(),
         };
     }
 
@@ -355,7 +369,7 @@ impl FinalizedEffectType {
                     effect.types.degeneric(&*manager, variables, syntax, span).await?;
                 }
                 // Calls the degeneric method on the method.
-                *method = degeneric_function(method.clone(), manager, effects, syntax, variables, None).await?;
+                *method = degeneric_function(method.clone(), manager, effects, syntax, variables, None, span).await?;
             }
             Self::GenericMethodCall(function, found_trait, effects) => {
             }
@@ -153,6 +153,12 @@ pub struct CodelessFinalizedFunction {
 }
 
 impl CodelessFinalizedFunction {
+    /// Checks if this is an instance method, which takes self as its first argument and is called like value.method().
+    /// Functions in an impl without a self receiver are associated functions, called like Type::method().
+    pub fn has_receiver(&self) -> bool {
+        return self.arguments.first().is_some_and(|argument| argument.field.name == "self");
+    }
+
     /// Combines the CodelessFinalizedFunction with a FinalizedCodeBody to get a FinalizedFunction.
     pub fn add_code(self, code: FinalizedCodeBody) -> FinalizedFunction {
         return FinalizedFunction {
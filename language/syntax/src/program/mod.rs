@@ -2,6 +2,8 @@
 pub mod code;
 /// Types used to represent functions
 pub mod function;
+/// Prints finalized code for debugging
+pub mod printer;
 /// Types used to represent structs
 pub mod r#struct;
 /// The syntax type, used to represent the entire program
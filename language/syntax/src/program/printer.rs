@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+
+use data::tokens::{Span, Token};
+use data::SourceSet;
+
+use crate::program::code::{ExpressionType, FinalizedEffectType, FinalizedEffects};
+use crate::program::function::{FinalizedCodeBody, FinalizedFunction};
+use crate::{SimpleVariableManager, VariableManager};
+
+/// Finds the source location of spans
+pub struct SourceLocator {
+    /// The path, contents, and tokens of each file, by the file's hash
+    files: HashMap<u64, (String, String, Vec<Token>)>,
+}
+
+impl SourceLocator {
+    /// Creates a new locator, reading the tokens of every file to find spans in
+    pub fn new(sources: &Vec<Box<dyn SourceSet>>) -> Self {
+        let mut files = HashMap::default();
+        for source in sources {
+            for file in source.get_files() {
+                if file.path().ends_with("rv") {
+                    files.insert(file.hash(), (file.path(), file.contents(), file.read()));
+                }
+            }
+        }
+        return Self { files };
+    }
+
+    /// Gets the file, line, and column of a span
+    pub fn location(&self, span: &Span) -> String {
+        return match self.position(span) {
+            Some((path, start, end)) => format!("{}:{}:{}-{}:{}", path, start.0, start.1, end.0, end.1),
+            None => "unknown".to_string(),
+        };
+    }
+
+    /// Gets the file and the starting and ending line and column of a span, or None if the span isn't in a known file
+    pub fn position(&self, span: &Span) -> Option<(&String, (u32, u32), (u32, u32))> {
+        let (path, contents, tokens) = self.files.get(&span.file)?;
+        if span.start >= tokens.len() {
+            return None;
+        }
+        let start = &tokens[span.start];
+        let end = &tokens[span.end.min(tokens.len() - 1)];
+        let (start_column, _) = start.character_columns(contents.as_bytes());
+        let (_, end_column) = end.character_columns(contents.as_bytes());
+        return Some((path, (start.start.0, start_column), (end.end.0, end_column)));
+    }
+
+    /// Gets the file and line a span starts on, or None if the span isn't in a known file.
+    /// Like errors, a token spanning multiple lines is counted as being on its last line.
+    pub fn line(&self, span: &Span) -> Option<(&String, u32)> {
+        let (path, _, tokens) = self.files.get(&span.file)?;
+        return tokens.get(span.start).map(|token| (path, token.end.0));
+    }
+}
+
+/// Prints finalized code with the type and source location of every effect, used by --emit=checked-ast.
+/// Unlike the Debug output, this is meant to be read when debugging the checker.
+pub struct CheckedPrinter {
+    /// Finds the location of each effect
+    locator: SourceLocator,
+    /// The output
+    output: String,
+}
+
+impl CheckedPrinter {
+    /// Creates a new printer, reading the tokens of every file to find spans in
+    pub fn new(sources: &Vec<Box<dyn SourceSet>>) -> Self {
+        return Self { locator: SourceLocator::new(sources), output: String::default() };
+    }
+
+    /// Prints a single function, returning the output
+    pub fn print_function(&mut self, function: &FinalizedFunction) -> String {
+        self.output.clear();
+        let arguments = function
+            .fields
+            .iter()
+            .map(|field| format!("{}: {}", field.field.name, field.field.field_type))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let returning = function.return_type.as_ref().map(|inner| format!(" -> {}", inner)).unwrap_or_default();
+        let header = format!(
+            "fn {}({}){} @ {}\n",
+            function.data.name,
+            arguments,
+            returning,
+            self.locator.location(&function.data.span)
+        );
+        self.output += &header;
+
+        let mut variables = SimpleVariableManager::for_final_function(function);
+        self.print_body(&function.code, &mut variables, 1);
+        return self.output.clone();
+    }
+
+    /// Prints a code body and all of its expressions
+    fn print_body(&mut self, body: &FinalizedCodeBody, variables: &mut SimpleVariableManager, depth: usize) {
+        self.output += &format!("{}{}:{}\n", "  ".repeat(depth), body.label, if body.returns { " (returns)" } else { "" });
+        for expression in &body.expressions {
+            match expression.expression_type {
+                ExpressionType::Return(_) => self.output += &format!("{}return\n", "  ".repeat(depth + 1)),
+                ExpressionType::Break => self.output += &format!("{}break\n", "  ".repeat(depth + 1)),
+                ExpressionType::Line => {}
+            }
+            self.print_effect(&expression.effect, variables, depth + 2);
+        }
+    }
+
+    /// Prints an effect with its type and location, then all of its inner effects one level deeper
+    fn print_effect(&mut self, effect: &FinalizedEffects, variables: &mut SimpleVariableManager, depth: usize) {
+        let (name, inner): (String, Vec<&FinalizedEffects>) = match &effect.types {
+            FinalizedEffectType::NOP => ("NOP".to_string(), vec![]),
+            FinalizedEffectType::CreateVariable(name, value, types) => {
+                variables.variables.insert(name.clone(), types.clone());
+                (format!("CreateVariable {}", name), vec![&**value])
+            }
+            FinalizedEffectType::Jump(label) => (format!("Jump {}", label), vec![]),
+            FinalizedEffectType::CompareJump(comparing, first, second) => {
+                (format!("CompareJump {} else {}", first, second), vec![&**comparing])
+            }
+            FinalizedEffectType::CodeBody(body) => {
+                let line = format!("{}CodeBody @ {}\n", "  ".repeat(depth), self.locator.location(&effect.span));
+                self.output += &line;
+                self.print_body(body, variables, depth + 1);
+                return;
+            }
+            FinalizedEffectType::MethodCall(calling, function, arguments, _) => {
+                let mut inner = calling.iter().map(|inner| &**inner).collect::<Vec<_>>();
+                inner.extend(arguments);
+                (format!("MethodCall {}", function.data.name), inner)
+            }
+            FinalizedEffectType::GenericMethodCall(function, target, arguments) => {
+                (format!("GenericMethodCall {} on {}", function.data.name, target), arguments.iter().collect())
+            }
+            FinalizedEffectType::Set(setting, value) => ("Set".to_string(), vec![&**setting, &**value]),
+            FinalizedEffectType::LoadVariable(name) => (format!("LoadVariable {}", name), vec![]),
+            FinalizedEffectType::Load(loading, name, _) => (format!("Load {}", name), vec![&**loading]),
+            FinalizedEffectType::CreateStruct(target, _, fields) => {
+                let mut inner = target.iter().map(|inner| &**inner).collect::<Vec<_>>();
+                inner.extend(fields.iter().map(|(_, field)| field));
+                ("CreateStruct".to_string(), inner)
+            }
+            FinalizedEffectType::CreateArray(_, values) => ("CreateArray".to_string(), values.iter().collect()),
+            FinalizedEffectType::Float(value) => (format!("Float {}", value), vec![]),
+            FinalizedEffectType::UInt(value) => (format!("UInt {}", value), vec![]),
+            FinalizedEffectType::Bool(value) => (format!("Bool {}", value), vec![]),
+            FinalizedEffectType::String(value) => (format!("String {:?}", value), vec![]),
+            FinalizedEffectType::Char(value) => (format!("Char {:?}", value), vec![]),
+            FinalizedEffectType::VirtualCall(index, function, arguments, _) => {
+                (format!("VirtualCall {} ({})", function.data.name, index), arguments.iter().collect())
+            }
+            FinalizedEffectType::GenericVirtualCall(index, target, function, arguments, _) => (
+                format!("GenericVirtualCall {} for {} ({})", function.data.name, target.name, index),
+                arguments.iter().collect(),
+            ),
+            FinalizedEffectType::Downcast(downcasting, _, _) => ("Downcast".to_string(), vec![&**downcasting]),
+            FinalizedEffectType::HeapStore(storing) => ("HeapStore".to_string(), vec![&**storing]),
+            FinalizedEffectType::HeapAllocate(types) => {
+                // Heap allocations have no return type, so the allocated type is printed instead
+                let line =
+                    format!("{}HeapAllocate {} @ {}\n", "  ".repeat(depth), types, self.locator.location(&effect.span));
+                self.output += &line;
+                return;
+            }
+            FinalizedEffectType::ReferenceLoad(loading) => ("ReferenceLoad".to_string(), vec![&**loading]),
+            FinalizedEffectType::StackStore(storing) => ("StackStore".to_string(), vec![&**storing]),
+            FinalizedEffectType::InlineCode(code, inputs, _) => (format!("InlineCode {:?}", code), inputs.iter().collect()),
+        };
+
+        let types = match &effect.types {
+            // Unknown variables would panic in get_nongeneric_return, and the printer shouldn't crash on bad code
+            FinalizedEffectType::LoadVariable(name) => variables.get_variable(name),
+            _ => effect.types.get_nongeneric_return(variables),
+        }
+        .map(|inner| inner.to_string())
+        .unwrap_or("void".to_string());
+        let line = format!("{}{}: {} @ {}\n", "  ".repeat(depth), name, types, self.locator.location(&effect.span));
+        self.output += &line;
+        for effect in inner {
+            self.print_effect(effect, variables, depth + 1);
+        }
+    }
+}
@@ -18,13 +18,17 @@ use tokio::sync::mpsc::Receiver;
 use async_recursion::async_recursion;
 use async_trait::async_trait;
 // Re-export main
+use data::stats;
 use data::tokens::Span;
 pub use data::Main;
 
 use crate::async_util::{AsyncStructImplGetter, AsyncTypesGetter, NameResolver, UnparsedType};
 use crate::chalk_interner::ChalkIr;
 use crate::errors::{ErrorSource, ParsingMessage};
+use crate::impl_index::ImplIndex;
+use crate::operation_util::{operation_order, operation_token};
 use crate::program::function::{FinalizedFunction, FunctionData};
+use crate::program::printer::SourceLocator;
 use crate::program::r#struct::{FinalizedStruct, StructData, BOOL, F32, F64, I16, I32, I64, I8, STR, U16, U32, U64, U8};
 use crate::program::types::FinalizedTypes;
 use crate::top_element_manager::{GetterManager, TopElementManager};
@@ -54,6 +58,8 @@ pub struct Syntax {
     pub functions: TopElementManager<FunctionData>,
     /// All implementations of a trait in the program
     pub implementations: Vec<Arc<FinishedTraitImplementor>>,
+    /// The implementations indexed by trait and type, kept up to date by Syntax::add_implementation
+    pub impl_index: ImplIndex,
     /// All implementations of a struct in the program
     pub struct_implementations: HashMap<FinalizedTypes, Vec<Arc<FinishedStructImplementor>>>,
     /// The parsing state
@@ -65,6 +71,49 @@ pub struct Syntax {
     pub operation_wakers: HashMap<String, Vec<Waker>>,
     /// Manages the next steps of compilation after parsing
     pub process_manager: Box<dyn ProcessManager>,
+    /// The enabled features, checked by #[cfg(feature = "name")] attributes
+    pub features: Vec<String>,
+    /// How deeply expressions and generic types can nest before erroring, instead of overflowing the stack
+    pub recursion_limit: u32,
+    /// The edition of the project being compiled, one of EDITIONS
+    pub edition: u32,
+    /// The edition of each library file written for a different edition than the project, by the file's hash,
+    /// so libraries are checked with the edition they were written for
+    pub file_editions: HashMap<u64, u32>,
+    /// The spans of every call to each instantiation of a generic function, like foo$u64,
+    /// only recorded for --dump-instantiations
+    pub instantiations: Option<HashMap<String, Vec<Span>>>,
+    /// The imports of each file by the file's hash, the first being the file's own module,
+    /// so names in the file can be resolved after parsing
+    pub file_imports: HashMap<u64, Vec<String>>,
+    /// The spans of every call to each function, by the name of the function the checker resolved it to,
+    /// only recorded for the language server's references and renaming
+    pub call_sites: Option<HashMap<String, Vec<Span>>>,
+    /// The extern function marked #[allocator], which is called instead of libc's malloc. Only set for --no-std
+    pub allocator: Option<String>,
+    /// The extern function marked #[panic_handler], which is called instead of the runtime's panic hook.
+    /// Only set for --no-std
+    pub panic_handler: Option<String>,
+    /// Finds the lines of spans for the compiler's debug info, only set for --debug-info
+    pub source_locator: Option<Arc<SourceLocator>>,
+}
+
+/// The recursion limit if the compiler arguments don't set one
+pub const DEFAULT_RECURSION_LIMIT: u32 = 256;
+
+/// Every edition of the language, oldest first. Changes that would stop existing programs from compiling are only made
+/// in a new edition, so programs can keep using the edition they were written for
+pub const EDITIONS: [u32; 2] = [2024, 2025];
+
+/// The newest edition, which the core and std libraries are written for
+pub const LATEST_EDITION: u32 = 2025;
+
+/// The edition of projects that don't set one, the oldest so programs written before editions existed keep compiling
+pub const DEFAULT_EDITION: u32 = EDITIONS[0];
+
+/// Gets the full name of a name if it's in the import, like math::Add for Add in math
+pub fn with_import(import: &str, name: &str) -> String {
+    return if import.ends_with(name) { import.to_string() } else { format!("{}::{}", import, name) };
 }
 
 impl Syntax {
@@ -92,14 +141,39 @@ impl Syntax {
                 STR.data.clone(),
             ]),
             implementations: Vec::default(),
+            impl_index: ImplIndex::default(),
             struct_implementations: HashMap::default(),
             async_manager: GetterManager::default(),
             operations: HashMap::default(),
             operation_wakers: HashMap::default(),
             process_manager,
+            features: Vec::default(),
+            recursion_limit: DEFAULT_RECURSION_LIMIT,
+            edition: DEFAULT_EDITION,
+            file_editions: HashMap::default(),
+            instantiations: None,
+            file_imports: HashMap::default(),
+            call_sites: None,
+            allocator: None,
+            panic_handler: None,
+            source_locator: None,
         };
     }
 
+    /// Resolves a struct or function name written in the file to its full name, the same way the checker does:
+    /// the name as written first, then the name in each of the file's imports
+    pub fn resolve_name(&self, file: u64, name: &str) -> Option<String> {
+        let exists = |name: &String| self.structures.types.contains_key(name) || self.functions.types.contains_key(name);
+        let mut names = vec![name.to_string()];
+        names.extend(self.file_imports.get(&file).into_iter().flatten().map(|import| with_import(import, name)));
+        return names.into_iter().find(exists);
+    }
+
+    /// Gets the edition the file with the hash is checked with
+    pub fn edition_of(&self, file: u64) -> u32 {
+        return self.file_editions.get(&file).copied().unwrap_or(self.edition);
+    }
+
     /// Adds a function to the compiling list
     pub async fn add_compiling(
         process_manager: Box<dyn ProcessManager>,
@@ -231,21 +305,77 @@ impl Syntax {
         trait_type: &FinalizedTypes,
     ) -> Option<Vec<(Arc<FinishedTraitImplementor>, Vec<Arc<FunctionData>>)>> {
         let mut output = Vec::default();
-        let implementations = {
-            let locked = syntax.lock();
-            locked.implementations.clone()
-        };
-
+        let implementations = syntax.lock().trait_implementations(trait_type);
         for implementation in &implementations {
-            if trait_type.of_type_sync(&implementation.target, None).0
-                && struct_type.of_type(&implementation.base, syntax.clone()).await
-            {
+            if struct_type.of_type(&implementation.base, syntax.clone()).await {
                 output.push((implementation.clone(), implementation.functions.clone()));
             }
         }
         return if output.is_empty() { None } else { Some(output) };
     }
 
+    /// Gets every implementation of the trait, for any type.
+    /// May not be complete if the syntax isn't finished parsing implementations, check Syntax::finished_impls.
+    pub fn trait_implementations(&self, trait_type: &FinalizedTypes) -> Vec<Arc<FinishedTraitImplementor>> {
+        // Only implementations of the trait can match, unless it's a generic that has to be checked against everything
+        let implementations: Vec<Arc<FinishedTraitImplementor>> = match ImplIndex::key(trait_type) {
+            Some(key) => {
+                self.impl_index.of_trait(&key).iter().map(|position| self.implementations[*position].clone()).collect()
+            }
+            None => self.implementations.clone(),
+        };
+        return implementations
+            .into_iter()
+            .filter(|implementation| trait_type.of_type_sync(&implementation.target, None).0)
+            .collect();
+    }
+
+    /// Adds a trait implementation and indexes it
+    pub fn add_implementation(&mut self, implementor: Arc<FinishedTraitImplementor>) {
+        self.impl_index.add(self.implementations.len(), &implementor);
+        self.implementations.push(implementor);
+    }
+
+    /// Finds every implementation of the trait with the name, either the full name like "iter::Iter" or just "Iter".
+    /// May not be complete if the syntax isn't finished parsing implementations, check Syntax::finished_impls.
+    pub fn implementors_of(&self, trait_name: &str) -> Vec<Arc<FinishedTraitImplementor>> {
+        let mut output = Vec::default();
+        for key in self.impl_index.trait_keys(trait_name) {
+            output.extend(self.impl_index.of_trait(key).iter().map(|position| self.implementations[*position].clone()));
+        }
+        return output;
+    }
+
+    /// Finds every trait implementation for the type with the name, either the full name or just the last part.
+    /// Blanket implementations like impl<T: Bound> Trait for T are included if the type meets their bounds.
+    /// May not be complete if the syntax isn't finished parsing implementations, check Syntax::finished_impls.
+    pub fn traits_of(&self, type_name: &str) -> Vec<Arc<FinishedTraitImplementor>> {
+        let mut output = Vec::default();
+        for key in self.impl_index.type_keys(type_name) {
+            output.extend(self.impl_index.of_type(key).iter().map(|position| self.implementations[*position].clone()));
+        }
+
+        let found = match self.structures.types.get(type_name) {
+            Some(found) => Some(found.clone()),
+            None => self
+                .structures
+                .types
+                .iter()
+                .find(|(key, _)| key.rsplit("::").next() == Some(type_name))
+                .map(|(_, found)| found.clone()),
+        };
+        if let Some(found) = found.and_then(|found| self.structures.data.get(&found).cloned()) {
+            let types = FinalizedTypes::Struct(found);
+            for position in self.impl_index.blanket() {
+                let implementation = &self.implementations[*position];
+                if self.solve(&types, &implementation.target) {
+                    output.push(implementation.clone());
+                }
+            }
+        }
+        return output;
+    }
+
     /// Recursively solves if a type is a generic type by checking if the target type matches all the bounds.
     fn solve_nonstruct_types(&self, target_type: &FinalizedTypes, checking: &FinalizedTypes) -> Option<bool> {
         return match target_type {
@@ -317,11 +447,13 @@ impl Syntax {
     }
 
     pub fn add_function(syntax: &Arc<Mutex<Syntax>>, adding: &mut Arc<FunctionData>) {
+        stats::PARSED_FUNCTIONS.add();
         let mut locked = syntax.lock();
         locked.add(adding);
     }
 
     pub fn add_struct(syntax: &Arc<Mutex<Syntax>>, adding: &mut Arc<StructData>) {
+        stats::PARSED_STRUCTS.add();
         let mut locked = syntax.lock();
         locked.add(adding);
         if adding.is_operator() {
@@ -339,6 +471,29 @@ impl Syntax {
                 locked.errors.push(adding.get_span().make_error(ParsingMessage::DuplicateStructure()));
             }
 
+            // Operations sharing a token with the same priority have to agree on parse_left, or else the order they're
+            // applied in would depend on which one is written first.
+            let order = operation_order(&adding.attributes);
+            let token = operation_token(&name);
+            let mut conflicts = locked
+                .operations
+                .keys()
+                .filter(|other| **other != name && operation_token(other) == token)
+                .filter(|other| {
+                    let other_order = operation_order(&locked.operations[*other].attributes);
+                    other_order.0 == order.0 && other_order.1 != order.1
+                })
+                .cloned()
+                .collect::<Vec<_>>();
+            conflicts.sort();
+            for other in conflicts {
+                locked.errors.push(adding.get_span().make_error(ParsingMessage::UnorderedOperators(
+                    name.clone(),
+                    other,
+                    order.0,
+                )));
+            }
+
             locked.operations.insert(name.clone(), adding.clone());
 
             // Wakes every waker waiting for that operation.
@@ -547,9 +702,6 @@ impl Syntax {
                     );
                 }
 
-                if generics.is_empty() {
-                    println!("Found with no generics!");
-                }
                 Ok(Types::GenericType(
                     Box::new(Self::parse_type(syntax, error, resolver, *name, resolved_generics).await?),
                     generics,
@@ -117,7 +117,6 @@ impl<F: AsyncFnMut(Arc<FinishedTraitImplementor>, Arc<FunctionData>) -> Result<F
                         None => {}
                     }
                     if finished {
-                        println!("Failed!");
                         Poll::Ready(Err(self.error.clone()))
                     } else {
                         self.syntax.lock().async_manager.impl_waiters.push(cx.waker().clone());
@@ -0,0 +1,15 @@
+#include <stdint.h>
+
+/* Fields declaring multiple names, where only some are pointers */
+typedef struct point {
+    int x, y;
+    int *data, count;
+} point;
+
+// Structs are only passed by pointer, as addresses
+struct point *point_new(int x, int y);
+void point_free(struct point *);
+int point_sum(const point *p);
+// Unnamed struct arguments are a type, not a type named struct
+void by_value(struct point);
+unsigned long count_points(const struct point *points, unsigned long);
@@ -0,0 +1,53 @@
+use std::sync::Arc;
+
+use crossbeam_channel::Sender;
+use lsp_server::{Message, RequestId};
+use lsp_types::request::{GotoImplementationParams, GotoImplementationResponse};
+use lsp_types::{Location, Position, Range, Url};
+use parking_lot::Mutex;
+
+use data::SourceSet;
+use syntax::program::printer::SourceLocator;
+use syntax::program::syntax::Syntax;
+
+use crate::rename::{name_at, send};
+
+/// Sends every implementation of the trait under the cursor to the IDE.
+/// If the name isn't a trait, it's treated as a type and the implementations of traits for it are sent instead.
+pub fn send_implementations(
+    id: RequestId,
+    params: GotoImplementationParams,
+    document: String,
+    sources: Vec<Box<dyn SourceSet>>,
+    syntax: Arc<Mutex<Syntax>>,
+    sender: Sender<Message>,
+) {
+    let result = name_at(&document, params.text_document_position_params.position).map(|name| {
+        let implementations = {
+            let locked = syntax.lock();
+            let found = locked.implementors_of(&name);
+            if found.is_empty() {
+                locked.traits_of(&name)
+            } else {
+                found
+            }
+        };
+
+        let locator = SourceLocator::new(&sources);
+        let locations = implementations
+            .iter()
+            .filter_map(|implementation| {
+                let (path, start, end) = locator.position(&implementation.span)?;
+                Some(Location {
+                    uri: Url::from_file_path(path).ok()?,
+                    range: Range {
+                        start: Position { line: start.0 - 1, character: start.1 },
+                        end: Position { line: end.0 - 1, character: end.1 },
+                    },
+                })
+            })
+            .collect::<Vec<_>>();
+        GotoImplementationResponse::Array(locations)
+    });
+    send(id, serde_json::to_value(&result).unwrap(), sender);
+}
@@ -4,20 +4,26 @@ use std::fs;
 
 use lsp_server::{Connection, ExtractError, Message, Notification, Request, RequestId};
 use lsp_types::notification::{DidChangeTextDocument, DidOpenTextDocument};
-use lsp_types::request::{GotoDeclaration, SemanticTokensFullRequest};
+use lsp_types::request::{GotoDeclaration, GotoImplementation, References, Rename, SemanticTokensFullRequest};
 use lsp_types::{
-    DeclarationCapability, InitializeParams, SemanticTokenModifier, SemanticTokenType, SemanticTokensFullOptions,
-    SemanticTokensLegend, SemanticTokensOptions, SemanticTokensServerCapabilities, ServerCapabilities,
-    TextDocumentSyncCapability, TextDocumentSyncKind, Url, WorkDoneProgressOptions,
+    DeclarationCapability, ImplementationProviderCapability, InitializeParams, OneOf, SemanticTokenModifier,
+    SemanticTokenType, SemanticTokensFullOptions, SemanticTokensLegend, SemanticTokensOptions,
+    SemanticTokensServerCapabilities, ServerCapabilities, TextDocumentSyncCapability, TextDocumentSyncKind, Url,
+    WorkDoneProgressOptions,
 };
 use tokio::runtime::Builder;
 
+use crate::implementation::send_implementations;
+use crate::rename::{send_references, send_rename};
 use crate::semantic_tokens::{parse_semantic_tokens, TokenIterator};
 use crate::syntax_manager::SyntaxManager;
 
 /// This file is templated from Rust's LSP example.
+mod implementation;
+mod rename;
 mod semantic_tokens;
 mod syntax_manager;
+mod test;
 
 /// The main function, which sets up the server and starts the main loop
 pub fn main() -> Result<(), Box<dyn Error + Sync + Send>> {
@@ -28,6 +34,11 @@ pub fn main() -> Result<(), Box<dyn Error + Sync + Send>> {
     // Run the server and wait for the two threads to end (typically by trigger LSP Exit event).
     let server_capabilities = serde_json::to_value(&ServerCapabilities {
         declaration_provider: Some(DeclarationCapability::Simple(true)),
+        // References and renaming both search the whole project for usages of the definition
+        references_provider: Some(OneOf::Left(true)),
+        rename_provider: Some(OneOf::Left(true)),
+        // Implementations are looked up in the checker's index of impls
+        implementation_provider: Some(ImplementationProviderCapability::Simple(true)),
         // Semantic tokens provider gives the coloring of tokens
         semantic_tokens_provider: Some(SemanticTokensServerCapabilities::SemanticTokensOptions(SemanticTokensOptions {
             work_done_progress_options: WorkDoneProgressOptions::default(),
@@ -116,9 +127,12 @@ fn main_loop(connection: Connection, params: serde_json::Value) -> Result<(), Bo
                     Err(err @ ExtractError::JsonError { .. }) => panic!("{:?}", err),
                     Err(ExtractError::MethodMismatch(req)) => req,
                 };
-                match cast::<GotoDeclaration>(req) {
+                let req = match cast::<GotoDeclaration>(req) {
                     Ok((id, params)) => {
-                        syntax.get_syntax(params.text_document_position_params.text_document.uri.to_file_path().unwrap());
+                        syntax.get_syntax(
+                            params.text_document_position_params.text_document.uri.to_file_path().unwrap(),
+                            &documents,
+                        );
                         let mut position = params.text_document_position_params.position;
                         position.line += 1;
                         let token = TokenIterator::new(
@@ -149,6 +163,48 @@ fn main_loop(connection: Connection, params: serde_json::Value) -> Result<(), Bo
                     Err(err @ ExtractError::JsonError { .. }) => panic!("{:?}", err),
                     Err(ExtractError::MethodMismatch(req)) => req,
                 };
+                let req = match cast::<References>(req) {
+                    Ok((id, params)) => {
+                        let uri = &params.text_document_position.text_document.uri;
+                        let document = documents[uri].clone();
+                        let file = uri.to_file_path().unwrap();
+                        let sources = SyntaxManager::get_project(&file, &documents);
+                        let found = syntax.get_syntax(file, &documents);
+                        let sender = connection.sender.clone();
+                        pool.spawn(async move { send_references(id, params, document, sources, found, sender) });
+                        continue;
+                    }
+                    Err(err @ ExtractError::JsonError { .. }) => panic!("{:?}", err),
+                    Err(ExtractError::MethodMismatch(req)) => req,
+                };
+                let req = match cast::<GotoImplementation>(req) {
+                    Ok((id, params)) => {
+                        let uri = &params.text_document_position_params.text_document.uri;
+                        let document = documents[uri].clone();
+                        let file = uri.to_file_path().unwrap();
+                        let sources = SyntaxManager::get_project(&file, &documents);
+                        let found = syntax.get_syntax(file, &documents);
+                        let sender = connection.sender.clone();
+                        pool.spawn(async move { send_implementations(id, params, document, sources, found, sender) });
+                        continue;
+                    }
+                    Err(err @ ExtractError::JsonError { .. }) => panic!("{:?}", err),
+                    Err(ExtractError::MethodMismatch(req)) => req,
+                };
+                let _req = match cast::<Rename>(req) {
+                    Ok((id, params)) => {
+                        let uri = &params.text_document_position.text_document.uri;
+                        let document = documents[uri].clone();
+                        let file = uri.to_file_path().unwrap();
+                        let sources = SyntaxManager::get_project(&file, &documents);
+                        let found = syntax.get_syntax(file, &documents);
+                        let sender = connection.sender.clone();
+                        pool.spawn(async move { send_rename(id, params, document, sources, found, sender) });
+                        continue;
+                    }
+                    Err(err @ ExtractError::JsonError { .. }) => panic!("{:?}", err),
+                    Err(ExtractError::MethodMismatch(req)) => req,
+                };
             }
             Message::Response(_resp) => {}
             Message::Notification(not) => {
@@ -171,6 +227,8 @@ fn main_loop(connection: Connection, params: serde_json::Value) -> Result<(), Bo
                         }
                         // Assume it's only one thing being changed across the whole document
                         documents.insert(params.text_document.uri, params.content_changes[0].text.clone());
+                        // Any file can depend on the changed one, so every syntax is rebuilt with the new contents
+                        syntax.parents.clear();
                         continue;
                     }
                     Err(err @ ExtractError::JsonError { .. }) => panic!("{:?}", err),
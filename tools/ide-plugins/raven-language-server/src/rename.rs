@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crossbeam_channel::Sender;
+use lsp_server::{Message, RequestId, Response};
+use lsp_types::{Location, Position, Range, ReferenceParams, RenameParams, TextEdit, Url, WorkspaceEdit};
+use parking_lot::Mutex;
+
+use data::SourceSet;
+use parser::references::{definition_at, find_references, Reference, ReferenceType};
+use syntax::program::syntax::Syntax;
+
+/// Sends every reference to the definition under the cursor to the IDE
+pub fn send_references(
+    id: RequestId,
+    params: ReferenceParams,
+    document: String,
+    sources: Vec<Box<dyn SourceSet>>,
+    syntax: Arc<Mutex<Syntax>>,
+    sender: Sender<Message>,
+) {
+    let syntax = syntax.lock();
+    let position = &params.text_document_position;
+    let definition = definition_under(&document, &position.text_document.uri, position.position, &sources, &syntax);
+    let result = definition.map(|name| {
+        find_references(&sources, &syntax, &name)
+            .iter()
+            .filter(|reference| params.context.include_declaration || reference.reference_type != ReferenceType::Definition)
+            .map(|reference| Location { uri: Url::from_file_path(&reference.file).unwrap(), range: get_range(reference) })
+            .collect::<Vec<_>>()
+    });
+    send(id, serde_json::to_value(&result).unwrap(), sender);
+}
+
+/// Sends the edits required to rename the definition under the cursor to the IDE
+pub fn send_rename(
+    id: RequestId,
+    params: RenameParams,
+    document: String,
+    sources: Vec<Box<dyn SourceSet>>,
+    syntax: Arc<Mutex<Syntax>>,
+    sender: Sender<Message>,
+) {
+    let syntax = syntax.lock();
+    let position = &params.text_document_position;
+    let definition = definition_under(&document, &position.text_document.uri, position.position, &sources, &syntax);
+    let result = definition.map(|name| {
+        let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::default();
+        // Operators call the trait through its attribute, so they stay the same when it's renamed
+        for reference in find_references(&sources, &syntax, &name) {
+            if reference.reference_type == ReferenceType::Operator {
+                continue;
+            }
+            changes
+                .entry(Url::from_file_path(&reference.file).unwrap())
+                .or_default()
+                .push(TextEdit { range: get_range(&reference), new_text: params.new_name.clone() });
+        }
+        WorkspaceEdit { changes: Some(changes), ..Default::default() }
+    });
+    send(id, serde_json::to_value(&result).unwrap(), sender);
+}
+
+/// Finds the full name of the definition referenced under the cursor, like math::Add
+fn definition_under(
+    document: &str,
+    uri: &Url,
+    position: Position,
+    sources: &Vec<Box<dyn SourceSet>>,
+    syntax: &Syntax,
+) -> Option<String> {
+    let path = uri.to_file_path().ok()?;
+    return definition_at(sources, syntax, path.to_str()?, offset_of(document, position)?);
+}
+
+/// Finds the full (possibly qualified) name under the cursor as it's written
+pub(crate) fn name_at(document: &str, position: Position) -> Option<String> {
+    let cursor = offset_of(document, position)?;
+    let document = document.as_bytes();
+    // Any byte of a multi-byte character is part of a name, since identifiers can contain them
+    let is_name =
+        |character: u8| character.is_ascii_alphanumeric() || character == b'_' || character == b':' || !character.is_ascii();
+    let mut start = cursor;
+    let mut end = cursor;
+    while start > 0 && is_name(document[start - 1]) {
+        start -= 1;
+    }
+    while end < document.len() && is_name(document[end]) {
+        end += 1;
+    }
+
+    // Anything after the segment under the cursor isn't part of the definition, like the "add" in "Add::add"
+    if let Some(found) = document[cursor..end].windows(2).position(|window| window == b"::") {
+        end = cursor + found;
+    }
+    let name = String::from_utf8_lossy(&document[start..end]).trim_matches(':').to_string();
+    return if name.is_empty() { None } else { Some(name) };
+}
+
+/// Converts an LSP position to an offset in the document. Positions count columns in UTF-16 code units, not bytes,
+/// and a column past the end of the line is the end of the line
+pub(crate) fn offset_of(document: &str, position: Position) -> Option<usize> {
+    let mut offset = 0;
+    for (number, line) in document.split_inclusive('\n').enumerate() {
+        if number == position.line as usize {
+            let mut column = 0;
+            for (index, character) in line.char_indices() {
+                if column >= position.character || character == '\n' {
+                    return Some(offset + index);
+                }
+                column += character.len_utf16() as u32;
+            }
+            return Some(offset + line.len());
+        }
+        offset += line.len();
+    }
+    // The cursor can be on the empty line after a trailing newline
+    let after_newline = document.is_empty() || document.ends_with('\n');
+    return if after_newline && position.line as usize == document.split_inclusive('\n').count() {
+        Some(document.len())
+    } else {
+        None
+    };
+}
+
+/// Converts a reference to an LSP range
+fn get_range(reference: &Reference) -> Range {
+    return Range {
+        start: Position { line: reference.start.0 - 1, character: reference.start.1 },
+        end: Position { line: reference.end.0 - 1, character: reference.end.1 },
+    };
+}
+
+/// Sends the result back to the IDE
+pub(crate) fn send(id: RequestId, result: serde_json::Value, sender: Sender<Message>) {
+    sender.send(Message::Response(Response { id, result: Some(result), error: None })).unwrap();
+}
@@ -98,6 +98,9 @@ fn get_token(last: &TokenTypes, token_type: &TokenTypes) -> u32 {
         | TokenTypes::False
         | TokenTypes::Else
         | TokenTypes::Do
+        | TokenTypes::Defer
+        | TokenTypes::Break
+        | TokenTypes::Continue
         | TokenTypes::While
         | TokenTypes::In => SemanticTokenTypes::Keyword,
         TokenTypes::Comment => SemanticTokenTypes::Comment,
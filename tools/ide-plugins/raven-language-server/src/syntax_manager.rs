@@ -5,8 +5,9 @@ use std::sync::Arc;
 use parking_lot::Mutex;
 
 use data::{Arguments, CompilerArguments, RunnerSettings, SourceSet};
+use lsp_types::Url;
 use magpie_lib::build_project;
-use parser::FileSourceSet;
+use parser::{FileSourceSet, OverlaySourceSet};
 use syntax::program::syntax::Syntax;
 
 #[derive(Default)]
@@ -15,21 +16,25 @@ pub struct SyntaxManager {
 }
 
 impl SyntaxManager {
-    pub fn get_syntax(&mut self, file: PathBuf) -> Arc<Mutex<Syntax>> {
+    pub fn get_syntax(&mut self, file: PathBuf, documents: &HashMap<Url, String>) -> Arc<Mutex<Syntax>> {
         if let Some(found) = self.parents.get(&file) {
             return found.clone();
         }
-        return self.update_syntax(file);
+        return self.update_syntax(file, documents);
     }
 
-    pub fn update_syntax(&mut self, file: PathBuf) -> Arc<Mutex<Syntax>> {
-        let mut directory = Self::get_project(&file);
+    pub fn update_syntax(&mut self, file: PathBuf, documents: &HashMap<Url, String>) -> Arc<Mutex<Syntax>> {
+        let mut directory = Self::get_project(&file, documents);
 
         let mut arguments = Arguments::build_args(
             false,
             RunnerSettings {
                 sources: vec![],
-                compiler_arguments: CompilerArguments { compiler: "llvm".to_string(), ..Default::default() },
+                compiler_arguments: CompilerArguments {
+                    compiler: "llvm".to_string(),
+                    record_call_sites: true,
+                    ..Default::default()
+                },
             },
         );
 
@@ -37,7 +42,9 @@ impl SyntaxManager {
         return self.parents.get(&file).unwrap().clone();
     }
 
-    fn get_project(file: &PathBuf) -> Vec<Box<dyn SourceSet>> {
+    /// Gets the source sets of the project the file is in, with the open documents shadowing the files on disk
+    /// so unsaved changes are included
+    pub fn get_project(file: &PathBuf, documents: &HashMap<Url, String>) -> Vec<Box<dyn SourceSet>> {
         let mut directory = file.parent();
         while let Some(dir) = directory {
             if dir.join("build.rv").exists() {
@@ -45,6 +52,13 @@ impl SyntaxManager {
             }
             directory = dir.parent();
         }
-        return vec![Box::new(FileSourceSet { root: directory.map(|inner| inner.to_path_buf()).unwrap_or(file.clone()) })];
+        let overlays = documents
+            .iter()
+            .filter_map(|(uri, text)| Some((uri.to_file_path().ok()?.to_str()?.to_string(), text.clone())))
+            .collect();
+        return vec![Box::new(OverlaySourceSet {
+            inner: Box::new(FileSourceSet { root: directory.map(|inner| inner.to_path_buf()).unwrap_or(file.clone()) }),
+            overlays: Arc::new(overlays),
+        })];
     }
 }
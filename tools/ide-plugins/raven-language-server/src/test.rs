@@ -0,0 +1,156 @@
+#[cfg(test)]
+mod test {
+    use std::fs;
+    use std::sync::Arc;
+
+    use lsp_server::{Message, RequestId};
+    use lsp_types::request::GotoImplementationParams;
+    use lsp_types::{
+        Location, Position, ReferenceContext, ReferenceParams, RenameParams, TextDocumentIdentifier,
+        TextDocumentPositionParams, Url, WorkspaceEdit,
+    };
+    use parking_lot::Mutex;
+
+    use data::{Arguments, CompilerArguments, RunnerSettings, SourceSet};
+    use magpie_lib::build_project;
+    use parser::FileSourceSet;
+    use syntax::program::syntax::Syntax;
+
+    use crate::implementation::send_implementations;
+    use crate::rename::{name_at, offset_of, send_references, send_rename};
+
+    static MAIN: &str =
+        "import shapes::Point;\n\nfn test() -> u64 {\n    let café = new Point { x: 1 };\n    return café.x;\n}\n";
+    static SHAPES: &str = "pub struct Point {\n    pub x: u64;\n}\n";
+    static AREA: &str = "pub trait Area {\n    fn area(self) -> u64;\n}\n\npub struct Square {\n    pub side: u64;\n}\n\n\
+        impl Area for Square {\n    pub fn area(self) -> u64 {\n        return self.side * self.side;\n    }\n}\n";
+
+    /// Checks LSP positions, which count columns in UTF-16 code units, are converted to byte offsets
+    #[test]
+    pub fn test_offset_of() {
+        let document = "let 🐦 = é;\nPoint\n";
+        // The bird is two UTF-16 code units and four bytes, and é is one code unit and two bytes
+        assert_eq!(offset_of(document, Position { line: 0, character: 6 }), Some(8));
+        assert_eq!(offset_of(document, Position { line: 0, character: 9 }), Some(11));
+        assert_eq!(offset_of(document, Position { line: 0, character: 100 }), Some(14));
+        assert_eq!(offset_of(document, Position { line: 1, character: 2 }), Some(17));
+        assert_eq!(offset_of(document, Position { line: 2, character: 0 }), Some(document.len()));
+        assert_eq!(offset_of(document, Position { line: 3, character: 0 }), None);
+
+        assert_eq!(name_at("math::Add::add", Position { line: 0, character: 7 }), Some("math::Add".to_string()));
+    }
+
+    /// Checks the references and rename handlers find the struct in both files, past the multi-byte variable name,
+    /// and the implementation handler finds trait implementations
+    #[test]
+    pub fn test_rename() {
+        let root = std::env::temp_dir().join("raven-language-server-rename");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("main.rv"), MAIN).unwrap();
+        fs::write(root.join("shapes.rv"), SHAPES).unwrap();
+        fs::write(root.join("area.rv"), AREA).unwrap();
+        let sources: Vec<Box<dyn SourceSet>> = vec![Box::new(FileSourceSet { root: root.clone() })];
+        let syntax = build(&sources);
+
+        let main = Url::from_file_path(root.join("main.rv")).unwrap();
+        let shapes = Url::from_file_path(root.join("shapes.rv")).unwrap();
+        let position = TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier { uri: main.clone() },
+            position: Position { line: 3, character: 20 },
+        };
+
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        send_references(
+            RequestId::from(1),
+            ReferenceParams {
+                text_document_position: position.clone(),
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+                context: ReferenceContext { include_declaration: true },
+            },
+            MAIN.to_string(),
+            sources.iter().map(|inner| inner.cloned()).collect(),
+            syntax.clone(),
+            sender.clone(),
+        );
+        let mut locations: Vec<Location> = serde_json::from_value(result(receiver.recv().unwrap())).unwrap();
+        locations
+            .sort_by_key(|location| (location.uri.to_string(), location.range.start.line, location.range.start.character));
+        let found = locations
+            .iter()
+            .map(|location| (location.uri.clone(), location.range.start.line, location.range.start.character))
+            .collect::<Vec<_>>();
+        // "café" is five bytes but four UTF-16 code units, so Point starts at column 19 instead of its byte offset 20
+        assert_eq!(found, vec![(main.clone(), 0, 15), (main.clone(), 3, 19), (shapes.clone(), 0, 11)]);
+
+        send_rename(
+            RequestId::from(2),
+            RenameParams {
+                text_document_position: position,
+                new_name: "Vector".to_string(),
+                work_done_progress_params: Default::default(),
+            },
+            MAIN.to_string(),
+            sources.iter().map(|inner| inner.cloned()).collect(),
+            syntax.clone(),
+            sender,
+        );
+        let edit: WorkspaceEdit = serde_json::from_value(result(receiver.recv().unwrap())).unwrap();
+        let changes = edit.changes.unwrap();
+        assert_eq!(changes[&main].len(), 2);
+        assert_eq!(changes[&shapes].len(), 1);
+        assert!(changes.values().flatten().all(|edit| edit.new_text == "Vector"));
+
+        // The trait's implementation is found from its name, and the type's implementations from the type's name
+        let area = Url::from_file_path(root.join("area.rv")).unwrap();
+        for (character, line) in [(12, 0), (14, 4)] {
+            let (sender, receiver) = crossbeam_channel::unbounded();
+            send_implementations(
+                RequestId::from(3),
+                GotoImplementationParams {
+                    text_document_position_params: TextDocumentPositionParams {
+                        text_document: TextDocumentIdentifier { uri: area.clone() },
+                        position: Position { line, character },
+                    },
+                    work_done_progress_params: Default::default(),
+                    partial_result_params: Default::default(),
+                },
+                AREA.to_string(),
+                sources.iter().map(|inner| inner.cloned()).collect(),
+                syntax.clone(),
+                sender,
+            );
+            let locations: Vec<Location> = serde_json::from_value(result(receiver.recv().unwrap())).unwrap();
+            assert_eq!(locations.len(), 1);
+            assert_eq!((&locations[0].uri, locations[0].range.start.line), (&area, 8));
+        }
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    /// Builds the sources with call sites recorded, like the language server does
+    fn build(sources: &Vec<Box<dyn SourceSet>>) -> Arc<Mutex<Syntax>> {
+        let mut arguments = Arguments::build_args(
+            false,
+            RunnerSettings {
+                sources: vec![],
+                compiler_arguments: CompilerArguments {
+                    compiler: "llvm".to_string(),
+                    record_call_sites: true,
+                    ..Default::default()
+                },
+            },
+        );
+        return build_project::<()>(&mut arguments, &mut sources.iter().map(|inner| inner.cloned()).collect(), false)
+            .unwrap()
+            .0;
+    }
+
+    /// Gets the result of a response sent to the IDE
+    fn result(message: Message) -> serde_json::Value {
+        return match message {
+            Message::Response(response) => response.result.unwrap(),
+            _ => panic!("Expected a response, got {:?}", message),
+        };
+    }
+}
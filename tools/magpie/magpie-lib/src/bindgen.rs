@@ -0,0 +1,298 @@
+use std::fs;
+use std::path::Path;
+
+use data::tokens::TokenTypes;
+use data::SourceSet;
+use parser::FileSourceSet;
+use syntax::mangling::mangle;
+
+/// A function signature shared between Raven and another language, with the types already in the target language
+#[derive(Clone, Debug)]
+pub struct BoundFunction {
+    /// The function's name, or the full path for Raven functions
+    pub name: String,
+    /// The name and type of each argument
+    pub arguments: Vec<(String, String)>,
+    /// The return type, if it returns anything
+    pub return_type: Option<String>,
+}
+
+/// Generates Raven extern declarations for every exported function in a Rust file or crate.
+/// Exported functions are public functions that are #[no_mangle] or extern "C".
+pub fn rust_to_raven(path: &Path) -> String {
+    let mut files = Vec::default();
+    find_files(path, "rs", &mut files);
+
+    let mut output = format!("// Generated by magpie bindgen from {}\n", path.display());
+    for file in files {
+        for function in parse_rust(&fs::read_to_string(&file).unwrap()) {
+            output += &raven_extern(&function, &rust_to_raven_type);
+        }
+    }
+    return output;
+}
+
+/// Generates a Rust extern block for every public non-generic top-level function in the Raven source folder,
+/// linking each one to its mangled symbol. Each function is named with its full path joined by underscores,
+/// like main_foo for main::foo, so functions with the same name in different files don't collide.
+/// Returns an error if a function uses a type that can't be passed to Rust.
+pub fn raven_to_rust(path: &Path) -> Result<String, String> {
+    let (functions, structs) = parse_raven(&FileSourceSet { root: path.to_path_buf() });
+    let convert = |function: &BoundFunction, types: &String| {
+        raven_to_rust_type(types, &structs)
+            .ok_or_else(|| format!("{} uses {}, which can't be passed to Rust", function.name, types))
+    };
+
+    let mut output = format!("// Generated by magpie bindgen from {}\nextern \"C\" {{\n", path.display());
+    for function in functions {
+        let mut arguments = Vec::default();
+        for (name, types) in &function.arguments {
+            arguments.push(format!("{}: {}", name, convert(&function, types)?));
+        }
+        let returning = match &function.return_type {
+            Some(types) => format!(" -> {}", convert(&function, types)?),
+            None => String::default(),
+        };
+        output += &format!("    #[link_name = \"{}\"]\n", mangle(&function.name));
+        output += &format!("    pub fn {}({}){};\n", rust_name(&function.name), arguments.join(", "), returning);
+    }
+    output += "}\n";
+    return Ok(output);
+}
+
+/// Gets the Rust name of a Raven function from its full path, like main_foo for main::foo.
+/// Characters that can't be in a Rust identifier, like the dash in a file name, are replaced with underscores.
+fn rust_name(path: &str) -> String {
+    return path
+        .replace("::", "_")
+        .chars()
+        .map(|character| if character.is_alphanumeric() || character == '_' { character } else { '_' })
+        .collect();
+}
+
+/// Formats a Raven extern declaration, converting each type with the given function.
+/// Functions with types that can't be converted are skipped with a comment.
+pub fn raven_extern(function: &BoundFunction, convert: &dyn Fn(&str) -> Option<String>) -> String {
+    let mut arguments = Vec::default();
+    for (name, types) in &function.arguments {
+        match convert(types) {
+            Some(found) => arguments.push(format!("{}: {}", name, found)),
+            None => return format!("// Skipped {}: unsupported type {}\n", function.name, types),
+        }
+    }
+    let returning = match &function.return_type {
+        Some(types) => match convert(types) {
+            Some(found) => format!(" -> {}", found),
+            None => return format!("// Skipped {}: unsupported type {}\n", function.name, types),
+        },
+        None => String::default(),
+    };
+    return format!("pub extern fn {}({}){};\n", function.name, arguments.join(", "), returning);
+}
+
+/// Finds every function that's exported to C in a Rust file
+fn parse_rust(source: &str) -> Vec<BoundFunction> {
+    let source = source.lines().map(|line| line.split("//").next().unwrap()).collect::<Vec<_>>().join("\n");
+    let mut output = Vec::default();
+    let mut searched = 0;
+    while let Some(found) = find_keyword(&source, "fn", searched) {
+        searched = found + 2;
+        // Everything since the last item or block is the function's attributes and qualifiers
+        let start = source[..found].rfind(|character| matches!(character, ';' | '{' | '}')).map(|i| i + 1).unwrap_or(0);
+        let prefix = &source[start..found];
+        if find_keyword(prefix, "pub", 0).is_none() || !(prefix.contains("extern \"C\"") || prefix.contains("no_mangle")) {
+            continue;
+        }
+
+        let header = &source[searched..];
+        let (Some(open), Some(close)) = (header.find('('), find_closing(header)) else {
+            continue;
+        };
+        let name = header[..open].trim();
+        if name.contains('<') {
+            // Generic functions can't be exported
+            continue;
+        }
+        let arguments = split_top_level(&header[open + 1..close])
+            .into_iter()
+            .filter_map(|argument| argument.split_once(':'))
+            .map(|(name, types)| (name.trim().trim_start_matches("mut ").to_string(), types.trim().to_string()))
+            .collect();
+        let rest = &header[close + 1..];
+        let end = rest.find(|character| matches!(character, '{' | ';')).unwrap_or(rest.len());
+        let return_type = rest[..end]
+            .split("where")
+            .next()
+            .unwrap()
+            .trim()
+            .strip_prefix("->")
+            .map(|inner| inner.trim().to_string())
+            .filter(|inner| inner != "()");
+        output.push(BoundFunction { name: name.to_string(), arguments, return_type });
+    }
+    return output;
+}
+
+/// Finds every public non-generic function outside of a struct, trait, or impl in the Raven source set,
+/// and the name of every struct declared in it
+fn parse_raven(source: &dyn SourceSet) -> (Vec<BoundFunction>, Vec<String>) {
+    let mut output = Vec::default();
+    let mut structs = Vec::default();
+    for file in source.get_files() {
+        if !file.path().ends_with("rv") {
+            continue;
+        }
+        let contents = file.contents();
+        let buffer = contents.as_bytes();
+        let module = source.relative(&*file);
+
+        let mut in_structure = false;
+        let mut naming_struct = false;
+        let mut modifiers = Vec::default();
+        let mut current: Option<BoundFunction> = None;
+        let mut argument = String::default();
+        for token in file.read() {
+            let text = token.to_string(buffer).trim().to_string();
+            match token.token_type {
+                TokenTypes::StructStart | TokenTypes::TraitStart | TokenTypes::ImplStart => {
+                    in_structure = true;
+                    naming_struct = token.token_type == TokenTypes::StructStart;
+                    modifiers.clear();
+                }
+                TokenTypes::Identifier if naming_struct => {
+                    naming_struct = false;
+                    structs.push(text);
+                }
+                TokenTypes::StructEnd => {
+                    in_structure = false;
+                    modifiers.clear();
+                }
+                TokenTypes::Modifier => modifiers.push(text),
+                TokenTypes::FunctionStart => {
+                    // Extern and internal functions aren't compiled from Raven code, so they can't be exported
+                    let exported = modifiers.iter().any(|modifier| modifier == "pub")
+                        && !modifiers.iter().any(|modifier| modifier == "extern" || modifier == "internal");
+                    if !in_structure && exported {
+                        current =
+                            Some(BoundFunction { name: String::default(), arguments: Vec::default(), return_type: None });
+                    }
+                    modifiers.clear();
+                }
+                TokenTypes::Identifier if current.as_ref().is_some_and(|function| function.name.is_empty()) => {
+                    current.as_mut().unwrap().name = format!("{}::{}", module, text)
+                }
+                // Generic functions are only compiled when they're used, so they can't be exported
+                TokenTypes::GenericsStart => current = None,
+                TokenTypes::ArgumentName => argument = text,
+                TokenTypes::ArgumentType => {
+                    if let Some(function) = &mut current {
+                        function.arguments.push((argument.clone(), text));
+                    }
+                }
+                TokenTypes::ReturnType => {
+                    if let Some(function) = &mut current {
+                        function.return_type = Some(text);
+                    }
+                }
+                TokenTypes::CodeStart | TokenTypes::CodeEnd => output.extend(current.take()),
+                _ => {}
+            }
+        }
+    }
+    return (output, structs);
+}
+
+/// Converts a Rust FFI type to its Raven type
+fn rust_to_raven_type(types: &str) -> Option<String> {
+    let types = types.replace(' ', "");
+    return Some(
+        match types.as_str() {
+            "u8" | "u16" | "u32" | "u64" | "i8" | "i16" | "i32" | "i64" | "f32" | "f64" | "bool" => types.as_str(),
+            "usize" => "u64",
+            "isize" => "i64",
+            "*constc_char" | "*mutc_char" | "*consti8" | "*muti8" | "*constu8" | "*mutu8" => "str",
+            _ if types.starts_with('*') => "u64",
+            _ => return None,
+        }
+        .to_string(),
+    );
+}
+
+/// Converts a Raven type to its Rust FFI type, given the structs declared in the source, or None if it can't be passed
+fn raven_to_rust_type(types: &str, structs: &Vec<String>) -> Option<String> {
+    return Some(
+        match types {
+            "u8" | "u16" | "u32" | "u64" | "i8" | "i16" | "i32" | "i64" | "f32" | "f64" | "bool" => types,
+            "char" => "u8",
+            "str" => "*const std::ffi::c_char",
+            // Structs are passed by pointer
+            _ if structs.iter().any(|found| found == types) => "*mut std::ffi::c_void",
+            _ => return None,
+        }
+        .to_string(),
+    );
+}
+
+/// Finds every file with the extension in the path, which can be a single file or a folder
+pub(crate) fn find_files(path: &Path, extension: &str, output: &mut Vec<std::path::PathBuf>) {
+    if path.is_dir() {
+        for entry in fs::read_dir(path).unwrap() {
+            find_files(&entry.unwrap().path(), extension, output);
+        }
+    } else if path.extension().is_some_and(|found| found == extension) {
+        output.push(path.to_path_buf());
+    }
+}
+
+/// Finds the next occurrence of the keyword that isn't part of a larger identifier
+pub(crate) fn find_keyword(source: &str, keyword: &str, from: usize) -> Option<usize> {
+    let bytes = source.as_bytes();
+    let is_identifier = |character: u8| character.is_ascii_alphanumeric() || character == b'_';
+    let mut searched = from;
+    while let Some(found) = source[searched..].find(keyword) {
+        let found = searched + found;
+        searched = found + keyword.len();
+        if (found == 0 || !is_identifier(bytes[found - 1])) && (searched == bytes.len() || !is_identifier(bytes[searched])) {
+            return Some(found);
+        }
+    }
+    return None;
+}
+
+/// Finds the parenthesis closing the first opening parenthesis
+pub(crate) fn find_closing(source: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (i, character) in source.char_indices() {
+        match character {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    return None;
+}
+
+/// Splits arguments on commas that aren't inside of brackets, ignoring empty arguments
+pub(crate) fn split_top_level(source: &str) -> Vec<&str> {
+    let mut output = Vec::default();
+    let mut depth = 0;
+    let mut last = 0;
+    for (i, character) in source.char_indices() {
+        match character {
+            '(' | '<' | '[' | '{' => depth += 1,
+            ')' | '>' | ']' | '}' => depth -= 1,
+            ',' if depth == 0 => {
+                output.push(&source[last..i]);
+                last = i + 1;
+            }
+            _ => {}
+        }
+    }
+    output.push(&source[last..]);
+    return output.into_iter().filter(|argument| !argument.trim().is_empty()).collect();
+}
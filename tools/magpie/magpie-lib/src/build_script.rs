@@ -0,0 +1,75 @@
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+
+use data::CompilerArguments;
+
+use crate::project::{BuildScript, GeneratedFile};
+use crate::workspace::{has_build_function, run_build_file};
+
+/// Runs the build scripts declared by the folder's build file, returning the folder of generated source if there are any.
+///
+/// A build file can declare either or both of:
+/// - A generate function returning a list of GeneratedFiles, for code generation written in Raven
+/// - A build_script function returning a BuildScript, for code generation written in another language like Rust.
+///   The command is run in the project's folder with RAVEN_OUT_DIR set to the folder it should write .rv files to.
+///
+/// The generated folder (target/generated) is cleared first, so removed files don't linger between builds.
+pub fn run_build_scripts(folder: &Path, compiler_arguments: &CompilerArguments) -> Result<Option<PathBuf>, String> {
+    let generate = has_build_function(folder, "generate");
+    let build_script = has_build_function(folder, "build_script");
+    if !generate && !build_script {
+        return Ok(None);
+    }
+
+    let output = compiler_arguments.temp_folder.join("generated");
+    if output.exists() {
+        fs::remove_dir_all(&output).map_err(|error| format!("Failed to clear {}: {}", output.display(), error))?;
+    }
+    fs::create_dir_all(&output).map_err(|error| format!("Failed to create {}: {}", output.display(), error))?;
+
+    if generate {
+        let files = run_build_file::<Vec<GeneratedFile>>(folder, "generate", compiler_arguments)
+            .ok_or_else(|| "Failed to run the build file's generate method!".to_string())?;
+        for file in files {
+            write_generated(&output, &file)?;
+        }
+    }
+
+    if build_script {
+        let script = run_build_file::<BuildScript>(folder, "build_script", compiler_arguments)
+            .ok_or_else(|| "Failed to run the build file's build_script method!".to_string())?;
+        run_command(folder, &output, &script)?;
+    }
+    return Ok(Some(output));
+}
+
+/// Writes a file generated by a Raven build script, which must stay inside the generated folder
+fn write_generated(output: &Path, file: &GeneratedFile) -> Result<(), String> {
+    let relative = Path::new(&file.path);
+    if !relative.components().all(|component| matches!(component, Component::Normal(_))) {
+        return Err(format!("Generated file {} must be a relative path inside the generated folder!", file.path));
+    }
+    if relative.extension().map_or(true, |extension| extension != "rv") {
+        return Err(format!("Generated file {} must be a .rv file!", file.path));
+    }
+
+    let path = output.join(relative);
+    fs::create_dir_all(path.parent().unwrap()).map_err(|error| error.to_string())?;
+    return fs::write(&path, &file.contents).map_err(|error| format!("Failed to write {}: {}", path.display(), error));
+}
+
+/// Runs an external build script, erroring if it fails
+fn run_command(folder: &Path, output: &Path, script: &BuildScript) -> Result<(), String> {
+    let (program, arguments) = script.command.split_first().ok_or_else(|| "Build script command is empty!".to_string())?;
+    let status = Command::new(program)
+        .args(arguments)
+        .current_dir(folder)
+        .env("RAVEN_OUT_DIR", output)
+        .status()
+        .map_err(|error| format!("Failed to run build script {}: {}", program, error))?;
+    if !status.success() {
+        return Err(format!("Build script {} failed with {}!", script.command.join(" "), status));
+    }
+    return Ok(());
+}
@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::bindgen::{find_closing, find_files, raven_extern, split_top_level, BoundFunction};
+
+/// Qualifiers that don't change how a type is passed
+static IGNORED_QUALIFIERS: [&str; 7] = ["const", "volatile", "extern", "static", "inline", "restrict", "register"];
+
+/// Keywords that have to be followed by a tag, so the word after them is part of the type and not a name
+static TAG_KEYWORDS: [&str; 3] = ["struct", "enum", "union"];
+
+/// Generates Raven extern declarations and structs from the function prototypes and simple structs in a C header,
+/// or every header in a folder. Anything that can't be represented in Raven is skipped with a comment.
+pub fn header_to_raven(path: &Path) -> String {
+    let mut files = Vec::default();
+    find_files(path, "h", &mut files);
+
+    let mut output = format!("// Generated by magpie bindgen from {}\n", path.display());
+    for file in files {
+        output += &convert_header(&fs::read_to_string(&file).unwrap());
+    }
+    return output;
+}
+
+/// Converts a single header's declarations
+fn convert_header(source: &str) -> String {
+    let mut header = CHeader::default();
+    let mut functions = Vec::default();
+    let mut structs = Vec::default();
+    for statement in split_statements(&strip_comments(source)) {
+        if statement.starts_with("typedef") && !statement.contains('{') {
+            header.parse_typedef(&statement);
+        } else if (statement.starts_with("struct") || statement.starts_with("typedef struct")) && statement.contains('{') {
+            if let Some(found) = header.parse_struct(&statement) {
+                structs.push(found);
+            }
+        } else if statement.contains('(') && !statement.contains('{') && !statement.starts_with("typedef") {
+            functions.push(statement);
+        }
+    }
+
+    let mut output = String::default();
+    if !structs.is_empty() {
+        output +=
+            "// Raven structs start with a type id, so pointers to these structs are passed as u64 addresses instead\n";
+    }
+    for (name, fields) in structs {
+        output += &header.raven_struct(&name, &fields);
+    }
+    for function in functions {
+        output += &match parse_prototype(&function) {
+            Some(function) => raven_extern(&function, &|types| header.to_raven_type(types)),
+            None => format!("// Skipped unsupported declaration {}\n", function),
+        };
+    }
+    return output;
+}
+
+/// The types declared in a header
+#[derive(Default)]
+struct CHeader {
+    /// Every typedef, from the alias to the type it stands for
+    typedefs: HashMap<String, String>,
+    /// Every struct that was converted
+    structs: Vec<String>,
+}
+
+impl CHeader {
+    /// Reads a typedef like "typedef unsigned long size_type"
+    fn parse_typedef(&mut self, statement: &str) {
+        let declaration = statement["typedef".len()..].trim();
+        if declaration.contains('(') {
+            // Function pointer typedefs aren't supported
+            return;
+        }
+        let (types, alias) = split_declaration(declaration);
+        self.typedefs.insert(alias, types);
+    }
+
+    /// Reads a struct like "struct point { int x; int y; }" or "typedef struct { int x; } point",
+    /// returning the name and the name and type of each field
+    fn parse_struct(&mut self, statement: &str) -> Option<(String, Vec<(String, String)>)> {
+        let open = statement.find('{')?;
+        let close = statement.rfind('}')?;
+        let tag = statement[..open].trim().trim_start_matches("typedef").trim().trim_start_matches("struct").trim();
+        let alias = statement[close + 1..].trim();
+        let name = if alias.is_empty() { tag } else { alias };
+        if name.is_empty() {
+            return None;
+        }
+        if !tag.is_empty() && tag != name {
+            self.typedefs.insert(format!("struct {}", tag), name.to_string());
+        }
+
+        let mut fields = Vec::default();
+        for field in statement[open + 1..close].split(';').map(|field| field.trim()).filter(|field| !field.is_empty()) {
+            // Fields can declare multiple names with the same type, like "int *x, y", where only x is a pointer
+            let mut declarators = field.split(',');
+            let (types, name) = split_declaration(declarators.next().unwrap());
+            let base = types.trim_end_matches('*').to_string();
+            fields.push((name, types));
+            for declarator in declarators {
+                let declarator = declarator.trim();
+                let name = declarator.trim_start_matches('*').trim();
+                let pointers = declarator.chars().filter(|character| *character == '*').count();
+                fields.push((name.to_string(), format!("{}{}", base, "*".repeat(pointers))));
+            }
+        }
+        self.structs.push(name.to_string());
+        return Some((name.to_string(), fields));
+    }
+
+    /// Formats a struct as a Raven struct, skipping it if any field can't be converted
+    fn raven_struct(&self, name: &str, fields: &Vec<(String, String)>) -> String {
+        let mut output = format!("pub struct {} {{\n", name);
+        for (field, types) in fields {
+            match self.to_raven_type(types) {
+                Some(found) if !field.contains('[') && !field.contains(':') => {
+                    output += &format!("    pub {}: {};\n", field, found)
+                }
+                _ => return format!("// Skipped struct {}: unsupported field {}\n", name, field),
+            }
+        }
+        output += "}\n";
+        return output;
+    }
+
+    /// Converts a C type to its Raven type, resolving typedefs
+    fn to_raven_type(&self, types: &str) -> Option<String> {
+        let mut types = types
+            .split_whitespace()
+            .filter(|part| !IGNORED_QUALIFIERS.contains(part))
+            .collect::<Vec<_>>()
+            .join(" ")
+            .replace(" *", "*");
+        while let Some(found) = self.typedefs.get(types.trim_end_matches('*')) {
+            types = format!("{}{}", found, "*".repeat(types.len() - types.trim_end_matches('*').len()));
+        }
+
+        let pointers = types.len() - types.trim_end_matches('*').len();
+        let base = types.trim_end_matches('*').trim().trim_start_matches("struct ");
+        if self.structs.iter().any(|found| found == base) {
+            // Raven structs start with a type id, so C would read it as the first field if the struct was passed.
+            // Pointers to structs are passed as addresses instead, and structs can't be passed by value.
+            return if pointers > 0 { Some("u64".to_string()) } else { None };
+        }
+        return Some(
+            match (base, pointers) {
+                ("char", 1) => "str",
+                ("void", 0) => return None,
+                (_, 0) => primitive_type(base)?,
+                // Other pointers are passed as addresses
+                _ => "u64",
+            }
+            .to_string(),
+        );
+    }
+}
+
+/// Converts a primitive C type to its Raven type
+fn primitive_type(types: &str) -> Option<&'static str> {
+    return Some(match types {
+        "char" | "signed char" | "int8_t" => "i8",
+        "unsigned char" | "uint8_t" => "u8",
+        "short" | "short int" | "signed short" | "int16_t" => "i16",
+        "unsigned short" | "unsigned short int" | "uint16_t" => "u16",
+        "int" | "signed" | "signed int" | "int32_t" => "i32",
+        "unsigned" | "unsigned int" | "uint32_t" => "u32",
+        "long" | "long int" | "long long" | "long long int" | "int64_t" | "ssize_t" | "intptr_t" => "i64",
+        "unsigned long" | "unsigned long int" | "unsigned long long" | "uint64_t" | "size_t" | "uintptr_t" => "u64",
+        "bool" | "_Bool" => "bool",
+        _ => return None,
+    });
+}
+
+/// Parses a function prototype like "int add(int a, int b)"
+fn parse_prototype(statement: &str) -> Option<BoundFunction> {
+    let open = statement.find('(')?;
+    let close = find_closing(statement)?;
+    let (return_type, name) = split_declaration(&statement[..open]);
+    let mut arguments = Vec::default();
+    for (i, argument) in split_top_level(&statement[open + 1..close]).into_iter().enumerate() {
+        let argument = argument.trim();
+        if argument == "..." || argument.contains('(') {
+            // Variadic functions and function pointers can't be called from Raven
+            return None;
+        }
+        if argument == "void" {
+            continue;
+        }
+        let (types, name) = split_declaration(argument);
+        // Unnamed arguments only have a type
+        if is_unnamed(&types, &name) || primitive_type(argument).is_some() {
+            arguments.push((format!("arg{}", i), argument.to_string()));
+        } else {
+            arguments.push((name, types));
+        }
+    }
+    let return_type = if return_type == "void" { None } else { Some(return_type) };
+    return Some(BoundFunction { name, arguments, return_type });
+}
+
+/// Whether a split declaration is only a type, like "struct point", which split_declaration splits into a type of
+/// "struct" and a name of "point", or "const size_t", which has no type left after the qualifier
+fn is_unnamed(types: &str, name: &str) -> bool {
+    let types = types.split_whitespace().filter(|part| !IGNORED_QUALIFIERS.contains(part)).collect::<Vec<_>>();
+    return name.is_empty() || types.last().map_or(true, |last| TAG_KEYWORDS.contains(last));
+}
+
+/// Splits a declaration like "const char *name" into the type ("const char*") and the name ("name")
+fn split_declaration(declaration: &str) -> (String, String) {
+    let declaration = declaration.trim();
+    let split =
+        declaration.rfind(|character: char| character.is_whitespace() || character == '*').map(|i| i + 1).unwrap_or(0);
+    let types = declaration[..split].trim().replace(" *", "*");
+    return (types, declaration[split..].trim().to_string());
+}
+
+/// Removes comments, preprocessor directives, and C++ extern "C" guards, leaving only declarations
+fn strip_comments(source: &str) -> String {
+    let mut output = String::default();
+    let mut remaining = source;
+    while let Some(start) = remaining.find("/*") {
+        output += &remaining[..start];
+        remaining = remaining[start..].find("*/").map(|end| &remaining[start + end + 2..]).unwrap_or("");
+    }
+    output += remaining;
+
+    return output
+        .lines()
+        .map(|line| line.split("//").next().unwrap())
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .replace("extern \"C\" {", "");
+}
+
+/// Splits the header into declarations on semicolons outside of braces.
+/// Function definitions end at their closing brace instead, and unmatched closing braces (like the end of an
+/// extern "C" guard) are ignored.
+fn split_statements(source: &str) -> Vec<String> {
+    let mut output = Vec::default();
+    let mut current = String::default();
+    let mut depth = 0;
+    for character in source.chars() {
+        match character {
+            '{' => depth += 1,
+            '}' if depth == 0 => continue,
+            '}' => depth -= 1,
+            _ => {}
+        }
+        current.push(character);
+
+        let trimmed = current.trim_start();
+        let definition = character == '}' && depth == 0 && !trimmed.starts_with("typedef") && !trimmed.starts_with("struct");
+        if (character == ';' && depth == 0) || definition {
+            let statement = current.trim().trim_end_matches(';').split_whitespace().collect::<Vec<_>>().join(" ");
+            if !statement.is_empty() && !definition {
+                output.push(statement);
+            }
+            current.clear();
+        }
+    }
+    return output;
+}
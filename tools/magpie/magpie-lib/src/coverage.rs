@@ -0,0 +1,155 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use parking_lot::Mutex;
+
+use ::runner::coverage_sites;
+use data::SourceSet;
+use syntax::program::printer::SourceLocator;
+
+/// The coverage of every file run so far, by the file's path
+static FILES: Mutex<BTreeMap<String, FileCoverage>> = Mutex::new(BTreeMap::new());
+
+/// How many times each instrumented line of a file ran, summed over every test
+struct FileCoverage {
+    /// The file's source, shown in the HTML report
+    contents: String,
+    /// The times each instrumented line ran, by its line number
+    lines: BTreeMap<u32, u64>,
+}
+
+/// Adds the lines run by the last program to the coverage, called after each test.
+/// A line with multiple instrumented effects counts the most any of them ran, so a line isn't counted twice per run.
+pub fn record_run(sources: &Vec<Box<dyn SourceSet>>) {
+    let locator = SourceLocator::new(sources);
+    let mut run: BTreeMap<String, BTreeMap<u32, u64>> = BTreeMap::default();
+    for site in coverage_sites() {
+        let Some((path, line)) = locator.line(&site.span) else {
+            continue;
+        };
+        let count = run.entry(path.clone()).or_default().entry(line).or_default();
+        *count = (*count).max(site.count);
+    }
+    merge(run, sources);
+}
+
+/// Adds the lines in an lcov report, like one written by an isolated test, to the coverage
+pub fn merge_lcov(lcov: &str, sources: &Vec<Box<dyn SourceSet>>) {
+    let mut run: BTreeMap<String, BTreeMap<u32, u64>> = BTreeMap::default();
+    let mut path = String::default();
+    for line in lcov.lines() {
+        if let Some(file) = line.strip_prefix("SF:") {
+            path = file.to_string();
+        } else if let Some((line, count)) = line.strip_prefix("DA:").and_then(|data| data.split_once(',')) {
+            if let (Ok(line), Ok(count)) = (line.parse(), count.parse()) {
+                run.entry(path.clone()).or_default().insert(line, count);
+            }
+        }
+    }
+    merge(run, sources);
+}
+
+/// Adds the times each line ran, by file path, to the coverage, reading the source of new files from the sources
+fn merge(mut run: BTreeMap<String, BTreeMap<u32, u64>>, sources: &Vec<Box<dyn SourceSet>>) {
+    let mut files = FILES.lock();
+    for source in sources {
+        for file in source.get_files() {
+            let Some(lines) = run.remove(&file.path()) else {
+                continue;
+            };
+            let coverage = files
+                .entry(file.path())
+                .or_insert_with(|| FileCoverage { contents: file.contents(), lines: BTreeMap::default() });
+            for (line, count) in lines {
+                *coverage.lines.entry(line).or_default() += count;
+            }
+        }
+    }
+}
+
+/// Writes the coverage so far as an lcov report to the file
+pub fn write_lcov(path: &Path) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, lcov(&FILES.lock())).unwrap();
+}
+
+/// Formats the coverage of each file as an lcov report
+fn lcov(files: &BTreeMap<String, FileCoverage>) -> String {
+    let mut lcov = String::default();
+    for (path, coverage) in files {
+        lcov += &format!("SF:{}\n", path);
+        for (line, count) in &coverage.lines {
+            lcov += &format!("DA:{},{}\n", line, count);
+        }
+        let hit = coverage.lines.values().filter(|count| **count > 0).count();
+        lcov += &format!("LH:{}\nLF:{}\nend_of_record\n", hit, coverage.lines.len());
+    }
+    return lcov;
+}
+
+/// Writes the coverage as lcov.info and index.html to the folder, returning the lines that ran and the total lines
+pub fn write_report(folder: &Path) -> (usize, usize) {
+    let files = FILES.lock();
+    let mut html = String::from(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Raven Coverage</title>\n<style>\n\
+        body { font-family: sans-serif; }\npre { margin: 0; }\n.hit { background: #dfd; }\n.miss { background: #fdd; }\n\
+        .count { color: #888; display: inline-block; text-align: right; width: 6em; }\n</style>\n</head>\n<body>\n",
+    );
+    let (mut total_hit, mut total_found) = (0, 0);
+    let mut summary = String::default();
+    let mut sources = String::default();
+    for (path, coverage) in files.iter() {
+        let hit = coverage.lines.values().filter(|count| **count > 0).count();
+        let found = coverage.lines.len();
+        total_hit += hit;
+        total_found += found;
+
+        summary += &format!(
+            "<tr><td><a href=\"#{}\">{}</a></td><td>{}/{}</td><td>{:.1}%</td></tr>\n",
+            escape(path),
+            escape(path),
+            hit,
+            found,
+            percent(hit, found)
+        );
+        sources += &format!("<h2 id=\"{}\">{}</h2>\n", escape(path), escape(path));
+        for (index, text) in coverage.contents.lines().enumerate() {
+            let (class, count) = match coverage.lines.get(&(index as u32 + 1)) {
+                Some(0) => ("miss", "0".to_string()),
+                Some(count) => ("hit", count.to_string()),
+                None => ("", String::default()),
+            };
+            sources += &format!(
+                "<pre class=\"{}\"><span class=\"count\">{}</span> {:>5}  {}</pre>\n",
+                class,
+                count,
+                index + 1,
+                escape(text)
+            );
+        }
+    }
+    html += &format!(
+        "<h1>Coverage: {}/{} lines ({:.1}%)</h1>\n<table>\n{}</table>\n{}</body>\n</html>\n",
+        total_hit,
+        total_found,
+        percent(total_hit, total_found),
+        summary,
+        sources
+    );
+
+    fs::create_dir_all(folder).unwrap();
+    fs::write(folder.join("lcov.info"), lcov(&files)).unwrap();
+    fs::write(folder.join("index.html"), html).unwrap();
+    return (total_hit, total_found);
+}
+
+/// Gets the percent of lines that ran, or 100% if there are no lines
+pub fn percent(hit: usize, found: usize) -> f64 {
+    return if found == 0 { 100.0 } else { hit as f64 * 100.0 / found as f64 };
+}
+
+/// Escapes text to be shown in HTML
+fn escape(text: &str) -> String {
+    return text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;");
+}
@@ -0,0 +1,24 @@
+use std::path::Path;
+
+use data::CompilerArguments;
+use syntax::program::syntax::{DEFAULT_EDITION, EDITIONS};
+
+use crate::workspace::{has_build_function, run_build_file};
+
+/// Finds the edition of the project in the folder: the one passed with --edition, or else the one returned by the
+/// build file's edition function, or else the default edition so projects written before editions keep compiling
+pub fn find_edition(folder: &Path, compiler_arguments: &CompilerArguments) -> Result<u32, String> {
+    if compiler_arguments.edition != 0 {
+        return Ok(compiler_arguments.edition);
+    }
+    if !has_build_function(folder, "edition") {
+        return Ok(DEFAULT_EDITION);
+    }
+
+    let edition = run_build_file::<u64>(folder, "edition", compiler_arguments)
+        .ok_or_else(|| "Failed to run the build file's edition method!".to_string())?;
+    return match u32::try_from(edition) {
+        Ok(edition) if EDITIONS.contains(&edition) => Ok(edition),
+        _ => Err(format!("Unknown edition {} in {}, expected one of {:?}!", edition, folder.display(), EDITIONS)),
+    };
+}
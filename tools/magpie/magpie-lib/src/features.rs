@@ -0,0 +1,43 @@
+use std::path::Path;
+
+use data::CompilerArguments;
+
+use crate::project::Feature;
+use crate::workspace::{has_build_function, run_build_file};
+
+/// Resolves the features to enable for the project in the folder: the requested features and the "default" feature
+/// (if default_features is true), along with every feature they enable.
+/// Features must be declared by the build file's features function, so unknown features are an error.
+pub fn resolve_features(
+    folder: &Path,
+    requested: &Vec<String>,
+    default_features: bool,
+    compiler_arguments: &CompilerArguments,
+) -> Result<Vec<String>, String> {
+    let declared = if has_build_function(folder, "features") {
+        run_build_file::<Vec<Feature>>(folder, "features", compiler_arguments)
+            .ok_or_else(|| "Failed to run the build file's features method!".to_string())?
+    } else {
+        Vec::default()
+    };
+
+    let mut pending = requested.clone();
+    if default_features && declared.iter().any(|feature| feature.name == "default") {
+        pending.push("default".to_string());
+    }
+
+    let mut enabled = Vec::default();
+    while let Some(name) = pending.pop() {
+        if enabled.contains(&name) {
+            continue;
+        }
+        let feature = declared
+            .iter()
+            .find(|feature| feature.name == name)
+            .ok_or_else(|| format!("Unknown feature {} in {}!", name, folder.display()))?;
+        pending.extend(feature.enables.iter().cloned());
+        enabled.push(name);
+    }
+    enabled.sort();
+    return Ok(enabled);
+}
@@ -4,6 +4,10 @@ use std::sync::Arc;
 use std::{env, path};
 
 use ::runner::runner::{build, create_syntax, run};
+pub use ::runner::{
+    clear_host_functions, register_host_function, set_panic_hook, take_panic_hook, HostFunction, HostType, HostValue,
+    PanicHook,
+};
 use include_dir::{include_dir, Dir, DirEntry, File};
 use parking_lot::Mutex;
 
@@ -11,11 +15,29 @@ use data::tokens::{Token, TokenTypes};
 use data::{Arguments, RavenExtern, Readable, SourceSet};
 use parser::tokens::tokenizer::Tokenizer;
 use syntax::errors::ParsingError;
-use syntax::program::syntax::Syntax;
-
+use syntax::program::syntax::{Syntax, LATEST_EDITION};
+
+/// Generates bindings between Raven and Rust
+pub mod bindgen;
+/// Runs build scripts that generate Raven source before compiling
+pub mod build_script;
+/// Converts C headers into Raven extern declarations
+pub mod c_header;
+/// Collects test coverage and writes lcov and HTML reports
+pub mod coverage;
+/// Finds the edition of the language a project is written for
+pub mod editions;
+/// Resolves the features enabled for a project
+pub mod features;
+/// Selects and applies build profiles
+pub mod profiles;
 /// The Raven project types
 pub mod project;
 mod runner;
+/// Runs Raven test files
+pub mod testing;
+/// Builds workspaces of multiple projects
+pub mod workspace;
 
 /// The core Raven library
 static CORE: Dir = include_dir!("lib/core/src");
@@ -30,8 +52,8 @@ static STD_MACOS: Dir = include_dir!("lib/std/macos/src");
 /// The Magpie classes
 pub static MAGPIE: Dir = include_dir!("tools/magpie/lib/src");
 
-/// Sets up the arguments with the std
-pub fn setup_arguments(arguments: &mut Arguments, source: &mut Vec<Box<dyn SourceSet>>) {
+/// Gets the source sets of the std and core libraries for the current platform
+pub fn library_sources() -> Vec<Box<dyn SourceSet>> {
     let platform_std = match env::consts::OS {
         "windows" => &STD_WINDOWS,
         "linux" => &STD_LINUX,
@@ -39,9 +61,25 @@ pub fn setup_arguments(arguments: &mut Arguments, source: &mut Vec<Box<dyn Sourc
         _ => panic!("Unsupported platform {}!", env::consts::OS),
     };
 
-    source.push(Box::new(InnerSourceSet { set: &STD_UNIVERSAL }));
-    source.push(Box::new(InnerSourceSet { set: platform_std }));
-    source.push(Box::new(InnerSourceSet { set: &CORE }));
+    return vec![
+        Box::new(InnerSourceSet { set: &STD_UNIVERSAL }),
+        Box::new(InnerSourceSet { set: platform_std }),
+        Box::new(InnerSourceSet { set: &CORE }),
+    ];
+}
+
+/// Gets the source set of the core library, which doesn't need an operating system
+pub fn core_sources() -> Vec<Box<dyn SourceSet>> {
+    return vec![Box::new(InnerSourceSet { set: &CORE })];
+}
+
+/// Sets up the arguments with the std, or only the core library for --no-std
+pub fn setup_arguments(arguments: &mut Arguments, source: &mut Vec<Box<dyn SourceSet>>) {
+    if arguments.runner_settings.compiler_arguments.no_std {
+        source.append(&mut core_sources());
+    } else {
+        source.append(&mut library_sources());
+    }
 
     arguments.runner_settings.sources = source.iter().map(|inner| inner.cloned()).collect::<Vec<_>>();
 }
@@ -104,6 +142,11 @@ impl SourceSet for InnerSourceSet {
     fn cloned(&self) -> Box<dyn SourceSet> {
         return Box::new(self.clone());
     }
+
+    fn edition(&self) -> u32 {
+        // The libraries are kept up to date with the latest edition, whatever edition the project uses
+        return LATEST_EDITION;
+    }
 }
 
 /// Recursively reads an include_dir directory to the output
@@ -0,0 +1,53 @@
+use std::path::Path;
+
+use data::CompilerArguments;
+
+use crate::project::Profile;
+use crate::workspace::{has_build_function, run_build_file};
+
+/// Gets the built-in profile with the name, which can be overridden by a build file's profiles function.
+/// "debug" is unoptimized with IR verification, debug info, and overflow checks, and "release" is fully optimized with LTO.
+pub fn default_profile(name: &str) -> Option<Profile> {
+    let (opt_level, flags) = match name {
+        "debug" => (0, vec!["verify-ir", "debug-info", "overflow-checks"]),
+        "release" => (3, vec!["lto"]),
+        _ => return None,
+    };
+    return Some(Profile {
+        name: name.to_string(),
+        opt_level,
+        flags: flags.into_iter().map(|flag| flag.to_string()).collect(),
+    });
+}
+
+/// Finds the profile with the name in the folder's build file, falling back to the built-in profiles
+pub fn find_profile(folder: &Path, name: &str, compiler_arguments: &CompilerArguments) -> Result<Profile, String> {
+    if has_build_function(folder, "profiles") {
+        let profiles = run_build_file::<Vec<Profile>>(folder, "profiles", compiler_arguments)
+            .ok_or_else(|| "Failed to run the build file's profiles method!".to_string())?;
+        if let Some(found) = profiles.into_iter().find(|profile| profile.name == name) {
+            return Ok(found);
+        }
+    }
+    return default_profile(name).ok_or_else(|| format!("Unknown profile {}!", name));
+}
+
+/// Applies the profile to the compiler arguments. Flags passed on the command line stay enabled.
+pub fn apply_profile(profile: &Profile, compiler_arguments: &mut CompilerArguments) -> Result<(), String> {
+    if profile.opt_level > 3 {
+        return Err(format!("Optimization level {} of profile {} must be from 0 to 3!", profile.opt_level, profile.name));
+    }
+    compiler_arguments.opt_level = profile.opt_level as u8;
+    for flag in &profile.flags {
+        match flag.as_str() {
+            "verify-ir" => compiler_arguments.verify_ir = true,
+            "debug-info" => compiler_arguments.debug_info = true,
+            "overflow-checks" => compiler_arguments.overflow_checks = true,
+            "lto" => compiler_arguments.lto = true,
+            "opt-size" => compiler_arguments.opt_size = true,
+            "no-std" => compiler_arguments.no_std = true,
+            _ => return Err(format!("Unknown flag {} in profile {}!", flag, profile.name)),
+        }
+    }
+    return Ok(());
+}
@@ -17,3 +17,55 @@ pub struct Dependency {
     /// Dependency name
     pub name: String,
 }
+
+/// A workspace of Raven projects built together, returned by a build file's workspace function
+#[derive(Debug, RavenExtern)]
+pub struct RavenWorkspace {
+    /// Workspace members
+    pub members: Vec<Member>,
+}
+
+/// A member of a workspace
+#[derive(Debug, RavenExtern)]
+pub struct Member {
+    /// Path to the member's folder, relative to the workspace
+    pub path: String,
+}
+
+/// A build profile, returned in a list by a build file's profiles function.
+/// Flags are "verify-ir", "debug-info", "overflow-checks", "lto", "opt-size", and "no-std".
+#[derive(Debug, RavenExtern)]
+pub struct Profile {
+    /// Profile name, selected with --profile=(name) or --release for "release"
+    pub name: String,
+    /// The LLVM optimization level, from 0 to 3
+    pub opt_level: u64,
+    /// The enabled flags
+    pub flags: Vec<String>,
+}
+
+/// An external program (like a Rust program) that generates Raven source, returned by a build file's build_script function
+#[derive(Debug, RavenExtern)]
+pub struct BuildScript {
+    /// The program and its arguments, run in the project's folder
+    pub command: Vec<String>,
+}
+
+/// A Raven source file, returned in a list by a build file's generate function
+#[derive(Debug, RavenExtern)]
+pub struct GeneratedFile {
+    /// Path to the file, relative to the generated source folder
+    pub path: String,
+    /// The file's source
+    pub contents: String,
+}
+
+/// A feature that can be enabled with --features, returned in a list by a build file's features function.
+/// The feature named "default" is enabled unless --no-default-features is passed.
+#[derive(Debug, RavenExtern)]
+pub struct Feature {
+    /// Feature name
+    pub name: String,
+    /// Other features this feature enables
+    pub enables: Vec<String>,
+}
@@ -0,0 +1,226 @@
+use std::fmt::{Display, Formatter};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+use std::{fmt, fs, process, thread};
+
+use data::{Arguments, CompilerArguments, RunnerSettings, SourceSet};
+use parser::FileSourceSet;
+
+use crate::coverage::{merge_lcov, record_run};
+use crate::{build_project, core_sources, library_sources, set_panic_hook, take_panic_hook};
+
+/// The exit code of an isolated test that panicked in Raven code, so it's reported as a failure instead of a crash.
+/// Panics in the compiler exit with Rust's code of 101, which are crashes.
+pub const PANICKED_EXIT_CODE: i32 = 3;
+
+/// The exit code of an isolated test that didn't compile
+pub const COMPILE_ERROR_EXIT_CODE: i32 = 4;
+
+/// Why a test didn't pass
+#[derive(Clone, Debug)]
+pub enum TestFailure {
+    /// The test returned false, panicked, or has no test function
+    Failed(String),
+    /// The test had errors, so it never ran
+    CompileError(String),
+    /// The test's process died without reporting a result, like from a segfault
+    Crashed(String),
+    /// The test was killed because it ran longer than the timeout
+    TimedOut(String),
+}
+
+impl Display for TestFailure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        return match self {
+            TestFailure::Failed(message)
+            | TestFailure::CompileError(message)
+            | TestFailure::Crashed(message)
+            | TestFailure::TimedOut(message) => write!(f, "{}", message),
+        };
+    }
+}
+
+/// Runs each test in its own process, so a test that crashes or hangs only fails itself
+pub struct TestIsolation {
+    /// The program and arguments that run a single test, which the test file's path is added to
+    pub command: Vec<String>,
+    /// How long a test can run before it's killed, or None to wait forever
+    pub timeout: Option<Duration>,
+}
+
+/// Runs every test file in the folder, returning the name of each test and the failure if it didn't pass.
+/// Each file is its own test, with a "test" function returning whether it passed.
+/// Tests run in this process unless they're isolated.
+pub fn run_tests(
+    folder: &Path,
+    compiler_arguments: &CompilerArguments,
+    isolation: Option<&TestIsolation>,
+) -> Vec<(String, Result<(), TestFailure>)> {
+    let mut output = Vec::default();
+    run_recursive(folder, compiler_arguments, isolation, &mut output);
+    return output;
+}
+
+/// Recursively searches for files in the test folder to run as a test
+fn run_recursive(
+    path: &Path,
+    compiler_arguments: &CompilerArguments,
+    isolation: Option<&TestIsolation>,
+    output: &mut Vec<(String, Result<(), TestFailure>)>,
+) {
+    let mut entries = fs::read_dir(path).unwrap().map(|entry| entry.unwrap().path()).collect::<Vec<_>>();
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            run_recursive(&path, compiler_arguments, isolation, output);
+            continue;
+        }
+        if !path.extension().is_some_and(|extension| extension == "rv") {
+            println!("File {} doesn't have the right file extension!", path.display());
+            continue;
+        }
+
+        let name = test_name(&path);
+        if !compiler_arguments.quiet {
+            println!("Running {}", name);
+        }
+        let result = match isolation {
+            Some(isolation) => run_isolated(&path, compiler_arguments, isolation),
+            None => run_test(&path, compiler_arguments, false),
+        };
+        output.push((name, result));
+    }
+}
+
+/// Gets the name of the test function in the test file
+fn test_name(path: &Path) -> String {
+    return format!("{}::test", path.file_stem().unwrap().to_str().unwrap());
+}
+
+/// Runs a single test file in this process.
+/// If the test is isolated, a panic exits with PANICKED_EXIT_CODE instead of aborting.
+pub fn run_test(path: &Path, compiler_arguments: &CompilerArguments, isolated: bool) -> Result<(), TestFailure> {
+    let name = test_name(path);
+    // A panic aborts the whole test run, so the failing test has to be reported before that
+    let test_name = name.clone();
+    set_panic_hook(Box::new(move |message| {
+        println!("Failed test {}: panicked with {}", test_name, message);
+        if isolated {
+            process::exit(PANICKED_EXIT_CODE);
+        }
+    }));
+    let mut arguments = Arguments::build_args(
+        false,
+        RunnerSettings {
+            sources: vec![],
+            // Each test only reports whether it passed, not the progress of building it
+            compiler_arguments: CompilerArguments { target: name.clone(), quiet: true, ..compiler_arguments.clone() },
+        },
+    );
+
+    let built = build_project::<bool>(&mut arguments, &mut vec![Box::new(FileSourceSet { root: path.to_path_buf() })], true);
+    // Failing tests still ran, so their lines count, but tests that didn't compile or have no test function never ran
+    if compiler_arguments.coverage && matches!(built, Ok((_, Some(_)))) {
+        record_run(&arguments.runner_settings.sources);
+    }
+    let result = match built {
+        Ok((_, Some(true))) => Ok(()),
+        Ok((_, Some(false))) => Err(TestFailure::Failed(format!("Failed test {}!", name))),
+        Ok((_, None)) => Err(TestFailure::Failed(format!("Failed to find method test in test {}", name))),
+        Err(()) => Err(TestFailure::CompileError(format!("Failed to compile test {}!", name))),
+    };
+    take_panic_hook();
+    return result;
+}
+
+/// Gets the file an isolated test writes the lines it ran to, so they can be added to the coverage of the whole run
+pub fn coverage_run_path(path: &Path, compiler_arguments: &CompilerArguments) -> PathBuf {
+    return compiler_arguments
+        .temp_folder
+        .join("coverage")
+        .join(format!("{}.lcov", path.file_stem().unwrap().to_str().unwrap()));
+}
+
+/// Runs a single test file in its own process, killing it if it runs longer than the timeout
+fn run_isolated(path: &Path, compiler_arguments: &CompilerArguments, isolation: &TestIsolation) -> Result<(), TestFailure> {
+    let name = test_name(path);
+    // A test that crashes doesn't write its coverage, so it can't be confused with an earlier run's
+    let coverage = coverage_run_path(path, compiler_arguments);
+    let _ = fs::remove_file(&coverage);
+    let mut child = match Command::new(&isolation.command[0])
+        .args(&isolation.command[1..])
+        .arg(path)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(error) => return Err(TestFailure::Crashed(format!("Failed to start test {}: {}", name, error))),
+    };
+
+    // The output is read while waiting, so a test that prints a lot can't block on a full pipe
+    let mut stdout = child.stdout.take().unwrap();
+    let mut stderr = child.stderr.take().unwrap();
+    let stdout = thread::spawn(move || {
+        let mut output = String::default();
+        let _ = stdout.read_to_string(&mut output);
+        output
+    });
+    let stderr = thread::spawn(move || {
+        let mut output = String::default();
+        let _ = stderr.read_to_string(&mut output);
+        output
+    });
+
+    let start = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait().unwrap() {
+            break Some(status);
+        }
+        if isolation.timeout.is_some_and(|timeout| start.elapsed() >= timeout) {
+            let _ = child.kill();
+            let _ = child.wait();
+            break None;
+        }
+        thread::sleep(Duration::from_millis(10));
+    };
+    let output = stdout.join().unwrap() + &stderr.join().unwrap();
+    let output = output.trim_end();
+
+    if compiler_arguments.coverage {
+        if let Ok(lcov) = fs::read_to_string(&coverage) {
+            let mut sources: Vec<Box<dyn SourceSet>> = vec![Box::new(FileSourceSet { root: path.to_path_buf() })];
+            sources.append(&mut if compiler_arguments.no_std { core_sources() } else { library_sources() });
+            merge_lcov(&lcov, &sources);
+            let _ = fs::remove_file(coverage);
+        }
+    }
+
+    return match status {
+        None => Err(TestFailure::TimedOut(format!(
+            "Test {} timed out after {}s{}",
+            name,
+            isolation.timeout.unwrap().as_secs_f64(),
+            with_output(output)
+        ))),
+        Some(status) if status.success() => {
+            if !output.is_empty() {
+                println!("{}", output);
+            }
+            Ok(())
+        }
+        // The test printed why it failed, along with anything else it printed
+        Some(status) if status.code().is_some_and(|code| code == 1 || code == PANICKED_EXIT_CODE) => {
+            Err(TestFailure::Failed(output.to_string()))
+        }
+        Some(status) if status.code() == Some(COMPILE_ERROR_EXIT_CODE) => Err(TestFailure::CompileError(output.to_string())),
+        Some(status) => Err(TestFailure::Crashed(format!("Test {} crashed ({}){}", name, status, with_output(output)))),
+    };
+}
+
+/// Formats a test's output to be added after a failure message
+fn with_output(output: &str) -> String {
+    return if output.is_empty() { String::default() } else { format!(":\n{}", output) };
+}
@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::{env, fs};
+
+use data::{Arguments, CompilerArguments, RavenExtern, RunnerSettings, SourceSet};
+use parser::script::wrap_script;
+use parser::{FileSourceSet, OverlaySourceSet, StringSourceSet};
+
+use crate::build_script::run_build_scripts;
+use crate::project::{RavenProject, RavenWorkspace};
+use crate::{build_project, InnerSourceSet, MAGPIE};
+
+/// Whether the folder's build file declares a workspace instead of a project
+pub fn is_workspace(folder: &Path) -> bool {
+    return has_build_function(folder, "workspace");
+}
+
+/// Whether the folder's build file declares a function with the name
+pub fn has_build_function(folder: &Path, function: &str) -> bool {
+    return fs::read_to_string(folder.join("build.rv"))
+        .is_ok_and(|contents| contents.contains(&format!("fn {}(", function)));
+}
+
+/// Runs a function in the folder's build file, returning its result
+pub fn run_build_file<T: RavenExtern + 'static>(
+    folder: &Path,
+    function: &str,
+    compiler_arguments: &CompilerArguments,
+) -> Option<T> {
+    let mut arguments = Arguments::build_args(
+        false,
+        RunnerSettings {
+            sources: vec![],
+            // The build file shouldn't emit anything or report its progress, only the project itself.
+            // It always runs on the host, so it keeps the std even if the project is freestanding
+            compiler_arguments: CompilerArguments {
+                target: format!("build::{}", function),
+                emit: Vec::default(),
+                quiet: true,
+                stats: false,
+                no_std: false,
+                ..compiler_arguments.clone()
+            },
+        },
+    );
+
+    return build_project::<T>(
+        &mut arguments,
+        &mut vec![Box::new(FileSourceSet { root: folder.join("build.rv") }), Box::new(InnerSourceSet { set: &MAGPIE })],
+        true,
+    )
+    .ok()?
+    .1;
+}
+
+/// Builds and runs the main function in the folder's src folder, after running the build scripts
+pub fn build_source(folder: &Path, compiler_arguments: &CompilerArguments) {
+    let source = folder.join("src");
+    if !source.exists() {
+        panic!("Source folder (src) not found in {}!", folder.display());
+    }
+
+    let mut sources: Vec<Box<dyn SourceSet>> = vec![Box::new(FileSourceSet { root: source })];
+    match run_build_scripts(folder, compiler_arguments) {
+        Ok(Some(generated)) => sources.push(Box::new(FileSourceSet { root: generated })),
+        Ok(None) => {}
+        Err(error) => {
+            println!("{}", error);
+            return;
+        }
+    }
+
+    let mut arguments = Arguments::build_args(
+        false,
+        RunnerSettings {
+            sources: vec![],
+            compiler_arguments: CompilerArguments { target: "main::main".to_string(), ..compiler_arguments.clone() },
+        },
+    );
+    let _ = build_project::<()>(&mut arguments, &mut sources, true);
+}
+
+/// Builds and runs the main function of in-memory source, like source read from stdin, as main.rv.
+/// Source without a main function is run as a script, with its top-level statements as the main function.
+pub fn build_string_source(source: &str, compiler_arguments: &CompilerArguments) {
+    let source = wrap_script(source);
+    let mut arguments = Arguments::build_args(
+        false,
+        RunnerSettings {
+            sources: vec![],
+            compiler_arguments: CompilerArguments { target: "main::main".to_string(), ..compiler_arguments.clone() },
+        },
+    );
+    let _ = build_project::<()>(&mut arguments, &mut vec![Box::new(StringSourceSet::single("main.rv", &source))], true);
+}
+
+/// Builds and runs a single file, like "magpie run script.rv" or a script starting with "#!/usr/bin/env magpie".
+/// Files without a main function are run as scripts, like build_string_source.
+pub fn build_file(file: &Path, compiler_arguments: &CompilerArguments) {
+    let contents = fs::read_to_string(file).unwrap_or_else(|_| panic!("Failed to read {}!", file.display()));
+    let name = file.file_stem().unwrap().to_str().unwrap();
+    // Errors still point to the file on disk, with the script's wrapped contents overlaid on it
+    let overlays = HashMap::from([(file.to_str().unwrap().to_string(), wrap_script(&contents))]);
+    let mut sources: Vec<Box<dyn SourceSet>> = vec![Box::new(OverlaySourceSet {
+        inner: Box::new(FileSourceSet { root: file.to_path_buf() }),
+        overlays: Arc::new(overlays),
+    })];
+
+    let mut arguments = Arguments::build_args(
+        false,
+        RunnerSettings {
+            sources: vec![],
+            // Scripts can be run from anywhere, so they don't leave a target folder in the current directory
+            compiler_arguments: CompilerArguments {
+                target: format!("{}::main", name),
+                temp_folder: env::temp_dir().join("magpie").join(name),
+                ..compiler_arguments.clone()
+            },
+        },
+    );
+    let _ = build_project::<()>(&mut arguments, &mut sources, true);
+}
+
+/// Reads the project of every member of the workspace, sorted so each member comes after the members it depends on.
+/// Dependencies that aren't members of the workspace are ignored.
+pub fn workspace_members(
+    folder: &Path,
+    workspace: &RavenWorkspace,
+    compiler_arguments: &CompilerArguments,
+) -> Result<Vec<(PathBuf, RavenProject)>, String> {
+    let mut projects = Vec::default();
+    for member in &workspace.members {
+        let path = folder.join(&member.path);
+        match run_build_file::<RavenProject>(&path, "project", compiler_arguments) {
+            Some(project) => projects.push((path, project)),
+            None => return Err(format!("No project method in build file of member {}!", member.path)),
+        }
+    }
+
+    let indices = projects.iter().enumerate().map(|(i, (_, project))| (project.name.clone(), i)).collect::<HashMap<_, _>>();
+    let mut order = Vec::default();
+    // Whether each member is being visited (false) or is done (true)
+    let mut visited = HashMap::default();
+    for i in 0..projects.len() {
+        sort_member(i, &projects, &indices, &mut visited, &mut order)?;
+    }
+
+    let mut projects = projects.into_iter().map(Some).collect::<Vec<_>>();
+    return Ok(order.into_iter().map(|i| projects[i].take().unwrap()).collect());
+}
+
+/// Adds the member to the order after all of its dependencies, erroring on dependency cycles
+fn sort_member(
+    i: usize,
+    projects: &Vec<(PathBuf, RavenProject)>,
+    indices: &HashMap<String, usize>,
+    visited: &mut HashMap<usize, bool>,
+    order: &mut Vec<usize>,
+) -> Result<(), String> {
+    match visited.get(&i) {
+        Some(true) => return Ok(()),
+        Some(false) => return Err(format!("Dependency cycle in workspace at {}!", projects[i].1.name)),
+        None => {}
+    }
+    visited.insert(i, false);
+    for dependency in &projects[i].1.dependencies {
+        if let Some(found) = indices.get(&dependency.name) {
+            sort_member(*found, projects, indices, visited, order)?;
+        }
+    }
+    visited.insert(i, true);
+    order.push(i);
+    return Ok(());
+}
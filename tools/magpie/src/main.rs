@@ -1,66 +1,431 @@
-use std::env;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use std::{env, io, process};
 
-use data::{Arguments, CompilerArguments, RunnerSettings};
-use magpie_lib::project::RavenProject;
-use magpie_lib::{build_project, InnerSourceSet, MAGPIE};
+use data::{Arguments, CompilerArguments, RunnerSettings, SourceSet};
+use magpie_lib::bindgen::{raven_to_rust, rust_to_raven};
+use magpie_lib::c_header::header_to_raven;
+use magpie_lib::coverage::{percent, write_lcov, write_report};
+use magpie_lib::editions::find_edition;
+use magpie_lib::features::resolve_features;
+use magpie_lib::profiles::{apply_profile, find_profile};
+use magpie_lib::project::{RavenProject, RavenWorkspace};
+use magpie_lib::testing::{coverage_run_path, run_test, run_tests, TestFailure, TestIsolation, COMPILE_ERROR_EXIT_CODE};
+use magpie_lib::workspace::{
+    build_file, build_source, build_string_source, is_workspace, run_build_file, workspace_members,
+};
+use magpie_lib::{build_project, library_sources};
+use parser::grammar::{export_grammar, operator_table};
 use parser::FileSourceSet;
+use syntax::mangling::demangle_text;
+use syntax::program::printer::SourceLocator;
+use syntax::program::syntax::EDITIONS;
 
 mod test;
 
+/// How many seconds a test can run before it's killed, unless --test-timeout is passed
+const DEFAULT_TEST_TIMEOUT: u64 = 60;
+
 /// Finds the Raven project/file and runs it
 fn main() {
-    let args = env::args().collect::<Vec<_>>();
+    let mut compiler_arguments = CompilerArguments {
+        compiler: "llvm".to_string(),
+        temp_folder: env::current_dir().unwrap().join("target"),
+        ..Default::default()
+    };
+
+    // Flags can be anywhere in the arguments, so they're removed before checking for commands
+    let mut args = env::args().collect::<Vec<_>>();
+    // "--features a,b" is joined into "--features=a,b" so it can be parsed like the other flags
+    while let Some(i) = args.iter().position(|arg| arg == "--features") {
+        let features = if i + 1 < args.len() { args.remove(i + 1) } else { panic!("Expected features after --features!") };
+        args[i] = format!("--features={}", features);
+    }
+    // Isolated tests are run by calling magpie again, so it needs the same flags
+    let flags = args.iter().skip(1).filter(|arg| arg.starts_with('-') && *arg != "-").cloned().collect::<Vec<_>>();
+    // Whether to test every member of the workspace, which isn't a compiler flag so it's handled here
+    let workspace = args.iter().any(|arg| arg == "--workspace");
+    // How long each test can run before it's killed, which also isn't a compiler flag
+    let timeout = match args.iter().find_map(|arg| arg.strip_prefix("--test-timeout=")) {
+        Some(seconds) => match seconds.parse::<u64>() {
+            Ok(0) => None,
+            Ok(seconds) => Some(Duration::from_secs(seconds)),
+            Err(_) => panic!("Expected a number of seconds for the test timeout, found {}!", seconds),
+        },
+        None => Some(Duration::from_secs(DEFAULT_TEST_TIMEOUT)),
+    };
+    // The profile and features, which are read from each project's build file so they're also handled here
+    let options = ProjectOptions {
+        profile: match args.iter().find_map(|arg| arg.strip_prefix("--profile=")) {
+            Some(profile) => profile.to_string(),
+            None if args.iter().any(|arg| arg == "--release") => "release".to_string(),
+            None => "debug".to_string(),
+        },
+        default_features: !args.iter().any(|arg| arg == "--no-default-features"),
+    };
+    args.retain(|arg| {
+        arg != "--workspace"
+            && arg != "--release"
+            && arg != "--no-default-features"
+            && !arg.starts_with("--profile=")
+            && !arg.starts_with("--test-timeout=")
+            && !parse_flag(arg, &mut compiler_arguments)
+    });
+
+    if args.len() > 1 && args[1] == "demangle" {
+        demangle(&args[2..]);
+        return;
+    }
+
+    // Runs a single file, which is also how "#!/usr/bin/env magpie" scripts are run
+    if args.len() > 1 && (args[1] == "run" || args[1].ends_with(".rv")) {
+        let files = if args[1] == "run" { &args[2..] } else { &args[1..] };
+        if files.len() != 1 {
+            panic!("Usage: magpie run (file.rv)");
+        }
+        build_file(Path::new(&files[0]), &compiler_arguments);
+        return;
+    }
+
+    // Runs a single test for "magpie test", which runs each test in its own process
+    if args.len() > 1 && args[1] == "run-test" {
+        run_isolated_test(&args[2..], &options, &compiler_arguments);
+        return;
+    }
+
+    if args.len() > 1 && args[1] == "impls" {
+        impls(&args[2..], &compiler_arguments);
+        return;
+    }
+
+    if args.len() > 1 && args[1] == "bindgen" {
+        bindgen(&args[2..]);
+        return;
+    }
 
     if args.len() == 2 {
+        match args[1].as_str() {
+            // Prints the grammar for editor plugins, with the operators defined in the core library
+            "grammar" => {
+                println!("{}", export_grammar(&library_sources()));
+                return;
+            }
+            // Prints the precedence table of the operators in the libraries and the current project
+            "operators" => {
+                let mut sources = library_sources();
+                let source = env::current_dir().unwrap().join("src");
+                if source.exists() {
+                    sources.push(Box::new(FileSourceSet { root: source }));
+                }
+                print!("{}", operator_table(&sources));
+                return;
+            }
+            // Reads a single file's source from stdin, like "echo 'fn main() {}' | magpie -"
+            "-" => {
+                let mut source = String::default();
+                io::stdin().read_to_string(&mut source).unwrap();
+                build_string_source(&source, &compiler_arguments);
+                return;
+            }
+            "test" => {
+                test(workspace, &flags, timeout, &options, &compiler_arguments);
+                return;
+            }
+            _ => panic!("Unknown command {}!", args[1]),
+        }
     } else if args.len() > 2 {
         panic!("Unknown extra arguments! {:?}", args);
     }
 
-    let build_path = env::current_dir().unwrap().join("build.rv");
+    if !compiler_arguments.quiet {
+        println!("Setting up build...");
+    }
+    let projects = match find_projects(&env::current_dir().unwrap(), &compiler_arguments) {
+        Some(projects) => projects,
+        None => return,
+    };
 
-    if !build_path.exists() {
+    for (folder, project) in projects {
+        let Some(compiler_arguments) = project_arguments(&folder, &options, &compiler_arguments) else {
+            return;
+        };
+        if !compiler_arguments.quiet {
+            println!("Building and running {}...", project.name);
+        }
+        build_source(&folder, &compiler_arguments);
+    }
+}
+
+/// Options read from each project's build file
+struct ProjectOptions {
+    /// The name of the build profile
+    profile: String,
+    /// Whether to enable the "default" feature
+    default_features: bool,
+}
+
+/// Applies the project's build profile, edition, and features to a copy of the compiler arguments, printing any errors
+fn project_arguments(
+    folder: &Path,
+    options: &ProjectOptions,
+    compiler_arguments: &CompilerArguments,
+) -> Option<CompilerArguments> {
+    let mut compiler_arguments = compiler_arguments.clone();
+    let applied = find_profile(folder, &options.profile, &compiler_arguments)
+        .and_then(|found| apply_profile(&found, &mut compiler_arguments))
+        .and_then(|_| find_edition(folder, &compiler_arguments))
+        .and_then(|edition| {
+            compiler_arguments.edition = edition;
+            resolve_features(folder, &compiler_arguments.features, options.default_features, &compiler_arguments)
+        });
+    return match applied {
+        Ok(features) => {
+            compiler_arguments.features = features;
+            Some(compiler_arguments)
+        }
+        Err(error) => {
+            println!("{}", error);
+            None
+        }
+    };
+}
+
+/// Finds the folder and project of every project to build, in dependency order if the folder is a workspace
+fn find_projects(folder: &Path, compiler_arguments: &CompilerArguments) -> Option<Vec<(PathBuf, RavenProject)>> {
+    if !folder.join("build.rv").exists() {
         println!("Build file not found!");
+        return None;
+    }
+
+    if !is_workspace(folder) {
+        return match run_build_file::<RavenProject>(folder, "project", compiler_arguments) {
+            Some(project) => Some(vec![(folder.to_path_buf(), project)]),
+            None => {
+                println!("No project method in build file!");
+                None
+            }
+        };
+    }
+
+    let workspace = match run_build_file::<RavenWorkspace>(folder, "workspace", compiler_arguments) {
+        Some(workspace) => workspace,
+        None => {
+            println!("No workspace method in build file!");
+            return None;
+        }
+    };
+    return match workspace_members(folder, &workspace, compiler_arguments) {
+        Ok(members) => Some(members),
+        Err(error) => {
+            println!("{}", error);
+            None
+        }
+    };
+}
+
+/// Runs the tests in the project's test folder, or in every member's test folder for workspaces.
+/// Each test runs in its own process with the same flags, so a test that crashes or hangs only fails itself.
+fn test(
+    workspace: bool,
+    flags: &[String],
+    timeout: Option<Duration>,
+    options: &ProjectOptions,
+    compiler_arguments: &CompilerArguments,
+) {
+    let folder = env::current_dir().unwrap();
+    let folders = if workspace {
+        match find_projects(&folder, compiler_arguments) {
+            Some(projects) => projects.into_iter().map(|(folder, _)| folder).collect(),
+            None => return,
+        }
+    } else {
+        vec![folder]
+    };
+
+    let mut results = Vec::default();
+    for folder in folders {
+        let tests = folder.join("test");
+        if tests.exists() {
+            let Some(compiler_arguments) = project_arguments(&folder, options, compiler_arguments) else {
+                return;
+            };
+            let mut command = vec![env::current_exe().unwrap().to_string_lossy().to_string()];
+            command.extend(flags.iter().cloned());
+            command.push("run-test".to_string());
+            command.push(folder.to_string_lossy().to_string());
+            let isolation = TestIsolation { command, timeout };
+            results.extend(run_tests(&tests, &compiler_arguments, Some(&isolation)));
+        }
+    }
+
+    let (mut failed, mut crashed, mut timed_out) = (0, 0, 0);
+    for (name, result) in &results {
+        match result {
+            Ok(()) if compiler_arguments.quiet => {}
+            Ok(()) => println!("{} passed", name),
+            Err(error) => println!("{}", error),
+        }
+        match result {
+            Ok(()) => {}
+            Err(TestFailure::Failed(_) | TestFailure::CompileError(_)) => failed += 1,
+            Err(TestFailure::Crashed(_)) => crashed += 1,
+            Err(TestFailure::TimedOut(_)) => timed_out += 1,
+        }
+    }
+    println!("{} tests, {} failed, {} crashed, {} timed out", results.len(), failed, crashed, timed_out);
+    if compiler_arguments.coverage {
+        let folder = compiler_arguments.temp_folder.join("coverage");
+        let (hit, found) = write_report(&folder);
+        println!("Coverage: {}/{} lines ({:.1}%), report written to {}", hit, found, percent(hit, found), folder.display());
+    }
+    if failed + crashed + timed_out > 0 {
+        process::exit(1);
+    }
+}
+
+/// Runs a single test file in this process for "magpie test", exiting with 1 if it fails.
+/// The project folder is passed so the test is built with the project's profile and features.
+fn run_isolated_test(args: &[String], options: &ProjectOptions, compiler_arguments: &CompilerArguments) {
+    if args.len() != 2 {
+        panic!("Usage: magpie run-test (project folder) (test file)");
+    }
+    let Some(compiler_arguments) = project_arguments(Path::new(&args[0]), options, compiler_arguments) else {
+        process::exit(1);
+    };
+    let path = Path::new(&args[1]);
+    let result = run_test(path, &compiler_arguments, true);
+    if compiler_arguments.coverage {
+        write_lcov(&coverage_run_path(path, &compiler_arguments));
+    }
+    match result {
+        Ok(()) => {}
+        Err(TestFailure::CompileError(error)) => {
+            println!("{}", error);
+            process::exit(COMPILE_ERROR_EXIT_CODE);
+        }
+        Err(error) => {
+            println!("{}", error);
+            process::exit(1);
+        }
+    }
+}
+
+/// Demangles the given symbols, or every symbol in stdin if none are given
+fn demangle(symbols: &[String]) {
+    if !symbols.is_empty() {
+        for symbol in symbols {
+            println!("{}", demangle_text(symbol));
+        }
         return;
     }
 
+    for line in io::stdin().lines() {
+        println!("{}", demangle_text(&line.unwrap()));
+    }
+}
+
+/// Prints the bindings for the given direction and path
+fn bindgen(args: &[String]) {
+    if args.len() != 2 {
+        panic!("Usage: magpie bindgen (rust|raven|header) (path)");
+    }
+    let path = Path::new(&args[1]);
+    match args[0].as_str() {
+        // Raven externs for the functions a Rust crate exports
+        "rust" => print!("{}", rust_to_raven(path)),
+        // A Rust extern block for the functions a Raven project exports
+        "raven" => match raven_to_rust(path) {
+            Ok(found) => print!("{}", found),
+            Err(error) => panic!("Failed to generate bindings: {}!", error),
+        },
+        // Raven externs and structs for a C header
+        "header" => print!("{}", header_to_raven(path)),
+        _ => panic!("Unknown bindgen source {}, expected rust, raven, or header!", args[0]),
+    }
+}
+
+/// Prints every implementation of a trait, or every trait implemented by a type, in the libraries and current project
+fn impls(args: &[String], compiler_arguments: &CompilerArguments) {
+    if args.len() != 1 {
+        panic!("Usage: magpie impls (trait or type)");
+    }
+    let mut sources: Vec<Box<dyn SourceSet>> = Vec::default();
+    let source = env::current_dir().unwrap().join("src");
+    if source.exists() {
+        sources.push(Box::new(FileSourceSet { root: source }));
+    }
     let mut arguments = Arguments::build_args(
         false,
         RunnerSettings {
             sources: vec![],
-            compiler_arguments: CompilerArguments {
-                target: "build::project".to_string(),
-                compiler: "llvm".to_string(),
-                temp_folder: env::current_dir().unwrap().join("target"),
-            },
+            compiler_arguments: CompilerArguments { quiet: true, ..compiler_arguments.clone() },
         },
     );
-
-    println!("Setting up build...");
-    let project = match build_project::<RavenProject>(
-        &mut arguments,
-        &mut vec![Box::new(FileSourceSet { root: build_path }), Box::new(InnerSourceSet { set: &MAGPIE })],
-        true,
-    ) {
-        Ok((_, found)) => match found {
-            Some(found) => RavenProject::from(found),
-            None => {
-                println!("No project method in build file!");
-                return;
-            }
-        },
-        Err(()) => return,
+    let Ok((syntax, _)) = build_project::<()>(&mut arguments, &mut sources, false) else {
+        process::exit(1);
     };
 
-    arguments.runner_settings.compiler_arguments.target = "main::main".to_string();
-
-    let source = env::current_dir().unwrap().join("src");
-
-    if !source.exists() {
-        panic!("Source folder (src) not found!");
+    let locked = syntax.lock();
+    let implementors = locked.implementors_of(&args[0]);
+    let (implementations, trait_name) =
+        if implementors.is_empty() { (locked.traits_of(&args[0]), false) } else { (implementors, true) };
+    if implementations.is_empty() {
+        println!("No implementations found for {}", args[0]);
+        return;
+    }
+    let locator = SourceLocator::new(&sources);
+    for implementation in implementations {
+        let found = if trait_name { &implementation.base } else { &implementation.target };
+        println!("{} ({})", found, locator.location(&implementation.span));
     }
+}
 
-    println!("Building and running {}...", project.name);
-    match build_project::<()>(&mut arguments, &mut vec![Box::new(FileSourceSet { root: source })], true) {
-        _ => {}
+/// Parses a single flag into the compiler arguments, returning false if the argument isn't a flag
+fn parse_flag(arg: &str, compiler_arguments: &mut CompilerArguments) -> bool {
+    if let Some(emitting) = arg.strip_prefix("--emit=") {
+        for emit in emitting.split(',') {
+            match emit {
+                "checked-ast" | "metadata" => compiler_arguments.emit.push(emit.to_string()),
+                _ => panic!("Unknown emit type {}!", emit),
+            }
+        }
+    } else if let Some(features) = arg.strip_prefix("--features=") {
+        compiler_arguments.features.extend(features.split(',').filter(|feature| !feature.is_empty()).map(str::to_string));
+    } else if arg == "--quiet" {
+        compiler_arguments.quiet = true;
+    } else if let Some(limit) = arg.strip_prefix("--recursion-limit=") {
+        compiler_arguments.recursion_limit = match limit.parse() {
+            Ok(limit) if limit > 0 => limit,
+            _ => panic!("Expected a positive recursion limit, found {}!", limit),
+        };
+    } else if let Some(edition) = arg.strip_prefix("--edition=") {
+        compiler_arguments.edition = match edition.parse() {
+            Ok(edition) if EDITIONS.contains(&edition) => edition,
+            _ => panic!("Unknown edition {}, expected one of {:?}!", edition, EDITIONS),
+        };
+    } else if arg == "--stats" {
+        compiler_arguments.stats = true;
+    } else if arg == "--dump-instantiations" {
+        compiler_arguments.dump_instantiations = true;
+    } else if arg == "--lto" {
+        compiler_arguments.lto = true;
+    } else if arg == "--opt-size" || arg == "-Oz" {
+        compiler_arguments.opt_size = true;
+    } else if arg == "--debug-info" || arg == "-g" {
+        compiler_arguments.debug_info = true;
+    } else if arg == "--no-std" {
+        compiler_arguments.no_std = true;
+    } else if arg == "--heap-profile" {
+        compiler_arguments.heap_profile = true;
+    } else if arg == "--leak-check" {
+        compiler_arguments.leak_check = true;
+    } else if arg == "--coverage" {
+        compiler_arguments.coverage = true;
+    } else if arg.starts_with("--") {
+        panic!("Unknown flag {}!", arg);
+    } else {
+        return false;
     }
+    return true;
 }
@@ -1,63 +1,348 @@
 #[cfg(test)]
 mod test {
-    use data::{Arguments, CompilerArguments, RunnerSettings};
-    use magpie_lib::build_project;
-    use parser::FileSourceSet;
+    use data::{Arguments, CompilerArguments, RunnerSettings, SourceSet};
+    use magpie_lib::c_header::header_to_raven;
+    use magpie_lib::testing::{run_tests, TestFailure};
+    use magpie_lib::{build_project, register_host_function};
+    use parser::references::{definition_at, find_references, ReferenceType};
+    use parser::script::wrap_script;
+    use parser::StringSourceSet;
+    use std::env;
     use std::path::PathBuf;
-    use std::{env, fs, path};
+    use syntax::mangling::{demangle, mangle};
+    use syntax::metadata::CrateMetadata;
+    use syntax::program::syntax::EDITIONS;
 
     /// Main test
     #[test]
     pub fn test_magpie() {
         let test_folder: PathBuf = ["..", "..", "lib", "test", "test"].iter().collect();
-        test_recursive(test_folder);
-    }
-
-    /// Recursively searches for files in the test folder to run as a test
-    fn test_recursive(path: PathBuf) {
-        for entry in fs::read_dir(path).unwrap() {
-            let entry = entry.unwrap();
-            let path = entry.path();
-            if path.is_file() {
-                // supposedly, this is a test file
-                let mod_path = path.to_str().unwrap().replace(path::MAIN_SEPARATOR, "::");
-                if !mod_path.ends_with(".rv") {
-                    println!("File {} doesn't have the right file extension!", mod_path);
-                    continue;
-                }
-                let mod_path =
-                    format!("{}::test", &mod_path[path.parent().unwrap().to_str().unwrap().len() + 6..mod_path.len() - 3]);
-                println!("Running {}", mod_path);
-                let mut arguments = Arguments::build_args(
-                    false,
-                    RunnerSettings {
-                        sources: vec![],
-                        compiler_arguments: CompilerArguments {
-                            compiler: "llvm".to_string(),
-                            target: mod_path.clone(),
-                            temp_folder: env::current_dir().unwrap().join("target"),
-                        },
-                    },
-                );
-
-                match build_project::<bool>(&mut arguments, &mut vec![Box::new(FileSourceSet { root: path })], true) {
-                    Ok((_, inner)) => match inner {
-                        Some(found) => {
-                            if !found {
-                                assert!(false, "Failed test {}!", mod_path)
-                            }
-                        }
-                        None => assert!(false, "Failed to find method test in test {}", mod_path),
-                    },
-                    Err(()) => assert!(false, "Failed to compile test {}!", mod_path),
-                }
-            } else if path.is_dir() {
-                // supposedly, this is a sub-directory in the test folder
-                test_recursive(path);
-            } else {
-                println!("Unknown element in test folder!");
-                continue;
+        let compiler_arguments = CompilerArguments {
+            compiler: "llvm".to_string(),
+            temp_folder: env::current_dir().unwrap().join("target"),
+            // Nothing is freed yet, so leak checking is opt-in until Raven has a deallocation strategy
+            leak_check: env::var("RAVEN_LEAK_CHECK").is_ok(),
+            ..Default::default()
+        };
+
+        let failed = run_tests(&test_folder, &compiler_arguments, None)
+            .into_iter()
+            .filter_map(|(_, result)| result.err().map(|error| error.to_string()))
+            .collect::<Vec<_>>();
+        assert!(failed.is_empty(), "{}", failed.join("\n"));
+    }
+
+    /// Runs each edition's conformance tests with that edition. The tests in pass have to pass, so programs written for an
+    /// older edition keep compiling, and the tests in fail can't compile, so each edition's changes are really made
+    #[test]
+    pub fn test_conformance() {
+        let mut failed = Vec::default();
+        for edition in EDITIONS {
+            let test_folder: PathBuf = ["..", "..", "lib", "conformance", &edition.to_string()].iter().collect();
+            let compiler_arguments = CompilerArguments {
+                compiler: "llvm".to_string(),
+                temp_folder: env::current_dir().unwrap().join("target"),
+                edition,
+                ..Default::default()
+            };
+            failed.extend(
+                run_tests(&test_folder.join("pass"), &compiler_arguments, None)
+                    .into_iter()
+                    .filter_map(|(_, result)| result.err().map(|error| format!("Edition {}: {}", edition, error))),
+            );
+
+            let failing = test_folder.join("fail");
+            if failing.exists() {
+                failed.extend(run_tests(&failing, &compiler_arguments, None).into_iter().filter_map(|(name, result)| {
+                    match result {
+                        Err(TestFailure::CompileError(_)) => None,
+                        _ => Some(format!("Edition {}: {} compiled, but shouldn't have!", edition, name)),
+                    }
+                }));
             }
         }
+        assert!(failed.is_empty(), "{}", failed.join("\n"));
+    }
+
+    /// Checks names with paths in their generics, which are after the $, mangle as one generics segment
+    #[test]
+    pub fn test_mangling() {
+        assert_eq!(mangle("main::foo$main::Box"), "_RV4main3fooG13main_3a_3aBox");
+        assert_eq!(demangle(&mangle("main::foo$main::Box")).unwrap(), "main::foo<main::Box>");
+        assert_eq!(
+            demangle(&mangle("math::Add<math::u64>::add$main::Box_u64")).unwrap(),
+            "math::Add<math::u64>::add<main::Box_u64>"
+        );
+    }
+
+    /// Checks quotes and brackets in char and string literals don't split a script's statements
+    #[test]
+    pub fn test_script_literals() {
+        let script = "let a = '\"';\nlet b = '{';\nfn helper() {\n}\nlet c = \"};\" + '}';\n";
+        assert_eq!(
+            wrap_script(script),
+            "fn main() {let a = '\"';\nlet b = '{';\n             \n \nlet c = \"};\" + '}';\n\n}\nfn helper() {\n}\n"
+        );
+    }
+
+    /// Checks the main function a script is wrapped in starts after its shebang line
+    #[test]
+    pub fn test_script_shebang() {
+        assert_eq!(
+            wrap_script("#!/usr/bin/env magpie\nlet a = 1;\n"),
+            "#!/usr/bin/env magpie\nfn main() {let a = 1;\n\n}\n"
+        );
+    }
+
+    /// Checks references are found across files, skipping a same-named struct in another file and a variable
+    /// shadowing a function, with columns counted in UTF-16 like LSP positions
+    #[test]
+    pub fn test_references() {
+        let main = "import shapes::Point;\nimport shapes;\n\nfn test() -> bool {\n    \
+            let bird = \"🐦\"; let point = new Point { x: 1 };\n    let make = 2;\n    \
+            let made = make(point.x);\n    return made.x == make;\n}\n";
+        let shapes =
+            "pub struct Point {\n    pub x: u64;\n}\n\npub fn make(x: u64) -> Point {\n    return new Point { x: x };\n}\n";
+        let other = "pub struct Point {\n    pub y: u64;\n}\n";
+        let sources: Vec<Box<dyn SourceSet>> = vec![Box::new(StringSourceSet {
+            files: vec![
+                ("main.rv".to_string(), main.to_string()),
+                ("shapes.rv".to_string(), shapes.to_string()),
+                ("other.rv".to_string(), other.to_string()),
+            ],
+        })];
+        let mut arguments = Arguments::build_args(
+            false,
+            RunnerSettings {
+                sources: vec![],
+                compiler_arguments: CompilerArguments {
+                    compiler: "llvm".to_string(),
+                    record_call_sites: true,
+                    ..Default::default()
+                },
+            },
+        );
+        let syntax =
+            build_project::<()>(&mut arguments, &mut sources.iter().map(|inner| inner.cloned()).collect(), false).unwrap().0;
+        let syntax = syntax.lock();
+
+        let points = find_references(&sources, &syntax, "shapes::Point");
+        let mut found = points.iter().map(|reference| (reference.file.as_str(), reference.start)).collect::<Vec<_>>();
+        found.sort();
+        // The emoji is four bytes but two UTF-16 code units, so the column is two less than the byte offset
+        let line = main.lines().nth(4).unwrap();
+        let column = line.find("Point").unwrap() as u32 - 2;
+        assert_eq!(
+            found,
+            vec![
+                ("main.rv", (1, 15)),
+                ("main.rv", (5, column)),
+                ("shapes.rv", (1, 11)),
+                ("shapes.rv", (5, 23)),
+                ("shapes.rv", (6, 15))
+            ]
+        );
+        assert!(points.iter().any(|reference| reference.reference_type == ReferenceType::Definition));
+
+        // The variable named make shadows the function, so only the call references it
+        let makes = find_references(&sources, &syntax, "shapes::make");
+        let mut found = makes.iter().map(|reference| (reference.file.as_str(), reference.start)).collect::<Vec<_>>();
+        found.sort();
+        assert_eq!(found, vec![("main.rv", (7, 15)), ("shapes.rv", (5, 7))]);
+        let variable = main.find("let make").unwrap() + 4;
+        assert_eq!(definition_at(&sources, &syntax, "main.rv", variable), None);
+        let call = main.find("make(").unwrap();
+        assert_eq!(definition_at(&sources, &syntax, "main.rv", call), Some("shapes::make".to_string()));
+    }
+
+    /// Checks a registered Rust closure is called by the extern function with its full name, and an extern declared
+    /// with different types than the closure, like a struct instead of a str, fails to compile instead of being called
+    /// with the wrong values
+    #[test]
+    pub fn test_host_functions() {
+        register_host_function("main::host_add", |first: u64, second: u64| first + second);
+        register_host_function("main::host_mismatched", |value: f64| value);
+        register_host_function("main::host_length", |text: String| text.len() as u64);
+        let run = |source: &str| {
+            let mut arguments = Arguments::build_args(
+                false,
+                RunnerSettings {
+                    sources: vec![],
+                    compiler_arguments: CompilerArguments {
+                        compiler: "llvm".to_string(),
+                        target: "main::main".to_string(),
+                        temp_folder: env::current_dir().unwrap().join("target"),
+                        ..Default::default()
+                    },
+                },
+            );
+            build_project::<bool>(&mut arguments, &mut vec![Box::new(StringSourceSet::single("main.rv", source))], true)
+                .map(|(_, result)| result)
+        };
+
+        let called = "extern fn host_add(first: u64, second: u64) -> u64;\n\n\
+            fn main() -> bool {\n    return host_add(2, 3) == 5;\n}\n";
+        assert_eq!(run(called), Ok(Some(true)));
+
+        let mismatched = "extern fn host_mismatched(value: u64) -> u64;\n\n\
+            fn main() -> bool {\n    return host_mismatched(1) == 1;\n}\n";
+        assert!(run(mismatched).is_err());
+
+        // Structs are passed as pointers too, but only strs can be read as strings
+        let struct_argument = "struct Point {\n    x: u64;\n}\n\nextern fn host_length(point: Point) -> u64;\n\n\
+            fn main() -> bool {\n    return host_length(new Point { x: 1 }) == 1;\n}\n";
+        assert!(run(struct_argument).is_err());
+    }
+
+    /// Checks a struct update's base has to be the same type as the struct being created, even if it has the same fields
+    #[test]
+    pub fn test_struct_update_base() {
+        let mut arguments = Arguments::build_args(
+            false,
+            RunnerSettings {
+                sources: vec![],
+                compiler_arguments: CompilerArguments {
+                    compiler: "llvm".to_string(),
+                    target: "main::main".to_string(),
+                    temp_folder: env::current_dir().unwrap().join("target"),
+                    ..Default::default()
+                },
+            },
+        );
+        let source = "struct Point {\n    x: u64;\n}\n\nstruct Size {\n    x: u64;\n}\n\n\
+            fn main() -> bool {\n    let size = new Size { x: 1 };\n    let point = new Point { ..size };\n    \
+            return point.x == 1;\n}\n";
+        let built =
+            build_project::<bool>(&mut arguments, &mut vec![Box::new(StringSourceSet::single("main.rv", source))], false);
+        assert!(built.is_err());
+    }
+
+    /// Checks a program compiled with debug info passes LLVM's verifier, which rejects calls without a line in functions
+    /// with debug info, and still runs
+    #[test]
+    pub fn test_debug_info() {
+        let mut arguments = Arguments::build_args(
+            false,
+            RunnerSettings {
+                sources: vec![],
+                compiler_arguments: CompilerArguments {
+                    compiler: "llvm".to_string(),
+                    target: "main::main".to_string(),
+                    temp_folder: env::current_dir().unwrap().join("target"),
+                    debug_info: true,
+                    verify_ir: true,
+                    ..Default::default()
+                },
+            },
+        );
+        let source = "fn double(value: u64) -> u64 {\n    return value * 2;\n}\n\n\
+            fn main() -> bool {\n    let mut total = 0;\n    for i in 0..4 {\n        total += double(i);\n    }\n    \
+            return total == 12;\n}\n";
+        let built =
+            build_project::<bool>(&mut arguments, &mut vec![Box::new(StringSourceSet::single("main.rv", source))], true);
+        assert_eq!(built.map(|(_, result)| result), Ok(Some(true)));
+    }
+
+    /// Checks a crate's metadata reads back the same as it was written
+    #[test]
+    pub fn test_metadata() {
+        let mut arguments = Arguments::build_args(
+            false,
+            RunnerSettings {
+                sources: vec![],
+                compiler_arguments: CompilerArguments { compiler: "llvm".to_string(), ..Default::default() },
+            },
+        );
+        let source = "pub struct Point<T> {\n    pub x: T;\n}\n\n\
+            pub fn make<T>(x: T) -> Point<T> {\n    return new Point<T> { x: x };\n}\n";
+        let syntax =
+            build_project::<()>(&mut arguments, &mut vec![Box::new(StringSourceSet::single("main.rv", source))], false)
+                .unwrap()
+                .0;
+        let metadata = CrateMetadata::from_syntax("main".to_string(), &syntax.lock());
+        assert_eq!(CrateMetadata::read(&metadata.write()), Ok(metadata));
+    }
+
+    /// Checks format specs the value's type can't use, like a precision on an integer or a base on a float,
+    /// fail to compile, along with specs that can't be parsed
+    #[test]
+    pub fn test_format_specs() {
+        let build = |value: &str, spec: &str| {
+            let mut arguments = Arguments::build_args(
+                false,
+                RunnerSettings {
+                    sources: vec![],
+                    compiler_arguments: CompilerArguments {
+                        compiler: "llvm".to_string(),
+                        target: "main::main".to_string(),
+                        temp_folder: env::current_dir().unwrap().join("target"),
+                        ..Default::default()
+                    },
+                },
+            );
+            let source = format!(
+                "fn main() -> bool {{\n    let value = {};\n    return f\"{{value:{}}}\" != \"\";\n}}\n",
+                value, spec
+            );
+            build_project::<bool>(&mut arguments, &mut vec![Box::new(StringSourceSet::single("main.rv", &source))], true)
+                .map(|(_, result)| result)
+        };
+
+        assert_eq!(build("42", ">4x"), Ok(Some(true)));
+        assert_eq!(build("1.5", "08.3"), Ok(Some(true)));
+        assert!(build("42", ".2").is_err());
+        assert!(build("1.5", "x").is_err());
+        assert!(build("42", "^4").is_err());
+        assert!(build("1.5", ".2?").is_err());
+    }
+
+    /// Checks a --no-std program with an allocator and panic handler passes the freestanding checks, and one reaching
+    /// a #[hosted] function, like formatting a float, fails them
+    #[test]
+    pub fn test_freestanding() {
+        let check = |body: &str| {
+            let mut arguments = Arguments::build_args(
+                false,
+                RunnerSettings {
+                    sources: vec![],
+                    compiler_arguments: CompilerArguments {
+                        compiler: "llvm".to_string(),
+                        target: "main::main".to_string(),
+                        temp_folder: env::current_dir().unwrap().join("target"),
+                        no_std: true,
+                        ..Default::default()
+                    },
+                },
+            );
+            let source = format!(
+                "#[allocator]\nextern fn alloc(size: u64) -> u64;\n\n#[panic_handler]\nextern fn on_panic(message: str);\n\n\
+                struct Point {{\n    x: u64;\n    y: f64;\n}}\n\n\
+                fn main() -> bool {{\n    let point = new Point {{ x: 1, y: 1.5 }};\n    {}\n}}\n",
+                body
+            );
+            build_project::<bool>(&mut arguments, &mut vec![Box::new(StringSourceSet::single("main.rv", &source))], false)
+                .map(|_| ())
+        };
+
+        assert_eq!(check("return f\"{point.x}\" == \"1\";"), Ok(()));
+        assert!(check("return f\"{point.y}\" == \"1.5\";").is_err());
+    }
+
+    /// Checks C headers are converted with struct pointers as addresses, unnamed struct arguments as types,
+    /// and fields declaring multiple names split into one field per name
+    #[test]
+    pub fn test_c_header() {
+        let header: PathBuf = ["..", "..", "lib", "test", "headers", "points.h"].iter().collect();
+        let output = header_to_raven(&header);
+        assert_eq!(
+            output.split_once('\n').unwrap().1,
+            "// Raven structs start with a type id, so pointers to these structs are passed as u64 addresses instead\n\
+            pub struct point {\n    pub x: i32;\n    pub y: i32;\n    pub data: u64;\n    pub count: i32;\n}\n\
+            pub extern fn point_new(x: i32, y: i32) -> u64;\n\
+            pub extern fn point_free(arg0: u64);\n\
+            pub extern fn point_sum(p: u64) -> i32;\n\
+            // Skipped by_value: unsupported type struct point\n\
+            pub extern fn count_points(points: u64, arg1: u64) -> u64;\n"
+        );
     }
 }